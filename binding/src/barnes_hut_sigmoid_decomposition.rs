@@ -1,13 +1,16 @@
 use crate::numpy_decomposition::NumpyDecomposition;
 use crate::traits::*;
 use crate::*;
-use dimensional_reduction::basic_iterative_decomposition::BasicIterativeDecomposition;
+use dimensional_reduction::basic_iterative_decomposition::{
+    BasicIterativeDecomposition, IterativeParameters,
+};
 use dimensional_reduction::BarnesHutSigmoidDecomposition as BarnesHutSigmoidDecompositionRust;
-use pyo3::exceptions::PyValueError;
+use dimensional_reduction::similarities::SimilarityKernel;
+use dimensional_reduction::target_similarities::TargetSimilarityKernel;
 use pyo3::types::PyDict;
 use pyo3::*;
 
-impl FromPyDict for BarnesHutSigmoidDecompositionRust {
+impl FromPyDict for BarnesHutSigmoidDecompositionRust<SimilarityKernel, TargetSimilarityKernel> {
     fn from_pydict(py_kwargs: Option<&types::PyDict>) -> PyResult<Self>
     where
         Self: Sized,
@@ -15,15 +18,27 @@ impl FromPyDict for BarnesHutSigmoidDecompositionRust {
         let py = pyo3::Python::acquire_gil();
         let kwargs = normalize_kwargs!(py_kwargs, py.python());
 
-        Ok(Self::new(
+        pe!(Self::new(
             pe!(BasicIterativeDecomposition::new(
-                extract_value_rust_result!(kwargs, "iterations", usize),
-                extract_value_rust_result!(kwargs, "learning_rate", f32),
                 "Barnes-Hut Sigmoid Decomposition",
-                extract_value_rust_result!(kwargs, "random_state", u64),
-                extract_value_rust_result!(kwargs, "verbose", bool),
+                IterativeParameters {
+                    iterations: extract_value_rust_result!(kwargs, "iterations", usize),
+                    learning_rate: extract_value_rust_result!(kwargs, "learning_rate", f32),
+                    learning_rate_schedule: extract_learning_rate_schedule!(kwargs),
+                    tolerance: extract_value_rust_result!(kwargs, "tolerance", f32),
+                    patience: extract_value_rust_result!(kwargs, "patience", usize),
+                    initialization: extract_initialization!(kwargs),
+                    deterministic: extract_value_rust_result!(kwargs, "deterministic", bool),
+                    optimizer: extract_optimizer!(kwargs),
+                    random_state: extract_value_rust_result!(kwargs, "random_state", u64),
+                    verbose: extract_value_rust_result!(kwargs, "verbose", bool),
+                },
             ))?,
+            extract_similarity!(kwargs),
+            extract_target_similarity!(kwargs),
             extract_value_rust_result!(kwargs, "depth", usize),
+            extract_value_rust_result!(kwargs, "theta", f32),
+            extract_value_rust_result!(kwargs, "leaf_capacity", usize),
         ))
     }
 }
@@ -31,15 +46,15 @@ impl FromPyDict for BarnesHutSigmoidDecompositionRust {
 ///
 #[pyclass]
 #[derive(Clone)]
-#[pyo3(text_signature = "(*, iterations, learning_rate, random_state, verbose)")]
+#[pyo3(text_signature = "(*, iterations, learning_rate, learning_rate_schedule, decay_rate, step_size, warmup_iterations, tolerance, patience, initialization, initialization_scale, deterministic, optimizer, momentum, beta_1, beta_2, epsilon, similarity, bandwidth, target_similarity, minimum_distance, spread, random_state, verbose, depth, theta, leaf_capacity)")]
 pub struct BarnesHutSigmoidDecomposition {
-    inner: BarnesHutSigmoidDecompositionRust,
+    inner: BarnesHutSigmoidDecompositionRust<SimilarityKernel, TargetSimilarityKernel>,
 }
 
-impl DimensionalReductionBinding<BarnesHutSigmoidDecompositionRust>
+impl DimensionalReductionBinding<BarnesHutSigmoidDecompositionRust<SimilarityKernel, TargetSimilarityKernel>>
     for BarnesHutSigmoidDecomposition
 {
-    fn get_basic_dimensionality_reduction(&self) -> &BarnesHutSigmoidDecompositionRust {
+    fn get_basic_dimensionality_reduction(&self) -> &BarnesHutSigmoidDecompositionRust<SimilarityKernel, TargetSimilarityKernel> {
         &self.inner
     }
 }
@@ -52,21 +67,100 @@ impl BarnesHutSigmoidDecomposition {
     ///
     /// Parameters
     /// ------------------------
+    /// learning_rate_schedule: str = "constant"
+    ///     The schedule scaling the learning rate in each epoch, one of "constant",
+    ///     "linear", "exponential", "step" and "cosine". By default, "constant".
+    /// decay_rate: Optional[float] = None
+    ///     The factor of the "exponential" and "step" schedules. By default, 0.95 for
+    ///     the "exponential" schedule and 0.5 for the "step" schedule.
+    /// step_size: int = 10
+    ///     The number of epochs between the decays of the "step" schedule. By default, 10.
+    /// warmup_iterations: int = 0
+    ///     The number of epochs over which the learning rate grows linearly to its
+    ///     full value before the schedule decays it. By default, 0.
+    /// tolerance: Optional[float] = None
+    ///     The relative change of the epoch loss under which an epoch is considered
+    ///     not to improve the model. By default, no early stopping is done.
+    /// patience: int = 5
+    ///     The number of consecutive epochs without improvement after which
+    ///     the training is stopped. By default, 5.
+    /// initialization: str = "random_uniform"
+    ///     The strategy used to initialize the embedding, one of "random_uniform",
+    ///     "random_gaussian", "pca", "spectral" and "provided". By default, "random_uniform".
+    /// initialization_scale: float = 1e-4
+    ///     The standard deviation of the "random_gaussian" initialization. By default, 1e-4.
+    ///     It must not be provided with the other initializations.
+    /// deterministic: bool = False
+    ///     Whether to compute the updates of each epoch from the embedding at its start
+    ///     and apply them in a fixed order, so that the results do not depend on the
+    ///     number of threads, at the cost of memory and speed. By default, False.
+    /// optimizer: str = "sgd"
+    ///     The rule used to update the embedding, one of "sgd", "momentum", "adam" and
+    ///     "adagrad". The optimizers other than "sgd" accumulate the updates of each epoch
+    ///     from the embedding at its start, as when deterministic. By default, "sgd".
+    /// momentum: float = 0.9
+    ///     The momentum of the "momentum" optimizer. By default, 0.9.
+    /// beta_1: float = 0.9
+    ///     The decay of the first moment of the "adam" optimizer. By default, 0.9.
+    /// beta_2: float = 0.999
+    ///     The decay of the second moment of the "adam" optimizer. By default, 0.999.
+    /// epsilon: float = 1e-8
+    ///     The value added to the denominators of the "adam" and "adagrad" optimizers
+    ///     for numerical stability. By default, 1e-8.
+    /// similarity: str = "standardized_dot"
+    ///     The similarity between the original samples, one of "standardized_dot", "cosine",
+    ///     "gaussian", "manhattan", "tanimoto" and "hamming". By default, "standardized_dot".
+    /// bandwidth: float = 1.0
+    ///     The bandwidth of the "gaussian" and "manhattan" similarities. By default, 1.0.
+    /// target_similarity: str = "sigmoid_dot"
+    ///     The similarity between the embedded samples, one of "sigmoid_dot", "student_t",
+    ///     "umap" and "gaussian". The distance-based similarities produce layouts preserving
+    ///     the distances, while the sigmoid of the dot product produces radial layouts.
+    ///     By default, "sigmoid_dot".
+    /// minimum_distance: float = 0.1
+    ///     The distance under which the embedded samples are considered identical
+    ///     by the "umap" target similarity. By default, 0.1.
+    /// spread: float = 1.0
+    ///     The scale of the decay of the "umap" target similarity. By default, 1.0.
     /// random_state: int = 42
     ///     The random state to reproduce the model initialization and training. By default, 42.
+    /// depth: Optional[int] = None
+    ///     The depth of the grid used to approximate the far away samples, or its maximum
+    ///     depth when a leaf capacity is provided. Each layer splits every axis of 2D and 3D
+    ///     embeddings, and a single axis of higher-dimensional embeddings.
+    ///     By default, 3 for the complete grid and 20 for the adaptive grid.
+    /// theta: Optional[float] = None
+    ///     The Barnes-Hut opening criterion: a cell is approximated by its average when
+    ///     its longest side is smaller than theta times its distance from the sample, and
+    ///     is otherwise opened. Smaller values are more accurate and slower. By default,
+    ///     the siblings of the cells containing each sample are approximated.
+    /// leaf_capacity: Optional[int] = None
+    ///     The maximum number of samples in a leaf of the adaptive grid, which is rebuilt
+    ///     at every epoch splitting only the cells exceeding it. By default, the complete
+    ///     grid of the provided depth is used.
     pub fn new(py_kwargs: Option<&PyDict>) -> PyResult<Self> {
         Ok(Self {
-            inner: BarnesHutSigmoidDecompositionRust::from_pydict(py_kwargs)?,
+            inner: BarnesHutSigmoidDecompositionRust::<SimilarityKernel, TargetSimilarityKernel>::from_pydict(py_kwargs)?,
         })
     }
 
-    #[pyo3(text_signature = "($self, matrix, number_of_dimensions, dtype)")]
+    #[pyo3(
+        text_signature = "($self, matrix, number_of_dimensions, dtype, return_loss_history, initial_embedding)"
+    )]
     pub fn fit_transform(
         &self,
         matrix: Py<PyAny>,
         number_of_dimensions: Option<usize>,
         dtype: Option<&str>,
+        return_loss_history: Option<bool>,
+        initial_embedding: Option<Py<PyAny>>,
     ) -> PyResult<Py<PyAny>> {
-        self.fit_transform_binding(matrix, number_of_dimensions, dtype)
+        self.fit_transform_binding(
+            matrix,
+            number_of_dimensions,
+            dtype,
+            return_loss_history,
+            initial_embedding,
+        )
     }
 }
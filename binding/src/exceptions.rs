@@ -0,0 +1,61 @@
+use dimensional_reduction::errors::DimensionalReductionError;
+use pyo3::create_exception;
+use pyo3::exceptions::{PyNotImplementedError, PyValueError};
+use pyo3::PyErr;
+
+create_exception!(dimensional_reduction, EmptyInputError, PyValueError);
+create_exception!(dimensional_reduction, DimensionMismatchError, PyValueError);
+create_exception!(
+    dimensional_reduction,
+    UnsupportedTargetDimensionError,
+    PyNotImplementedError
+);
+create_exception!(dimensional_reduction, NonFiniteValueError, PyValueError);
+create_exception!(dimensional_reduction, InvalidParameterError, PyValueError);
+
+/// Conversion of the errors raised in the binding into Python exceptions.
+pub trait IntoPyErr {
+    fn into_py_err(self) -> PyErr;
+}
+
+impl IntoPyErr for PyErr {
+    fn into_py_err(self) -> PyErr {
+        self
+    }
+}
+
+impl IntoPyErr for String {
+    fn into_py_err(self) -> PyErr {
+        PyValueError::new_err(self)
+    }
+}
+
+impl IntoPyErr for &str {
+    fn into_py_err(self) -> PyErr {
+        PyValueError::new_err(self.to_string())
+    }
+}
+
+impl IntoPyErr for DimensionalReductionError {
+    fn into_py_err(self) -> PyErr {
+        let message = self.to_string();
+        match self {
+            DimensionalReductionError::EmptyInput => EmptyInputError::new_err(message),
+            DimensionalReductionError::ZeroDimension { .. }
+            | DimensionalReductionError::IncompatibleDimension { .. }
+            | DimensionalReductionError::SamplesNumberMismatch { .. } => {
+                DimensionMismatchError::new_err(message)
+            }
+            DimensionalReductionError::UnsupportedTargetDimension { .. } => {
+                UnsupportedTargetDimensionError::new_err(message)
+            }
+            DimensionalReductionError::NonFiniteValue { .. } => {
+                NonFiniteValueError::new_err(message)
+            }
+            DimensionalReductionError::EmptyModelName
+            | DimensionalReductionError::InvalidParameter { .. } => {
+                InvalidParameterError::new_err(message)
+            }
+        }
+    }
+}
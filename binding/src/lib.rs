@@ -1,21 +1,53 @@
 #![feature(return_position_impl_trait_in_trait)]
 
+pub mod exceptions;
 pub mod macros;
+pub mod mds_decomposition;
+pub mod metrics;
 pub mod numpy_decomposition;
 pub mod sigmoid_decomposition;
 pub mod sampled_sigmoid_decomposition;
 pub mod barnes_hut_sigmoid_decomposition;
+pub mod pca_decomposition;
+pub mod tsne_decomposition;
+pub mod umap_decomposition;
 pub mod traits;
 
-use pyo3::{pymodule, types::PyModule, PyResult, Python};
+use exceptions::*;
+use pyo3::{pymodule, types::PyModule, wrap_pyfunction, PyResult, Python};
 pub use sigmoid_decomposition::SigmoidDecomposition;
 pub use sampled_sigmoid_decomposition::SampledSigmoidDecomposition;
 pub use barnes_hut_sigmoid_decomposition::BarnesHutSigmoidDecomposition;
+pub use pca_decomposition::PCADecomposition;
+pub use tsne_decomposition::TSNEDecomposition;
+pub use umap_decomposition::UMAPDecomposition;
+pub use mds_decomposition::MDSDecomposition;
 
 #[pymodule]
-pub fn dimensional_reduction(_py: Python, m: &PyModule) -> PyResult<()> {
+pub fn dimensional_reduction(py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<SigmoidDecomposition>()?;
     m.add_class::<BarnesHutSigmoidDecomposition>()?;
     m.add_class::<SampledSigmoidDecomposition>()?;
+    m.add_class::<PCADecomposition>()?;
+    m.add_class::<TSNEDecomposition>()?;
+    m.add_class::<UMAPDecomposition>()?;
+    m.add_class::<MDSDecomposition>()?;
+    m.add_function(wrap_pyfunction!(metrics::trustworthiness, m)?)?;
+    m.add_function(wrap_pyfunction!(metrics::continuity, m)?)?;
+    m.add_function(wrap_pyfunction!(metrics::knn_recall, m)?)?;
+    m.add_function(wrap_pyfunction!(metrics::co_ranking_matrix, m)?)?;
+    m.add_function(wrap_pyfunction!(metrics::quality_curves, m)?)?;
+    m.add_function(wrap_pyfunction!(metrics::spearman_correlation, m)?)?;
+    m.add_function(wrap_pyfunction!(metrics::raw_stress, m)?)?;
+    m.add_function(wrap_pyfunction!(metrics::normalized_stress, m)?)?;
+    m.add_function(wrap_pyfunction!(metrics::sammon_stress, m)?)?;
+    m.add("EmptyInputError", py.get_type::<EmptyInputError>())?;
+    m.add("DimensionMismatchError", py.get_type::<DimensionMismatchError>())?;
+    m.add(
+        "UnsupportedTargetDimensionError",
+        py.get_type::<UnsupportedTargetDimensionError>(),
+    )?;
+    m.add("NonFiniteValueError", py.get_type::<NonFiniteValueError>())?;
+    m.add("InvalidParameterError", py.get_type::<InvalidParameterError>())?;
     Ok(())
 }
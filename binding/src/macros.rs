@@ -1,7 +1,7 @@
 #[macro_export]
 macro_rules! pe {
     ($value: expr) => {
-        ($value).map_err(|err| PyValueError::new_err(err))
+        ($value).map_err(|err| $crate::exceptions::IntoPyErr::into_py_err(err))
     };
 }
 
@@ -36,3 +36,120 @@ macro_rules! extract_value_rust_result {
             }))?
     };
 }
+
+#[macro_export]
+macro_rules! extract_initialization {
+    ($kwargs: ident) => {{
+        let initialization_scale =
+            extract_value_rust_result!($kwargs, "initialization_scale", f32);
+        match (
+            extract_value_rust_result!($kwargs, "initialization", String),
+            initialization_scale,
+        ) {
+            (None, None) => None,
+            // A scale without a strategy is checked against the default strategy.
+            (name, initialization_scale) => Some(pe!(
+                dimensional_reduction::initialization::Initialization::from_name(
+                    name.as_deref().unwrap_or("random_uniform"),
+                    initialization_scale
+                )
+            )?),
+        }
+    }};
+}
+
+#[macro_export]
+macro_rules! extract_optimizer {
+    ($kwargs: ident) => {{
+        let momentum = extract_value_rust_result!($kwargs, "momentum", f32);
+        let beta_1 = extract_value_rust_result!($kwargs, "beta_1", f32);
+        let beta_2 = extract_value_rust_result!($kwargs, "beta_2", f32);
+        let epsilon = extract_value_rust_result!($kwargs, "epsilon", f32);
+        match extract_value_rust_result!($kwargs, "optimizer", String) {
+            Some(name) => Some(pe!(
+                dimensional_reduction::optimizers::Optimizer::from_name(
+                    &name, momentum, beta_1, beta_2, epsilon
+                )
+            )?),
+            None => None,
+        }
+    }};
+}
+
+#[macro_export]
+macro_rules! extract_learning_rate_schedule {
+    ($kwargs: ident) => {{
+        let decay_rate = extract_value_rust_result!($kwargs, "decay_rate", f32);
+        let step_size = extract_value_rust_result!($kwargs, "step_size", usize);
+        let warmup_iterations = extract_value_rust_result!($kwargs, "warmup_iterations", usize);
+        match extract_value_rust_result!($kwargs, "learning_rate_schedule", String) {
+            Some(name) => Some(pe!(
+                dimensional_reduction::schedules::LearningRateSchedule::from_name(
+                    &name,
+                    decay_rate,
+                    step_size,
+                    warmup_iterations
+                )
+            )?),
+            None => None,
+        }
+    }};
+}
+
+#[macro_export]
+macro_rules! extract_neighbour_search {
+    ($kwargs: ident) => {{
+        let iterations = extract_value_rust_result!($kwargs, "nn_descent_iterations", usize);
+        let tolerance = extract_value_rust_result!($kwargs, "nn_descent_tolerance", f64);
+        let random_state = extract_value_rust_result!($kwargs, "random_state", u64);
+        match (
+            extract_value_rust_result!($kwargs, "neighbour_search", String),
+            iterations,
+            tolerance,
+        ) {
+            (None, None, None) => None,
+            // The NN-descent parameters without a search are checked against the exact search.
+            (name, iterations, tolerance) => Some(pe!(
+                dimensional_reduction::knn::NeighbourSearch::from_name(
+                    name.as_deref().unwrap_or("exact"),
+                    iterations,
+                    tolerance,
+                    random_state
+                )
+            )?),
+        }
+    }};
+}
+
+#[macro_export]
+macro_rules! extract_similarity {
+    ($kwargs: ident) => {{
+        let bandwidth = extract_value_rust_result!($kwargs, "bandwidth", f32);
+        match extract_value_rust_result!($kwargs, "similarity", String) {
+            Some(name) => pe!(
+                dimensional_reduction::similarities::SimilarityKernel::from_name(
+                    &name, bandwidth
+                )
+            )?,
+            None => dimensional_reduction::similarities::SimilarityKernel::default(),
+        }
+    }};
+}
+
+#[macro_export]
+macro_rules! extract_target_similarity {
+    ($kwargs: ident) => {{
+        let minimum_distance = extract_value_rust_result!($kwargs, "minimum_distance", f32);
+        let spread = extract_value_rust_result!($kwargs, "spread", f32);
+        match extract_value_rust_result!($kwargs, "target_similarity", String) {
+            Some(name) => pe!(
+                dimensional_reduction::target_similarities::TargetSimilarityKernel::from_name(
+                    &name,
+                    minimum_distance,
+                    spread
+                )
+            )?,
+            None => dimensional_reduction::target_similarities::TargetSimilarityKernel::default(),
+        }
+    }};
+}
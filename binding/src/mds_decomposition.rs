@@ -0,0 +1,118 @@
+use crate::numpy_decomposition::NumpyDecomposition;
+use crate::traits::*;
+use crate::*;
+use dimensional_reduction::basic_iterative_decomposition::{
+    BasicIterativeDecomposition, IterativeParameters,
+};
+use dimensional_reduction::MDSDecomposition as MDSDecompositionRust;
+use pyo3::types::PyDict;
+use pyo3::*;
+
+impl FromPyDict for MDSDecompositionRust {
+    fn from_pydict(py_kwargs: Option<&types::PyDict>) -> PyResult<Self>
+    where
+        Self: Sized,
+    {
+        let py = pyo3::Python::acquire_gil();
+        let kwargs = normalize_kwargs!(py_kwargs, py.python());
+
+        pe!(Self::new(pe!(BasicIterativeDecomposition::new(
+            "MDS Decomposition",
+            IterativeParameters {
+                iterations: extract_value_rust_result!(kwargs, "iterations", usize),
+                learning_rate: extract_value_rust_result!(kwargs, "learning_rate", f32),
+                learning_rate_schedule: extract_learning_rate_schedule!(kwargs),
+                tolerance: extract_value_rust_result!(kwargs, "tolerance", f32),
+                patience: extract_value_rust_result!(kwargs, "patience", usize),
+                initialization: extract_initialization!(kwargs),
+                deterministic: extract_value_rust_result!(kwargs, "deterministic", bool),
+                optimizer: extract_optimizer!(kwargs),
+                random_state: extract_value_rust_result!(kwargs, "random_state", u64),
+                verbose: extract_value_rust_result!(kwargs, "verbose", bool),
+            },
+        ))?))
+    }
+}
+
+///
+#[pyclass]
+#[derive(Clone)]
+#[pyo3(text_signature = "(*, iterations, learning_rate, learning_rate_schedule, decay_rate, step_size, warmup_iterations, tolerance, patience, initialization, initialization_scale, deterministic, random_state, verbose)")]
+pub struct MDSDecomposition {
+    inner: MDSDecompositionRust,
+}
+
+impl DimensionalReductionBinding<MDSDecompositionRust> for MDSDecomposition {
+    fn get_basic_dimensionality_reduction(&self) -> &MDSDecompositionRust {
+        &self.inner
+    }
+}
+
+#[pymethods]
+impl MDSDecomposition {
+    #[new]
+    #[args(py_kwargs = "**")]
+    /// Return a new instance of the metric MDS Decomposition model.
+    ///
+    /// Parameters
+    /// ------------------------
+    /// iterations: int = 50
+    ///     The number of SMACOF epochs to run. By default, 50.
+    /// learning_rate: float = 1.0
+    ///     The fraction of the step towards the Guttman transform taken in each epoch,
+    ///     where 1.0 runs the plain SMACOF iterations. By default, 1.0.
+    /// learning_rate_schedule: str = "constant"
+    ///     The schedule scaling the learning rate in each epoch, one of "constant",
+    ///     "linear", "exponential", "step" and "cosine". By default, "constant".
+    /// decay_rate: Optional[float] = None
+    ///     The factor of the "exponential" and "step" schedules. By default, 0.95 for
+    ///     the "exponential" schedule and 0.5 for the "step" schedule.
+    /// step_size: int = 10
+    ///     The number of epochs between the decays of the "step" schedule. By default, 10.
+    /// warmup_iterations: int = 0
+    ///     The number of epochs over which the learning rate grows linearly to its
+    ///     full value before the schedule decays it. By default, 0.
+    /// tolerance: Optional[float] = None
+    ///     The relative change of the epoch loss under which an epoch is considered
+    ///     not to improve the model. By default, no early stopping is done.
+    /// patience: int = 5
+    ///     The number of consecutive epochs without improvement after which
+    ///     the training is stopped. By default, 5.
+    /// initialization: str = "random_uniform"
+    ///     The strategy used to initialize the embedding, one of "random_uniform",
+    ///     "random_gaussian", "pca", "spectral" and "provided". By default, "random_uniform".
+    /// initialization_scale: float = 1e-4
+    ///     The standard deviation of the "random_gaussian" initialization. By default, 1e-4.
+    ///     It must not be provided with the other initializations.
+    /// deterministic: bool = False
+    ///     Whether the results must not depend on the number of threads. As MDS always
+    ///     computes the Guttman transform from the embedding at the start of each epoch
+    ///     and in a fixed order, this has no cost.
+    /// random_state: int = 42
+    ///     The random state to reproduce the model initialization and training. By default, 42.
+    pub fn new(py_kwargs: Option<&PyDict>) -> PyResult<Self> {
+        Ok(Self {
+            inner: MDSDecompositionRust::from_pydict(py_kwargs)?,
+        })
+    }
+
+    #[pyo3(
+        text_signature = "($self, matrix, number_of_dimensions, dtype, return_loss_history, initial_embedding)"
+    )]
+    pub fn fit_transform(
+        &self,
+        matrix: Py<PyAny>,
+        number_of_dimensions: Option<usize>,
+        dtype: Option<&str>,
+        return_loss_history: Option<bool>,
+        initial_embedding: Option<Py<PyAny>>,
+    ) -> PyResult<Py<PyAny>> {
+        self.fit_transform_binding(
+            matrix,
+            number_of_dimensions,
+            dtype,
+            return_loss_history,
+            initial_embedding,
+        )
+    }
+}
@@ -0,0 +1,282 @@
+use crate::*;
+use dimensional_reduction::matrices::{CSRMatrix, DenseMatrix};
+use dimensional_reduction::metrics::{
+    continuity as continuity_rust, knn_recall as knn_recall_rust,
+    normalized_stress as normalized_stress_rust, raw_stress as raw_stress_rust,
+    sammon_stress as sammon_stress_rust, spearman_correlation as spearman_correlation_rust,
+    trustworthiness as trustworthiness_rust, CoRankingMatrix,
+};
+use numpy::{PyArray1, PyArray2};
+use pyo3::types::IntoPyDict;
+use pyo3::*;
+
+/// Returns the provided matrix as a contiguous matrix of 64 bit floats.
+fn to_contiguous_f64<'a>(py: Python<'a>, matrix: &'a PyAny) -> PyResult<&'a PyAny> {
+    py.import("numpy")?.call_method(
+        "ascontiguousarray",
+        (matrix,),
+        Some([("dtype", "float64")].into_py_dict(py)),
+    )
+}
+
+/// Evaluates the provided expression on the provided original samples and embedding,
+/// converting them to 64 bit floats and the sparse matrices to the CSR format.
+macro_rules! with_original_and_embedding {
+    ($py: ident, $original: ident, $embedding: ident, |$matrix: ident, $target: ident, $target_dimension: ident| $operation: expr) => {{
+        let embedding = <&PyArray2<f64>>::extract(to_contiguous_f64($py, $embedding.as_ref($py))?)?;
+        let $target_dimension = embedding.shape()[1];
+        let $target = unsafe { embedding.as_slice().unwrap() };
+        let original = $original.as_ref($py);
+
+        if original.hasattr("tocsr")? {
+            let original = original.call_method0("tocsr")?;
+            let (_, number_of_features) = original.getattr("shape")?.extract::<(usize, usize)>()?;
+            // The offsets and column indices are usually stored as 32 bit integers.
+            let extract_positions = |name: &str| -> PyResult<Vec<usize>> {
+                let positions = <&PyArray1<u64>>::extract(
+                    original.getattr(name)?.call_method1("astype", ("uint64",))?
+                )?;
+                Ok(unsafe { positions.as_slice().unwrap() }
+                    .iter()
+                    .map(|&position| position as usize)
+                    .collect())
+            };
+            let data = <&PyArray1<f64>>::extract(to_contiguous_f64($py, original.getattr("data")?)?)?;
+            let $matrix = pe!(CSRMatrix::new(
+                extract_positions("indptr")?,
+                extract_positions("indices")?,
+                unsafe { data.as_slice().unwrap() }.to_vec(),
+                number_of_features,
+            ))?;
+            pe!($operation)
+        } else {
+            let original = <&PyArray2<f64>>::extract(to_contiguous_f64($py, original)?)?;
+            let $matrix = pe!(DenseMatrix::new(unsafe { original.as_slice().unwrap() }, original.shape()[1]))?;
+            pe!($operation)
+        }
+    }};
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "(original, embedding, number_of_neighbours)")]
+/// Return the trustworthiness of the embedding, that is how much the nearest
+/// neighbours in the embedding are also near in the original space.
+///
+/// Parameters
+/// ------------------------
+/// original: Union[np.ndarray, scipy.sparse.csr_matrix]
+///     2D Matrix containing the original samples.
+/// embedding: np.ndarray
+///     2D Matrix containing the embedding of the original samples.
+/// number_of_neighbours: int = 5
+///     The size of the neighbourhoods that are compared. By default, 5.
+pub fn trustworthiness(
+    original: Py<PyAny>,
+    embedding: Py<PyAny>,
+    number_of_neighbours: Option<usize>,
+) -> PyResult<f64> {
+    let gil = pyo3::Python::acquire_gil();
+    let py = gil.python();
+    with_original_and_embedding!(py, original, embedding, |matrix, target, target_dimension| {
+        trustworthiness_rust(&matrix, target, target_dimension, number_of_neighbours.unwrap_or(5))
+    })
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "(original, embedding, number_of_neighbours)")]
+/// Return the continuity of the embedding, that is how much the nearest
+/// neighbours in the original space are also near in the embedding.
+///
+/// Parameters
+/// ------------------------
+/// original: Union[np.ndarray, scipy.sparse.csr_matrix]
+///     2D Matrix containing the original samples.
+/// embedding: np.ndarray
+///     2D Matrix containing the embedding of the original samples.
+/// number_of_neighbours: int = 5
+///     The size of the neighbourhoods that are compared. By default, 5.
+pub fn continuity(
+    original: Py<PyAny>,
+    embedding: Py<PyAny>,
+    number_of_neighbours: Option<usize>,
+) -> PyResult<f64> {
+    let gil = pyo3::Python::acquire_gil();
+    let py = gil.python();
+    with_original_and_embedding!(py, original, embedding, |matrix, target, target_dimension| {
+        continuity_rust(&matrix, target, target_dimension, number_of_neighbours.unwrap_or(5))
+    })
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "(original, embedding, number_of_neighbours)")]
+/// Return the mean fraction of the nearest neighbours in the original space
+/// that are also nearest neighbours in the embedding.
+///
+/// Parameters
+/// ------------------------
+/// original: Union[np.ndarray, scipy.sparse.csr_matrix]
+///     2D Matrix containing the original samples.
+/// embedding: np.ndarray
+///     2D Matrix containing the embedding of the original samples.
+/// number_of_neighbours: int = 5
+///     The size of the neighbourhoods that are compared. By default, 5.
+pub fn knn_recall(
+    original: Py<PyAny>,
+    embedding: Py<PyAny>,
+    number_of_neighbours: Option<usize>,
+) -> PyResult<f64> {
+    let gil = pyo3::Python::acquire_gil();
+    let py = gil.python();
+    with_original_and_embedding!(py, original, embedding, |matrix, target, target_dimension| {
+        knn_recall_rust(&matrix, target, target_dimension, number_of_neighbours.unwrap_or(5))
+    })
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "(original, embedding, maximum_rank)")]
+/// Return the co-ranking matrix of the embedding, where the element in the row k
+/// and column l counts the pairs of samples where the second sample is the (k + 1)-th
+/// nearest neighbour of the first one in the original space and the (l + 1)-th in
+/// the embedding.
+///
+/// Parameters
+/// ------------------------
+/// original: Union[np.ndarray, scipy.sparse.csr_matrix]
+///     2D Matrix containing the original samples.
+/// embedding: np.ndarray
+///     2D Matrix containing the embedding of the original samples.
+/// maximum_rank: Optional[int] = None
+///     The largest rank counted by the matrix. By default, all of them.
+pub fn co_ranking_matrix(
+    original: Py<PyAny>,
+    embedding: Py<PyAny>,
+    maximum_rank: Option<usize>,
+) -> PyResult<Py<PyAny>> {
+    let gil = pyo3::Python::acquire_gil();
+    let py = gil.python();
+    let co_ranking = with_original_and_embedding!(
+        py,
+        original,
+        embedding,
+        |matrix, target, target_dimension| {
+            CoRankingMatrix::new(&matrix, target, target_dimension, maximum_rank)
+        }
+    )?;
+    let counts = co_ranking
+        .get_counts()
+        .chunks(co_ranking.get_maximum_rank())
+        .map(|row| row.iter().map(|&count| count as u64).collect::<Vec<u64>>())
+        .collect::<Vec<Vec<u64>>>();
+    Ok(PyArray2::from_vec2(py, &counts)?.to_owned().into_py(py))
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "(original, embedding, maximum_rank)")]
+/// Return the quality Q_NX and the local continuity meta-criterion LCMC of the
+/// embedding for each neighbourhood size from one to the maximum rank.
+///
+/// Parameters
+/// ------------------------
+/// original: Union[np.ndarray, scipy.sparse.csr_matrix]
+///     2D Matrix containing the original samples.
+/// embedding: np.ndarray
+///     2D Matrix containing the embedding of the original samples.
+/// maximum_rank: Optional[int] = None
+///     The largest neighbourhood size. By default, all of them.
+pub fn quality_curves(
+    original: Py<PyAny>,
+    embedding: Py<PyAny>,
+    maximum_rank: Option<usize>,
+) -> PyResult<Py<PyAny>> {
+    let gil = pyo3::Python::acquire_gil();
+    let py = gil.python();
+    let co_ranking = with_original_and_embedding!(
+        py,
+        original,
+        embedding,
+        |matrix, target, target_dimension| {
+            CoRankingMatrix::new(&matrix, target, target_dimension, maximum_rank)
+        }
+    )?;
+    Ok((
+        PyArray1::from_vec(py, co_ranking.get_quality_curve()).to_owned(),
+        PyArray1::from_vec(py, co_ranking.get_local_continuity_curve()).to_owned(),
+    )
+        .into_py(py))
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "(original, embedding)")]
+/// Return the Spearman correlation between the distances of all of the pairs
+/// of samples in the original space and in the embedding.
+///
+/// Parameters
+/// ------------------------
+/// original: Union[np.ndarray, scipy.sparse.csr_matrix]
+///     2D Matrix containing the original samples.
+/// embedding: np.ndarray
+///     2D Matrix containing the embedding of the original samples.
+pub fn spearman_correlation(original: Py<PyAny>, embedding: Py<PyAny>) -> PyResult<f64> {
+    let gil = pyo3::Python::acquire_gil();
+    let py = gil.python();
+    with_original_and_embedding!(py, original, embedding, |matrix, target, target_dimension| {
+        spearman_correlation_rust(&matrix, target, target_dimension)
+    })
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "(original, embedding)")]
+/// Return the raw stress of the embedding, that is the sum over all of the pairs
+/// of samples of the squared differences of their original and embedded distances.
+///
+/// Parameters
+/// ------------------------
+/// original: Union[np.ndarray, scipy.sparse.csr_matrix]
+///     2D Matrix containing the original samples.
+/// embedding: np.ndarray
+///     2D Matrix containing the embedding of the original samples.
+pub fn raw_stress(original: Py<PyAny>, embedding: Py<PyAny>) -> PyResult<f64> {
+    let gil = pyo3::Python::acquire_gil();
+    let py = gil.python();
+    with_original_and_embedding!(py, original, embedding, |matrix, target, target_dimension| {
+        raw_stress_rust(&matrix, target, target_dimension)
+    })
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "(original, embedding)")]
+/// Return the normalized stress of the embedding, that is the square root of
+/// the raw stress divided by the sum of the squared original distances.
+///
+/// Parameters
+/// ------------------------
+/// original: Union[np.ndarray, scipy.sparse.csr_matrix]
+///     2D Matrix containing the original samples.
+/// embedding: np.ndarray
+///     2D Matrix containing the embedding of the original samples.
+pub fn normalized_stress(original: Py<PyAny>, embedding: Py<PyAny>) -> PyResult<f64> {
+    let gil = pyo3::Python::acquire_gil();
+    let py = gil.python();
+    with_original_and_embedding!(py, original, embedding, |matrix, target, target_dimension| {
+        normalized_stress_rust(&matrix, target, target_dimension)
+    })
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "(original, embedding)")]
+/// Return the Sammon stress of the embedding, where the squared difference of the
+/// distances of each pair of samples is divided by their original distance.
+///
+/// Parameters
+/// ------------------------
+/// original: Union[np.ndarray, scipy.sparse.csr_matrix]
+///     2D Matrix containing the original samples.
+/// embedding: np.ndarray
+///     2D Matrix containing the embedding of the original samples.
+pub fn sammon_stress(original: Py<PyAny>, embedding: Py<PyAny>) -> PyResult<f64> {
+    let gil = pyo3::Python::acquire_gil();
+    let py = gil.python();
+    with_original_and_embedding!(py, original, embedding, |matrix, target, target_dimension| {
+        sammon_stress_rust(&matrix, target, target_dimension)
+    })
+}
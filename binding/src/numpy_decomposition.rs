@@ -1,8 +1,12 @@
-use dimensional_reduction::traits::DimensionalReduction;
+use dimensional_reduction::traits::{
+    DimensionalReduction, GraphDimensionalReduction, PrecomputedDimensionalReduction,
+};
+use dimensional_reduction::graph::Graph;
 use crate::*;
-use numpy::PyArray2;
+use dimensional_reduction::matrices::{CSRMatrix, DenseMatrix, PrecomputedMatrix};
+use dimensional_reduction::similarities::PrecomputedKind;
+use numpy::{PyArray1, PyArray2};
 use crate::traits::*;
-use pyo3::exceptions::PyValueError;
 use pyo3::{FromPyObject, IntoPy, Py, PyAny, PyResult};
 
 pub trait NumpyDecomposition<T> where T: DimensionalReduction {
@@ -11,21 +15,399 @@ pub trait NumpyDecomposition<T> where T: DimensionalReduction {
         matrix: Py<PyAny>,
         number_of_dimensions: Option<usize>,
         dtype: Option<&str>,
+        return_loss_history: Option<bool>,
+        initial_embedding: Option<Py<PyAny>>,
     ) -> PyResult<Py<PyAny>>;
 }
 
+pub trait NumpyPrecomputedDecomposition<T> where T: PrecomputedDimensionalReduction {
+    #[allow(clippy::too_many_arguments)]
+    fn fit_transform_precomputed_binding(
+        &self,
+        matrix: Py<PyAny>,
+        kind: Option<&str>,
+        bandwidth: Option<f32>,
+        number_of_dimensions: Option<usize>,
+        dtype: Option<&str>,
+        return_loss_history: Option<bool>,
+        initial_embedding: Option<Py<PyAny>>,
+    ) -> PyResult<Py<PyAny>>;
+}
+
+pub trait NumpyGraphDecomposition<T> where T: GraphDimensionalReduction {
+    fn fit_transform_graph_binding(
+        &self,
+        graph: Py<PyAny>,
+        number_of_nodes: Option<usize>,
+        number_of_dimensions: Option<usize>,
+        dtype: Option<&str>,
+        return_loss_history: Option<bool>,
+        initial_embedding: Option<Py<PyAny>>,
+    ) -> PyResult<Py<PyAny>>;
+}
+
+macro_rules! copy_initial_embedding {
+    ($gil: ident, $initial_embedding: ident, $target_ref: ident, $target_dtype:ty, $shape: expr) => {
+        if let Some(initial_embedding) = $initial_embedding.as_ref() {
+            let initial_embedding = pe!(<&PyArray2<$target_dtype>>::extract(
+                initial_embedding.as_ref($gil.python())
+            )
+            .map_err(|_| concat!(
+                "The provided initial embedding must be a 2D matrix ",
+                "with the same dtype of the requested embedding."
+            )))?;
+
+            if initial_embedding.shape() != $shape || !initial_embedding.is_c_contiguous() {
+                return pe!(Err(concat!(
+                    "The provided initial embedding must be a contiguous ",
+                    "matrix in C orientation with a row for each sample ",
+                    "and a column for each dimension."
+                )));
+            }
+
+            $target_ref.copy_from_slice(unsafe { initial_embedding.as_slice().unwrap() });
+        }
+    };
+}
+
+macro_rules! fit_transform_sparse {
+    ($self: ident, $gil: ident, $original: ident, $target_dtype:ty, $shape: expr, $return_loss_history: ident, $initial_embedding: ident) => {{
+        let target = PyArray2::zeros($gil.python(), $shape, false);
+        let target_ref: &mut [$target_dtype] = unsafe { target.as_slice_mut().unwrap() };
+        copy_initial_embedding!($gil, $initial_embedding, target_ref, $target_dtype, $shape);
+
+        let loss_history = pe!($self.get_basic_dimensionality_reduction().fit_transform_matrix(
+            target_ref,
+            $shape[1],
+            &$original,
+        ))?;
+
+        return Ok(if $return_loss_history.unwrap_or(false) {
+            (target.to_owned(), loss_history).into_py($gil.python())
+        } else {
+            target.to_owned().into_py($gil.python())
+        });
+    }};
+}
+
+macro_rules! fit_transform_precomputed {
+    ($self: ident, $gil: ident, $precomputed: ident, $target_dtype:ty, $shape: expr, $return_loss_history: ident, $initial_embedding: ident) => {{
+        let target = PyArray2::zeros($gil.python(), $shape, false);
+        let target_ref: &mut [$target_dtype] = unsafe { target.as_slice_mut().unwrap() };
+        copy_initial_embedding!($gil, $initial_embedding, target_ref, $target_dtype, $shape);
+
+        let loss_history = pe!($self.get_basic_dimensionality_reduction().fit_transform_precomputed(
+            target_ref,
+            $shape[1],
+            &$precomputed,
+        ))?;
+
+        return Ok(if $return_loss_history.unwrap_or(false) {
+            (target.to_owned(), loss_history).into_py($gil.python())
+        } else {
+            target.to_owned().into_py($gil.python())
+        });
+    }};
+}
+
+macro_rules! fit_transform_precomputed_dtypes {
+    ($self: ident, $gil: ident, $precomputed: ident, $dtype: ident, $shape: expr, $return_loss_history: ident, $initial_embedding: ident) => {
+        match $dtype.unwrap_or("f32") {
+            "f32" => fit_transform_precomputed!($self, $gil, $precomputed, f32, $shape, $return_loss_history, $initial_embedding),
+            "f64" => fit_transform_precomputed!($self, $gil, $precomputed, f64, $shape, $return_loss_history, $initial_embedding),
+            dtype => {
+                return pe!(Err(
+                    format!(
+                        "The data type {} is not supported.",
+                        dtype
+                    )
+                ));
+            }
+        }
+    };
+}
+
+macro_rules! impl_numpy_precomputed_decompositions {
+    ($($dtype:ty),*) => {
+        /// Returns the embedding of the samples of the provided precomputed matrix.
+        ///
+        /// Arguments
+        /// ------------
+        /// matrix: Union[np.ndarray, scipy.sparse.csr_matrix]
+        ///     Square matrix of the affinities or distances between the samples.
+        ///     The pairs missing from a sparse matrix have zero affinity.
+        /// kind: str = "affinity"
+        ///     Whether the matrix contains "affinity" values in [0, 1] or non-negative "distance" values.
+        /// bandwidth: float = 1.0
+        ///     The distance at which the affinity decays by a factor e, for the "distance" kind.
+        ///
+        #[allow(clippy::too_many_arguments)]
+        fn fit_transform_precomputed_binding(&self, matrix: Py<PyAny>, kind: Option<&str>, bandwidth: Option<f32>, number_of_dimensions: Option<usize>, dtype: Option<&str>, return_loss_history: Option<bool>, initial_embedding: Option<Py<PyAny>>) -> PyResult<Py<PyAny>> {
+            let gil = pyo3::Python::acquire_gil();
+            let matrix = matrix.as_ref(gil.python());
+            let kind = pe!(PrecomputedKind::from_name(kind.unwrap_or("affinity"), bandwidth))?;
+            let number_of_dimensions = number_of_dimensions.unwrap_or(2);
+
+            if matrix.hasattr("tocsr")? {
+                let matrix = matrix.call_method0("tocsr")?;
+                let (number_of_samples, number_of_columns) = matrix.getattr("shape")?.extract::<(usize, usize)>()?;
+                // The offsets and column indices are usually stored as 32 bit integers.
+                let extract_positions = |name: &str| -> PyResult<Vec<usize>> {
+                    let positions = <&PyArray1<u64>>::extract(
+                        matrix.getattr(name)?.call_method1("astype", ("uint64",))?
+                    )?;
+                    Ok(unsafe { positions.as_slice().unwrap() }
+                        .iter()
+                        .map(|&position| position as usize)
+                        .collect())
+                };
+                let (indptr, indices) = (extract_positions("indptr")?, extract_positions("indices")?);
+                $(
+                    if let Ok(data) = <&PyArray1<$dtype>>::extract(matrix.getattr("data")?) {
+                        let precomputed = pe!(PrecomputedMatrix::new(
+                            pe!(CSRMatrix::new(
+                                indptr,
+                                indices,
+                                unsafe { data.as_slice().unwrap() }.to_vec(),
+                                number_of_columns,
+                            ))?,
+                            kind,
+                        ))?;
+                        fit_transform_precomputed_dtypes!(self, gil, precomputed, dtype, [number_of_samples, number_of_dimensions], return_loss_history, initial_embedding);
+                    }
+                )*
+
+                return pe!(Err(concat!(
+                    "The values of the provided sparse matrix ",
+                    "must be either 32 or 64 bit floats."
+                )));
+            }
+
+            $(
+                if let Ok(matrix) = <&PyArray2<$dtype>>::extract(&matrix) {
+                    if !matrix.is_c_contiguous(){
+                        return pe!(Err(
+                            concat!(
+                                "The provided vector is not a contiguos vector in ",
+                                "C orientation."
+                            )
+                        ));
+                    }
+
+                    let number_of_samples = matrix.shape()[0];
+                    let precomputed = pe!(PrecomputedMatrix::new(
+                        pe!(DenseMatrix::new(unsafe { matrix.as_slice().unwrap() }, matrix.shape()[1]))?,
+                        kind,
+                    ))?;
+                    fit_transform_precomputed_dtypes!(self, gil, precomputed, dtype, [number_of_samples, number_of_dimensions], return_loss_history, initial_embedding);
+                }
+            )*
+
+            pe!(Err(concat!(
+                "The provided precomputed matrix must be a 2D matrix ",
+                "of either 32 or 64 bit floats."
+            )
+            .to_string()))
+        }
+    };
+}
+
+macro_rules! fit_transform_graph {
+    ($self: ident, $gil: ident, $graph: ident, $target_dtype:ty, $shape: expr, $return_loss_history: ident, $initial_embedding: ident) => {{
+        let target = PyArray2::zeros($gil.python(), $shape, false);
+        let target_ref: &mut [$target_dtype] = unsafe { target.as_slice_mut().unwrap() };
+        copy_initial_embedding!($gil, $initial_embedding, target_ref, $target_dtype, $shape);
+
+        let loss_history = pe!($self.get_basic_dimensionality_reduction().fit_transform_graph(
+            target_ref,
+            $shape[1],
+            &$graph,
+        ))?;
+
+        return Ok(if $return_loss_history.unwrap_or(false) {
+            (target.to_owned(), loss_history).into_py($gil.python())
+        } else {
+            target.to_owned().into_py($gil.python())
+        });
+    }};
+}
+
+macro_rules! fit_transform_graph_dtypes {
+    ($self: ident, $gil: ident, $graph: ident, $dtype: ident, $shape: expr, $return_loss_history: ident, $initial_embedding: ident) => {
+        match $dtype.unwrap_or("f32") {
+            "f32" => fit_transform_graph!($self, $gil, $graph, f32, $shape, $return_loss_history, $initial_embedding),
+            "f64" => fit_transform_graph!($self, $gil, $graph, f64, $shape, $return_loss_history, $initial_embedding),
+            dtype => {
+                return pe!(Err(
+                    format!(
+                        "The data type {} is not supported.",
+                        dtype
+                    )
+                ));
+            }
+        }
+    };
+}
+
+macro_rules! impl_numpy_graph_decompositions {
+    ($($dtype:ty),*) => {
+        /// Returns the embedding of the nodes of the provided graph.
+        ///
+        /// Arguments
+        /// ------------
+        /// graph: Union[scipy.sparse.csr_matrix, Tuple[np.ndarray, np.ndarray], Tuple[np.ndarray, np.ndarray, np.ndarray]]
+        ///     Either the square weighted adjacency matrix of the graph, or the
+        ///     sources, destinations and optionally the weights of its undirected edges.
+        /// number_of_nodes: Optional[int] = None
+        ///     The number of nodes of a graph provided as edges. By default, one more
+        ///     than the largest node of the edges.
+        ///
+        fn fit_transform_graph_binding(&self, graph: Py<PyAny>, number_of_nodes: Option<usize>, number_of_dimensions: Option<usize>, dtype: Option<&str>, return_loss_history: Option<bool>, initial_embedding: Option<Py<PyAny>>) -> PyResult<Py<PyAny>> {
+            let gil = pyo3::Python::acquire_gil();
+            let graph = graph.as_ref(gil.python());
+            let number_of_dimensions = number_of_dimensions.unwrap_or(2);
+            // The node identifiers and offsets are usually stored as 32 bit integers.
+            let extract_nodes = |nodes: &PyAny| -> PyResult<Vec<usize>> {
+                let nodes = <&PyArray1<u64>>::extract(
+                    nodes.call_method1("astype", ("uint64",))?
+                )?;
+                Ok(unsafe { nodes.as_slice().unwrap() }
+                    .iter()
+                    .map(|&node| node as usize)
+                    .collect())
+            };
+
+            if graph.hasattr("tocsr")? {
+                let adjacency = graph.call_method0("tocsr")?;
+                let (number_of_rows, number_of_columns) = adjacency.getattr("shape")?.extract::<(usize, usize)>()?;
+                let (indptr, indices) = (extract_nodes(adjacency.getattr("indptr")?)?, extract_nodes(adjacency.getattr("indices")?)?);
+                $(
+                    if let Ok(data) = <&PyArray1<$dtype>>::extract(adjacency.getattr("data")?) {
+                        let graph = pe!(Graph::new(pe!(CSRMatrix::new(
+                            indptr,
+                            indices,
+                            unsafe { data.as_slice().unwrap() }.to_vec(),
+                            number_of_columns,
+                        ))?))?;
+                        fit_transform_graph_dtypes!(self, gil, graph, dtype, [number_of_rows, number_of_dimensions], return_loss_history, initial_embedding);
+                    }
+                )*
+
+                return pe!(Err(concat!(
+                    "The weights of the provided adjacency matrix ",
+                    "must be either 32 or 64 bit floats."
+                )));
+            }
+
+            let edges = pe!(graph.extract::<Vec<&PyAny>>().map_err(|_| concat!(
+                "The provided graph must be either a sparse adjacency matrix ",
+                "or a tuple with the sources, destinations and optionally the weights of the edges."
+            )))?;
+            if edges.len() != 2 && edges.len() != 3 {
+                return pe!(Err(concat!(
+                    "The provided edges must be a tuple with the sources, ",
+                    "destinations and optionally the weights of the edges."
+                )));
+            }
+            let (sources, destinations) = (extract_nodes(edges[0])?, extract_nodes(edges[1])?);
+            let number_of_nodes = number_of_nodes.unwrap_or_else(|| {
+                sources
+                    .iter()
+                    .chain(destinations.iter())
+                    .max()
+                    .map_or(0, |&node| node + 1)
+            });
+            let shape = [number_of_nodes, number_of_dimensions];
+
+            if edges.len() == 2 {
+                let graph = pe!(Graph::<f32>::from_edge_list(number_of_nodes, &sources, &destinations, None))?;
+                fit_transform_graph_dtypes!(self, gil, graph, dtype, shape, return_loss_history, initial_embedding);
+            }
+
+            $(
+                if let Ok(weights) = <&PyArray1<$dtype>>::extract(edges[2]) {
+                    let graph = pe!(Graph::from_edge_list(
+                        number_of_nodes,
+                        &sources,
+                        &destinations,
+                        Some(unsafe { weights.as_slice().unwrap() }),
+                    ))?;
+                    fit_transform_graph_dtypes!(self, gil, graph, dtype, shape, return_loss_history, initial_embedding);
+                }
+            )*
+
+            pe!(Err(concat!(
+                "The weights of the provided edges ",
+                "must be either 32 or 64 bit floats."
+            )
+            .to_string()))
+        }
+    };
+}
+
 macro_rules! impl_numpy_decompositions {
     ($($dtype:ty),*) => {
         /// Returns cosine similarity of the provided source and destinations using the provided features.
         ///
         /// Arguments
         /// ------------
-        /// matrix: np.ndarray
-        ///     2D Matrix containing the feaures.
+        /// matrix: Union[np.ndarray, scipy.sparse.csr_matrix]
+        ///     2D Matrix containing the feaures. Sparse matrices in
+        ///     the other SciPy formats are converted to CSR.
+        /// return_loss_history: bool = False
+        ///     Whether to also return the loss history of the fit.
+        /// initial_embedding: Optional[np.ndarray] = None
+        ///     2D Matrix with the initial embedding, used by the "provided" initialization.
         ///
-        fn fit_transform_binding(&self, matrix: Py<PyAny>, number_of_dimensions: Option<usize>, dtype: Option<&str>) -> PyResult<Py<PyAny>> {
+        fn fit_transform_binding(&self, matrix: Py<PyAny>, number_of_dimensions: Option<usize>, dtype: Option<&str>, return_loss_history: Option<bool>, initial_embedding: Option<Py<PyAny>>) -> PyResult<Py<PyAny>> {
             let gil = pyo3::Python::acquire_gil();
             let matrix = matrix.as_ref(gil.python());
+
+            if matrix.hasattr("tocsr")? {
+                let matrix = matrix.call_method0("tocsr")?;
+                let (number_of_samples, number_of_features) = matrix.getattr("shape")?.extract::<(usize, usize)>()?;
+                let number_of_dimensions = number_of_dimensions.unwrap_or(2);
+                // The offsets and column indices are usually stored as 32 bit integers.
+                let extract_positions = |name: &str| -> PyResult<Vec<usize>> {
+                    let positions = <&PyArray1<u64>>::extract(
+                        matrix.getattr(name)?.call_method1("astype", ("uint64",))?
+                    )?;
+                    Ok(unsafe { positions.as_slice().unwrap() }
+                        .iter()
+                        .map(|&position| position as usize)
+                        .collect())
+                };
+                let (indptr, indices) = (extract_positions("indptr")?, extract_positions("indices")?);
+                $(
+                    if let Ok(data) = <&PyArray1<$dtype>>::extract(matrix.getattr("data")?) {
+                        let original = pe!(CSRMatrix::new(
+                            indptr,
+                            indices,
+                            unsafe { data.as_slice().unwrap() }.to_vec(),
+                            number_of_features,
+                        ))?;
+
+                        match dtype.unwrap_or("f32") {
+                            "f32" => fit_transform_sparse!(self, gil, original, f32, [number_of_samples, number_of_dimensions], return_loss_history, initial_embedding),
+                            "f64" => fit_transform_sparse!(self, gil, original, f64, [number_of_samples, number_of_dimensions], return_loss_history, initial_embedding),
+                            dtype => {
+                                return pe!(Err(
+                                    format!(
+                                        "The data type {} is not supported.",
+                                        dtype
+                                    )
+                                ));
+                            }
+                        }
+                    }
+                )*
+
+                return pe!(Err(concat!(
+                    "The values of the provided sparse matrix ",
+                    "must be either 32 or 64 bit floats."
+                )));
+            }
+
             $(
                 if let Ok(matrix) = <&PyArray2<$dtype>>::extract(&matrix) {
 
@@ -58,30 +440,40 @@ macro_rules! impl_numpy_decompositions {
                         //     return Ok(target.to_owned().into_py(gil.python()));
                         // },
                         "f32" => {
-                            let target = unsafe { PyArray2::new(gil.python(), [number_of_samples, number_of_dimensions], false) };
+                            let target = PyArray2::zeros(gil.python(), [number_of_samples, number_of_dimensions], false);
                             let target_ref: &mut [f32] = unsafe { target.as_slice_mut().unwrap() };
+                            copy_initial_embedding!(gil, initial_embedding, target_ref, f32, [number_of_samples, number_of_dimensions]);
 
-                            pe!(self.get_basic_dimensionality_reduction().fit_transform(
+                            let loss_history = pe!(self.get_basic_dimensionality_reduction().fit_transform(
                                 target_ref,
                                 number_of_dimensions,
                                 matrix_ref,
                                 number_of_features,
                             ))?;
 
-                            return Ok(target.to_owned().into_py(gil.python()));
+                            return Ok(if return_loss_history.unwrap_or(false) {
+                                (target.to_owned(), loss_history).into_py(gil.python())
+                            } else {
+                                target.to_owned().into_py(gil.python())
+                            });
                         },
                         "f64" => {
-                            let target = unsafe { PyArray2::new(gil.python(), [number_of_samples, number_of_dimensions], false) };
+                            let target = PyArray2::zeros(gil.python(), [number_of_samples, number_of_dimensions], false);
                             let target_ref: &mut [f64] = unsafe { target.as_slice_mut().unwrap() };
+                            copy_initial_embedding!(gil, initial_embedding, target_ref, f64, [number_of_samples, number_of_dimensions]);
 
-                            pe!(self.get_basic_dimensionality_reduction().fit_transform(
+                            let loss_history = pe!(self.get_basic_dimensionality_reduction().fit_transform(
                                 target_ref,
                                 number_of_dimensions,
                                 matrix_ref,
                                 number_of_features,
                             ))?;
 
-                            return Ok(target.to_owned().into_py(gil.python()));
+                            return Ok(if return_loss_history.unwrap_or(false) {
+                                (target.to_owned(), loss_history).into_py(gil.python())
+                            } else {
+                                target.to_owned().into_py(gil.python())
+                            });
                         },
                         dtype => {
                             pe!(Err(
@@ -114,3 +506,23 @@ where
         f32, f64
     }
 }
+
+impl<M, T> NumpyPrecomputedDecomposition<T> for M
+where
+    M: DimensionalReductionBinding<T>,
+    T: PrecomputedDimensionalReduction
+{
+    impl_numpy_precomputed_decompositions! {
+        f32, f64
+    }
+}
+
+impl<M, T> NumpyGraphDecomposition<T> for M
+where
+    M: DimensionalReductionBinding<T>,
+    T: GraphDimensionalReduction
+{
+    impl_numpy_graph_decompositions! {
+        f32, f64
+    }
+}
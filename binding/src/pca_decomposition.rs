@@ -0,0 +1,140 @@
+use crate::numpy_decomposition::NumpyDecomposition;
+use crate::traits::*;
+use crate::*;
+use dimensional_reduction::basic_decomposition::BasicDecomposition;
+use dimensional_reduction::PCADecomposition as PCADecompositionRust;
+use numpy::{PyArray1, PyArray2};
+use pyo3::types::PyDict;
+use pyo3::*;
+
+impl FromPyDict for PCADecompositionRust {
+    fn from_pydict(py_kwargs: Option<&types::PyDict>) -> PyResult<Self>
+    where
+        Self: Sized,
+    {
+        let py = pyo3::Python::acquire_gil();
+        let kwargs = normalize_kwargs!(py_kwargs, py.python());
+
+        Ok(Self::new(
+            pe!(BasicDecomposition::new(
+                "PCA Decomposition",
+                extract_value_rust_result!(kwargs, "random_state", u64),
+                extract_value_rust_result!(kwargs, "verbose", bool),
+            ))?,
+            extract_value_rust_result!(kwargs, "iterations", usize),
+        ))
+    }
+}
+
+///
+#[pyclass]
+#[derive(Clone)]
+#[pyo3(text_signature = "(*, iterations, random_state, verbose)")]
+pub struct PCADecomposition {
+    inner: PCADecompositionRust,
+}
+
+impl DimensionalReductionBinding<PCADecompositionRust> for PCADecomposition {
+    fn get_basic_dimensionality_reduction(&self) -> &PCADecompositionRust {
+        &self.inner
+    }
+}
+
+#[pymethods]
+impl PCADecomposition {
+    #[new]
+    #[args(py_kwargs = "**")]
+    /// Return a new instance of the PCA Decomposition model.
+    ///
+    /// Parameters
+    /// ------------------------
+    /// iterations: int = 30
+    ///     The number of subspace iterations used to compute the components. By default, 30.
+    /// random_state: int = 42
+    ///     The random state to reproduce the model initialization. By default, 42.
+    pub fn new(py_kwargs: Option<&PyDict>) -> PyResult<Self> {
+        Ok(Self {
+            inner: PCADecompositionRust::from_pydict(py_kwargs)?,
+        })
+    }
+
+    #[pyo3(
+        text_signature = "($self, matrix, number_of_dimensions, dtype, return_loss_history, initial_embedding)"
+    )]
+    pub fn fit_transform(
+        &self,
+        matrix: Py<PyAny>,
+        number_of_dimensions: Option<usize>,
+        dtype: Option<&str>,
+        return_loss_history: Option<bool>,
+        initial_embedding: Option<Py<PyAny>>,
+    ) -> PyResult<Py<PyAny>> {
+        self.fit_transform_binding(
+            matrix,
+            number_of_dimensions,
+            dtype,
+            return_loss_history,
+            initial_embedding,
+        )
+    }
+
+    #[pyo3(text_signature = "($self, matrix, number_of_components)")]
+    /// Returns the principal components and their explained variance ratios.
+    ///
+    /// Parameters
+    /// ------------------------
+    /// matrix: np.ndarray
+    ///     2D Matrix containing the feaures.
+    /// number_of_components: int = 2
+    ///     The number of principal components to compute.
+    pub fn fit_components(
+        &self,
+        matrix: Py<PyAny>,
+        number_of_components: Option<usize>,
+    ) -> PyResult<(Py<PyArray2<f64>>, Py<PyArray1<f64>>)> {
+        let gil = pyo3::Python::acquire_gil();
+        let matrix = matrix.as_ref(gil.python());
+        let number_of_components = number_of_components.unwrap_or(2);
+
+        macro_rules! fit_components {
+            ($dtype:ty) => {
+                if let Ok(matrix) = <&PyArray2<$dtype>>::extract(&matrix) {
+                    if !matrix.is_c_contiguous() {
+                        return pe!(Err(concat!(
+                            "The provided vector is not a contiguos vector in ",
+                            "C orientation."
+                        )));
+                    }
+
+                    let matrix_ref = unsafe { matrix.as_slice().unwrap() };
+                    let number_of_features = matrix.shape()[1];
+
+                    let components = pe!(self.inner.fit_components::<$dtype, f64>(
+                        number_of_components,
+                        matrix_ref,
+                        number_of_features,
+                    ))?;
+
+                    return Ok((
+                        PyArray1::from_slice(gil.python(), components.get_components())
+                            .reshape([number_of_components, number_of_features])?
+                            .to_owned(),
+                        PyArray1::from_slice(
+                            gil.python(),
+                            components.get_explained_variance_ratio(),
+                        )
+                        .to_owned(),
+                    ));
+                }
+            };
+        }
+
+        fit_components!(f32);
+        fit_components!(f64);
+
+        pe!(Err(concat!(
+            "The provided features are not supported ",
+            "in the principal components computation!"
+        )))
+    }
+}
@@ -1,13 +1,18 @@
 use crate::*;
 use crate::traits::*;
-use crate::numpy_decomposition::NumpyDecomposition;
-use dimensional_reduction::basic_iterative_decomposition::BasicIterativeDecomposition;
+use crate::numpy_decomposition::{
+    NumpyDecomposition, NumpyGraphDecomposition, NumpyPrecomputedDecomposition,
+};
+use dimensional_reduction::basic_iterative_decomposition::{
+    BasicIterativeDecomposition, IterativeParameters,
+};
 use dimensional_reduction::SampledSigmoidDecomposition as SampledSigmoidDecompositionRust;
+use dimensional_reduction::similarities::SimilarityKernel;
+use dimensional_reduction::target_similarities::TargetSimilarityKernel;
 use pyo3::types::PyDict;
 use pyo3::*;
-use pyo3::exceptions::PyValueError;
 
-impl FromPyDict for SampledSigmoidDecompositionRust {
+impl FromPyDict for SampledSigmoidDecompositionRust<SimilarityKernel, TargetSimilarityKernel> {
     fn from_pydict(py_kwargs: Option<&types::PyDict>) -> PyResult<Self>
     where
         Self: Sized,
@@ -15,13 +20,28 @@ impl FromPyDict for SampledSigmoidDecompositionRust {
         let py = pyo3::Python::acquire_gil();
         let kwargs = normalize_kwargs!(py_kwargs, py.python());
 
-        Ok(Self::from(pe!(BasicIterativeDecomposition::new(
-                extract_value_rust_result!(kwargs, "iterations", usize),
-                extract_value_rust_result!(kwargs, "learning_rate", f32),
+        pe!(Self::new(
+            pe!(BasicIterativeDecomposition::new(
                 "Sampled Sigmoid Decomposition",
-                extract_value_rust_result!(kwargs, "random_state", u64),
-                extract_value_rust_result!(kwargs, "verbose", bool),
+                IterativeParameters {
+                    iterations: extract_value_rust_result!(kwargs, "iterations", usize),
+                    learning_rate: extract_value_rust_result!(kwargs, "learning_rate", f32),
+                    learning_rate_schedule: extract_learning_rate_schedule!(kwargs),
+                    tolerance: extract_value_rust_result!(kwargs, "tolerance", f32),
+                    patience: extract_value_rust_result!(kwargs, "patience", usize),
+                    initialization: extract_initialization!(kwargs),
+                    deterministic: extract_value_rust_result!(kwargs, "deterministic", bool),
+                    optimizer: extract_optimizer!(kwargs),
+                    random_state: extract_value_rust_result!(kwargs, "random_state", u64),
+                    verbose: extract_value_rust_result!(kwargs, "verbose", bool),
+                },
             ))?,
+            extract_similarity!(kwargs),
+            extract_target_similarity!(kwargs),
+            extract_value_rust_result!(kwargs, "number_of_samples_per_point", usize),
+            extract_value_rust_result!(kwargs, "negative_samples", usize),
+            extract_value_rust_result!(kwargs, "number_of_neighbours", usize),
+            extract_value_rust_result!(kwargs, "negative_sampling_exponent", f32),
         ))
     }
 }
@@ -29,13 +49,13 @@ impl FromPyDict for SampledSigmoidDecompositionRust {
 ///
 #[pyclass]
 #[derive(Clone)]
-#[pyo3(text_signature = "(*, iterations, learning_rate, random_state, verbose)")]
+#[pyo3(text_signature = "(*, iterations, learning_rate, learning_rate_schedule, decay_rate, step_size, warmup_iterations, tolerance, patience, initialization, initialization_scale, deterministic, optimizer, momentum, beta_1, beta_2, epsilon, similarity, bandwidth, target_similarity, minimum_distance, spread, random_state, verbose, number_of_samples_per_point, negative_samples, number_of_neighbours, negative_sampling_exponent)")]
 pub struct SampledSigmoidDecomposition {
-    inner: SampledSigmoidDecompositionRust,
+    inner: SampledSigmoidDecompositionRust<SimilarityKernel, TargetSimilarityKernel>,
 }
 
-impl DimensionalReductionBinding<SampledSigmoidDecompositionRust> for SampledSigmoidDecomposition {
-    fn get_basic_dimensionality_reduction(&self) -> &SampledSigmoidDecompositionRust {
+impl DimensionalReductionBinding<SampledSigmoidDecompositionRust<SimilarityKernel, TargetSimilarityKernel>> for SampledSigmoidDecomposition {
+    fn get_basic_dimensionality_reduction(&self) -> &SampledSigmoidDecompositionRust<SimilarityKernel, TargetSimilarityKernel> {
         &self.inner
     }
 }
@@ -48,21 +68,193 @@ impl SampledSigmoidDecomposition {
     ///
     /// Parameters
     /// ------------------------
+    /// learning_rate_schedule: str = "constant"
+    ///     The schedule scaling the learning rate in each epoch, one of "constant",
+    ///     "linear", "exponential", "step" and "cosine". By default, "constant".
+    /// decay_rate: Optional[float] = None
+    ///     The factor of the "exponential" and "step" schedules. By default, 0.95 for
+    ///     the "exponential" schedule and 0.5 for the "step" schedule.
+    /// step_size: int = 10
+    ///     The number of epochs between the decays of the "step" schedule. By default, 10.
+    /// warmup_iterations: int = 0
+    ///     The number of epochs over which the learning rate grows linearly to its
+    ///     full value before the schedule decays it. By default, 0.
+    /// tolerance: Optional[float] = None
+    ///     The relative change of the epoch loss under which an epoch is considered
+    ///     not to improve the model. By default, no early stopping is done.
+    /// patience: int = 5
+    ///     The number of consecutive epochs without improvement after which
+    ///     the training is stopped. By default, 5.
+    /// initialization: str = "random_uniform"
+    ///     The strategy used to initialize the embedding, one of "random_uniform",
+    ///     "random_gaussian", "pca", "spectral" and "provided". By default, "random_uniform".
+    /// initialization_scale: float = 1e-4
+    ///     The standard deviation of the "random_gaussian" initialization. By default, 1e-4.
+    ///     It must not be provided with the other initializations.
+    /// deterministic: bool = False
+    ///     Whether to compute the updates of each epoch from the embedding at its start
+    ///     and apply them in a fixed order, so that the results do not depend on the
+    ///     number of threads, at the cost of memory and speed. By default, False.
+    /// optimizer: str = "sgd"
+    ///     The rule used to update the embedding, one of "sgd", "momentum", "adam" and
+    ///     "adagrad". The optimizers other than "sgd" accumulate the updates of each epoch
+    ///     from the embedding at its start, as when deterministic. By default, "sgd".
+    /// momentum: float = 0.9
+    ///     The momentum of the "momentum" optimizer. By default, 0.9.
+    /// beta_1: float = 0.9
+    ///     The decay of the first moment of the "adam" optimizer. By default, 0.9.
+    /// beta_2: float = 0.999
+    ///     The decay of the second moment of the "adam" optimizer. By default, 0.999.
+    /// epsilon: float = 1e-8
+    ///     The value added to the denominators of the "adam" and "adagrad" optimizers
+    ///     for numerical stability. By default, 1e-8.
+    /// similarity: str = "standardized_dot"
+    ///     The similarity between the original samples, one of "standardized_dot", "cosine",
+    ///     "gaussian", "manhattan", "tanimoto" and "hamming". By default, "standardized_dot".
+    /// bandwidth: float = 1.0
+    ///     The bandwidth of the "gaussian" and "manhattan" similarities. By default, 1.0.
+    /// target_similarity: str = "sigmoid_dot"
+    ///     The similarity between the embedded samples, one of "sigmoid_dot", "student_t",
+    ///     "umap" and "gaussian". The distance-based similarities produce layouts preserving
+    ///     the distances, while the sigmoid of the dot product produces radial layouts.
+    ///     By default, "sigmoid_dot".
+    /// minimum_distance: float = 0.1
+    ///     The distance under which the embedded samples are considered identical
+    ///     by the "umap" target similarity. By default, 0.1.
+    /// spread: float = 1.0
+    ///     The scale of the decay of the "umap" target similarity. By default, 1.0.
     /// random_state: int = 42
     ///     The random state to reproduce the model initialization and training. By default, 42.
+    /// number_of_samples_per_point: int = 0
+    ///     The number of positive pairs of each sample in each epoch, drawn among
+    ///     its most similar samples. By default, 0.
+    /// negative_samples: int = 1
+    ///     The number of negative pairs of each sample in each epoch. By default, 1.
+    /// number_of_neighbours: int = 10
+    ///     The number of most similar samples of each sample among which the positive
+    ///     pairs are drawn. By default, 10.
+    /// negative_sampling_exponent: float = 0.0
+    ///     The exponent of the degree, plus one, of the samples in the graph of the most
+    ///     similar samples, to which the probability of drawing them as negative samples
+    ///     is proportional. Use 0.75 to favour the hubs as in word2vec. By default, 0.0,
+    ///     that is uniform.
     pub fn new(py_kwargs: Option<&PyDict>) -> PyResult<Self> {
         Ok(Self {
-            inner: SampledSigmoidDecompositionRust::from_pydict(py_kwargs)?,
+            inner: SampledSigmoidDecompositionRust::<SimilarityKernel, TargetSimilarityKernel>::from_pydict(py_kwargs)?,
         })
     }
 
-    #[pyo3(text_signature = "($self, matrix, number_of_dimensions, dtype)")]
+    #[pyo3(
+        text_signature = "($self, matrix, number_of_dimensions, dtype, return_loss_history, initial_embedding)"
+    )]
     pub fn fit_transform(
         &self,
         matrix: Py<PyAny>,
         number_of_dimensions: Option<usize>,
         dtype: Option<&str>,
+        return_loss_history: Option<bool>,
+        initial_embedding: Option<Py<PyAny>>,
     ) -> PyResult<Py<PyAny>> {
-        self.fit_transform_binding(matrix, number_of_dimensions, dtype)
+        self.fit_transform_binding(
+            matrix,
+            number_of_dimensions,
+            dtype,
+            return_loss_history,
+            initial_embedding,
+        )
+    }
+
+    #[pyo3(
+        text_signature = "($self, matrix, kind, bandwidth, number_of_dimensions, dtype, return_loss_history, initial_embedding)"
+    )]
+    #[allow(clippy::too_many_arguments)]
+    /// Return the embedding of the samples of the provided precomputed matrix.
+    ///
+    /// Parameters
+    /// ------------------------
+    /// matrix: Union[np.ndarray, scipy.sparse.csr_matrix]
+    ///     Square matrix of the affinities or distances between the samples,
+    ///     used instead of the similarities between their features. The pairs
+    ///     missing from a sparse matrix have zero affinity.
+    /// kind: str = "affinity"
+    ///     Whether the matrix contains "affinity" values in [0, 1]
+    ///     or non-negative "distance" values. By default, "affinity".
+    /// bandwidth: float = 1.0
+    ///     The distance at which the affinity decays by a factor e,
+    ///     used by the "distance" kind. By default, 1.0.
+    /// number_of_dimensions: int = 2
+    ///     The dimensionality of the embedding. By default, 2.
+    /// dtype: str = "f32"
+    ///     The data type of the embedding, either "f32" or "f64". By default, "f32".
+    /// return_loss_history: bool = False
+    ///     Whether to also return the loss history of the fit.
+    /// initial_embedding: Optional[np.ndarray] = None
+    ///     2D Matrix with the initial embedding, used by the "provided" initialization.
+    ///     The "pca" initialization is not supported with precomputed matrices.
+    pub fn fit_transform_precomputed(
+        &self,
+        matrix: Py<PyAny>,
+        kind: Option<&str>,
+        bandwidth: Option<f32>,
+        number_of_dimensions: Option<usize>,
+        dtype: Option<&str>,
+        return_loss_history: Option<bool>,
+        initial_embedding: Option<Py<PyAny>>,
+    ) -> PyResult<Py<PyAny>> {
+        self.fit_transform_precomputed_binding(
+            matrix,
+            kind,
+            bandwidth,
+            number_of_dimensions,
+            dtype,
+            return_loss_history,
+            initial_embedding,
+        )
+    }
+
+    #[pyo3(
+        text_signature = "($self, graph, number_of_nodes, number_of_dimensions, dtype, return_loss_history, initial_embedding)"
+    )]
+    /// Return the embedding of the nodes of the provided graph.
+    ///
+    /// The positive pairs of each node are drawn among its neighbours with probability
+    /// proportional to the weight of their edge, and the negative pairs as for the other
+    /// inputs, producing a first-order LINE embedding. At least a positive pair per
+    /// point is required, see `number_of_samples_per_point`.
+    ///
+    /// Parameters
+    /// ------------------------
+    /// graph: Union[scipy.sparse.csr_matrix, Tuple[np.ndarray, np.ndarray], Tuple[np.ndarray, np.ndarray, np.ndarray]]
+    ///     Either the square weighted adjacency matrix of the graph, or the
+    ///     sources, destinations and optionally the weights of its undirected edges.
+    /// number_of_nodes: Optional[int] = None
+    ///     The number of nodes of a graph provided as edges. By default, one more
+    ///     than the largest node of the edges.
+    /// number_of_dimensions: int = 2
+    ///     The dimensionality of the embedding. By default, 2.
+    /// dtype: str = "f32"
+    ///     The data type of the embedding, either "f32" or "f64". By default, "f32".
+    /// return_loss_history: bool = False
+    ///     Whether to also return the loss history of the fit.
+    /// initial_embedding: Optional[np.ndarray] = None
+    ///     2D Matrix with the initial embedding, used by the "provided" initialization.
+    ///     The "pca" initialization is not supported with graphs.
+    pub fn fit_transform_graph(
+        &self,
+        graph: Py<PyAny>,
+        number_of_nodes: Option<usize>,
+        number_of_dimensions: Option<usize>,
+        dtype: Option<&str>,
+        return_loss_history: Option<bool>,
+        initial_embedding: Option<Py<PyAny>>,
+    ) -> PyResult<Py<PyAny>> {
+        self.fit_transform_graph_binding(
+            graph,
+            number_of_nodes,
+            number_of_dimensions,
+            dtype,
+            return_loss_history,
+            initial_embedding,
+        )
     }
 }
@@ -0,0 +1,152 @@
+use crate::numpy_decomposition::NumpyDecomposition;
+use crate::traits::*;
+use crate::*;
+use dimensional_reduction::basic_iterative_decomposition::{
+    BasicIterativeDecomposition, IterativeParameters,
+};
+use dimensional_reduction::TSNEDecomposition as TSNEDecompositionRust;
+use pyo3::types::PyDict;
+use pyo3::*;
+
+impl FromPyDict for TSNEDecompositionRust {
+    fn from_pydict(py_kwargs: Option<&types::PyDict>) -> PyResult<Self>
+    where
+        Self: Sized,
+    {
+        let py = pyo3::Python::acquire_gil();
+        let kwargs = normalize_kwargs!(py_kwargs, py.python());
+
+        pe!(Self::new(
+            pe!(BasicIterativeDecomposition::new(
+                "t-SNE Decomposition",
+                IterativeParameters {
+                    iterations: extract_value_rust_result!(kwargs, "iterations", usize),
+                    learning_rate: extract_value_rust_result!(kwargs, "learning_rate", f32),
+                    learning_rate_schedule: extract_learning_rate_schedule!(kwargs),
+                    tolerance: extract_value_rust_result!(kwargs, "tolerance", f32),
+                    patience: extract_value_rust_result!(kwargs, "patience", usize),
+                    initialization: extract_initialization!(kwargs),
+                    deterministic: extract_value_rust_result!(kwargs, "deterministic", bool),
+                    optimizer: extract_optimizer!(kwargs),
+                    random_state: extract_value_rust_result!(kwargs, "random_state", u64),
+                    verbose: extract_value_rust_result!(kwargs, "verbose", bool),
+                },
+            ))?,
+            extract_value_rust_result!(kwargs, "perplexity", f32),
+            extract_value_rust_result!(kwargs, "early_exaggeration", f32),
+            extract_value_rust_result!(kwargs, "early_exaggeration_iterations", usize),
+            extract_value_rust_result!(kwargs, "momentum", f32),
+            extract_value_rust_result!(kwargs, "theta", f32),
+            extract_value_rust_result!(kwargs, "depth", usize),
+            extract_neighbour_search!(kwargs),
+        ))
+    }
+}
+
+///
+#[pyclass]
+#[derive(Clone)]
+#[pyo3(text_signature = "(*, iterations, learning_rate, learning_rate_schedule, decay_rate, step_size, warmup_iterations, tolerance, patience, initialization, initialization_scale, deterministic, random_state, verbose, perplexity, early_exaggeration, early_exaggeration_iterations, momentum, theta, depth, neighbour_search, nn_descent_iterations, nn_descent_tolerance)")]
+pub struct TSNEDecomposition {
+    inner: TSNEDecompositionRust,
+}
+
+impl DimensionalReductionBinding<TSNEDecompositionRust> for TSNEDecomposition {
+    fn get_basic_dimensionality_reduction(&self) -> &TSNEDecompositionRust {
+        &self.inner
+    }
+}
+
+#[pymethods]
+impl TSNEDecomposition {
+    #[new]
+    #[args(py_kwargs = "**")]
+    /// Return a new instance of the t-SNE Decomposition model.
+    ///
+    /// Parameters
+    /// ------------------------
+    /// iterations: int = 50
+    ///     The number of epochs to run. t-SNE usually requires several hundreds of epochs.
+    /// learning_rate: float = 1.0
+    ///     The learning rate, relative to the number of samples divided by four times
+    ///     the early exaggeration, with a minimum of 50. By default, 1.0.
+    /// learning_rate_schedule: str = "constant"
+    ///     The schedule scaling the learning rate in each epoch, one of "constant",
+    ///     "linear", "exponential", "step" and "cosine". By default, "constant".
+    /// decay_rate: Optional[float] = None
+    ///     The factor of the "exponential" and "step" schedules. By default, 0.95 for
+    ///     the "exponential" schedule and 0.5 for the "step" schedule.
+    /// step_size: int = 10
+    ///     The number of epochs between the decays of the "step" schedule. By default, 10.
+    /// warmup_iterations: int = 0
+    ///     The number of epochs over which the learning rate grows linearly to its
+    ///     full value before the schedule decays it. By default, 0.
+    /// tolerance: Optional[float] = None
+    ///     The relative change of the epoch loss under which an epoch is considered
+    ///     not to improve the model. By default, no early stopping is done.
+    /// patience: int = 5
+    ///     The number of consecutive epochs without improvement after which
+    ///     the training is stopped. By default, 5.
+    /// initialization: str = "random_uniform"
+    ///     The strategy used to initialize the embedding, one of "random_uniform",
+    ///     "random_gaussian", "pca", "spectral" and "provided". By default, "random_uniform".
+    /// initialization_scale: float = 1e-4
+    ///     The standard deviation of the "random_gaussian" initialization. By default, 1e-4.
+    ///     It must not be provided with the other initializations.
+    /// deterministic: bool = False
+    ///     Whether to reduce the gradients of each epoch in a fixed order, so that the
+    ///     results do not depend on the number of threads. As t-SNE always computes the
+    ///     gradients from the embedding at the start of each epoch, this has no cost.
+    /// random_state: int = 42
+    ///     The random state to reproduce the model initialization and training. By default, 42.
+    /// perplexity: float = 30.0
+    ///     The effective number of neighbours of each sample, which must be smaller
+    ///     than the number of samples. By default, 30.
+    /// early_exaggeration: float = 12.0
+    ///     The factor multiplying the affinities of the original samples in the
+    ///     first epochs, so that the clusters form before being spread. By default, 12.
+    /// early_exaggeration_iterations: Optional[int] = None
+    ///     The number of epochs with early exaggeration. By default, a quarter of the epochs.
+    /// momentum: float = 0.8
+    ///     The momentum of the updates after the early exaggeration, during which
+    ///     a momentum of 0.5 is used. By default, 0.8.
+    /// theta: float = 0.5
+    ///     The Barnes-Hut opening criterion of the repulsive forces, where smaller
+    ///     values are more accurate and slower. By default, 0.5.
+    /// depth: Optional[int] = None
+    ///     The depth of the grid used to approximate the repulsive forces. By default,
+    ///     the smallest depth with enough leafs for a handful of samples per leaf.
+    /// neighbour_search: str = "exact"
+    ///     The search of the nearest neighbours of the original samples, either "exact",
+    ///     which takes quadratic time, or "approximate", which runs NN-descent. By default, "exact".
+    /// nn_descent_iterations: int = 10
+    ///     The maximum number of NN-descent iterations of the "approximate" search. By default, 10.
+    /// nn_descent_tolerance: float = 0.001
+    ///     The fraction of the neighbours that must change in an NN-descent iteration for
+    ///     the "approximate" search to continue. By default, 0.001.
+    pub fn new(py_kwargs: Option<&PyDict>) -> PyResult<Self> {
+        Ok(Self {
+            inner: TSNEDecompositionRust::from_pydict(py_kwargs)?,
+        })
+    }
+
+    #[pyo3(
+        text_signature = "($self, matrix, number_of_dimensions, dtype, return_loss_history, initial_embedding)"
+    )]
+    pub fn fit_transform(
+        &self,
+        matrix: Py<PyAny>,
+        number_of_dimensions: Option<usize>,
+        dtype: Option<&str>,
+        return_loss_history: Option<bool>,
+        initial_embedding: Option<Py<PyAny>>,
+    ) -> PyResult<Py<PyAny>> {
+        self.fit_transform_binding(
+            matrix,
+            number_of_dimensions,
+            dtype,
+            return_loss_history,
+            initial_embedding,
+        )
+    }
+}
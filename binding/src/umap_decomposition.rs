@@ -0,0 +1,162 @@
+use crate::numpy_decomposition::NumpyDecomposition;
+use crate::traits::*;
+use crate::*;
+use dimensional_reduction::basic_iterative_decomposition::{
+    BasicIterativeDecomposition, IterativeParameters,
+};
+use dimensional_reduction::target_similarities::UMAPCurve;
+use dimensional_reduction::UMAPDecomposition as UMAPDecompositionRust;
+use pyo3::types::PyDict;
+use pyo3::*;
+
+impl FromPyDict for UMAPDecompositionRust {
+    fn from_pydict(py_kwargs: Option<&types::PyDict>) -> PyResult<Self>
+    where
+        Self: Sized,
+    {
+        let py = pyo3::Python::acquire_gil();
+        let kwargs = normalize_kwargs!(py_kwargs, py.python());
+
+        pe!(Self::new(
+            pe!(BasicIterativeDecomposition::new(
+                "UMAP Decomposition",
+                IterativeParameters {
+                    iterations: extract_value_rust_result!(kwargs, "iterations", usize),
+                    learning_rate: extract_value_rust_result!(kwargs, "learning_rate", f32),
+                    learning_rate_schedule: extract_learning_rate_schedule!(kwargs),
+                    tolerance: extract_value_rust_result!(kwargs, "tolerance", f32),
+                    patience: extract_value_rust_result!(kwargs, "patience", usize),
+                    initialization: extract_initialization!(kwargs),
+                    deterministic: extract_value_rust_result!(kwargs, "deterministic", bool),
+                    optimizer: extract_optimizer!(kwargs),
+                    random_state: extract_value_rust_result!(kwargs, "random_state", u64),
+                    verbose: extract_value_rust_result!(kwargs, "verbose", bool),
+                },
+            ))?,
+            pe!(UMAPCurve::from_distances(
+                extract_value_rust_result!(kwargs, "minimum_distance", f32),
+                extract_value_rust_result!(kwargs, "spread", f32),
+            ))?,
+            extract_value_rust_result!(kwargs, "number_of_neighbours", usize),
+            extract_value_rust_result!(kwargs, "negative_samples", usize),
+            extract_value_rust_result!(kwargs, "repulsion_strength", f32),
+            extract_neighbour_search!(kwargs),
+        ))
+    }
+}
+
+///
+#[pyclass]
+#[derive(Clone)]
+#[pyo3(text_signature = "(*, iterations, learning_rate, learning_rate_schedule, decay_rate, step_size, warmup_iterations, tolerance, patience, initialization, initialization_scale, deterministic, optimizer, momentum, beta_1, beta_2, epsilon, random_state, verbose, minimum_distance, spread, number_of_neighbours, negative_samples, repulsion_strength, neighbour_search, nn_descent_iterations, nn_descent_tolerance)")]
+pub struct UMAPDecomposition {
+    inner: UMAPDecompositionRust,
+}
+
+impl DimensionalReductionBinding<UMAPDecompositionRust> for UMAPDecomposition {
+    fn get_basic_dimensionality_reduction(&self) -> &UMAPDecompositionRust {
+        &self.inner
+    }
+}
+
+#[pymethods]
+impl UMAPDecomposition {
+    #[new]
+    #[args(py_kwargs = "**")]
+    /// Return a new instance of the UMAP Decomposition model.
+    ///
+    /// Parameters
+    /// ------------------------
+    /// iterations: int = 50
+    ///     The number of epochs to run. UMAP usually requires a couple hundreds of epochs.
+    /// learning_rate: float = 1.0
+    ///     The initial learning rate of the schedule. By default, 1.0.
+    /// learning_rate_schedule: str = "linear"
+    ///     The schedule scaling the learning rate in each epoch, one of "constant",
+    ///     "linear", "exponential", "step" and "cosine". By default, "linear",
+    ///     so that the learning rate decays linearly to zero over the epochs.
+    /// decay_rate: Optional[float] = None
+    ///     The factor of the "exponential" and "step" schedules. By default, 0.95 for
+    ///     the "exponential" schedule and 0.5 for the "step" schedule.
+    /// step_size: int = 10
+    ///     The number of epochs between the decays of the "step" schedule. By default, 10.
+    /// warmup_iterations: int = 0
+    ///     The number of epochs over which the learning rate grows linearly to its
+    ///     full value before the schedule decays it. By default, 0.
+    /// tolerance: Optional[float] = None
+    ///     The relative change of the epoch loss under which an epoch is considered
+    ///     not to improve the model. By default, no early stopping is done.
+    /// patience: int = 5
+    ///     The number of consecutive epochs without improvement after which
+    ///     the training is stopped. By default, 5.
+    /// initialization: str = "random_uniform"
+    ///     The strategy used to initialize the embedding, one of "random_uniform",
+    ///     "random_gaussian", "pca", "spectral" and "provided". By default, "random_uniform".
+    /// initialization_scale: float = 1e-4
+    ///     The standard deviation of the "random_gaussian" initialization. By default, 1e-4.
+    ///     It must not be provided with the other initializations.
+    /// deterministic: bool = False
+    ///     Whether to compute the updates of each epoch from the embedding at its start
+    ///     and apply them in a fixed order, so that the results do not depend on the
+    ///     number of threads, at the cost of memory and speed. By default, False.
+    /// optimizer: str = "sgd"
+    ///     The rule used to update the embedding, one of "sgd", "momentum", "adam" and
+    ///     "adagrad". The optimizers other than "sgd" accumulate the updates of each epoch
+    ///     from the embedding at its start, as when deterministic. By default, "sgd".
+    /// momentum: float = 0.9
+    ///     The momentum of the "momentum" optimizer. By default, 0.9.
+    /// beta_1: float = 0.9
+    ///     The decay of the first moment of the "adam" optimizer. By default, 0.9.
+    /// beta_2: float = 0.999
+    ///     The decay of the second moment of the "adam" optimizer. By default, 0.999.
+    /// epsilon: float = 1e-8
+    ///     The value added to the denominators of the "adam" and "adagrad" optimizers
+    ///     for numerical stability. By default, 1e-8.
+    /// random_state: int = 42
+    ///     The random state to reproduce the model initialization and training. By default, 42.
+    /// minimum_distance: float = 0.1
+    ///     The distance under which the embedded samples are considered identical,
+    ///     so that smaller values produce denser clusters. By default, 0.1.
+    /// spread: float = 1.0
+    ///     The scale of the decay of the similarity of the embedded samples. By default, 1.0.
+    /// number_of_neighbours: int = 15
+    ///     The number of neighbours of each sample in the fuzzy simplicial set, where larger
+    ///     values preserve more of the global structure. By default, 15.
+    /// negative_samples: int = 5
+    ///     The number of negative samples drawn for each sampled edge. By default, 5.
+    /// repulsion_strength: float = 1.0
+    ///     The weight of the negative samples in the loss. By default, 1.0.
+    /// neighbour_search: str = "exact"
+    ///     The search of the nearest neighbours of the original samples, either "exact",
+    ///     which takes quadratic time, or "approximate", which runs NN-descent. By default, "exact".
+    /// nn_descent_iterations: int = 10
+    ///     The maximum number of NN-descent iterations of the "approximate" search. By default, 10.
+    /// nn_descent_tolerance: float = 0.001
+    ///     The fraction of the neighbours that must change in an NN-descent iteration for
+    ///     the "approximate" search to continue. By default, 0.001.
+    pub fn new(py_kwargs: Option<&PyDict>) -> PyResult<Self> {
+        Ok(Self {
+            inner: UMAPDecompositionRust::from_pydict(py_kwargs)?,
+        })
+    }
+
+    #[pyo3(
+        text_signature = "($self, matrix, number_of_dimensions, dtype, return_loss_history, initial_embedding)"
+    )]
+    pub fn fit_transform(
+        &self,
+        matrix: Py<PyAny>,
+        number_of_dimensions: Option<usize>,
+        dtype: Option<&str>,
+        return_loss_history: Option<bool>,
+        initial_embedding: Option<Py<PyAny>>,
+    ) -> PyResult<Py<PyAny>> {
+        self.fit_transform_binding(
+            matrix,
+            number_of_dimensions,
+            dtype,
+            return_loss_history,
+            initial_embedding,
+        )
+    }
+}
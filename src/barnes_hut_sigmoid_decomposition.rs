@@ -3,32 +3,305 @@ use std::sync::atomic::{AtomicUsize, Ordering};
 use crate::traits::*;
 use crate::{
     basic_iterative_decomposition::BasicIterativeDecomposition,
-    traits::{Decomposition, DimensionalReduction, GenericFeature, IterativeDecomposition},
-    utils::{dot, normal_dot, sigmoid, DataRaceAware},
+    errors::DimensionalReductionError,
+    matrices::{OriginalMatrix, Sample},
+    optimizers::OptimizerState,
+    similarities::{Similarity, StandardizedDot},
+    target_similarities::{SigmoidDot, TargetSimilarity},
+    traits::{DimensionalReduction, GenericFeature, IterativeDecomposition},
+    utils::{
+        binary_cross_entropy, deterministic_partitioned_sum, sigmoid,
+        validate_fit_transform_matrix_arguments, DataRaceAware,
+    },
 };
 use num_traits::{AsPrimitive, Float, Zero};
 use rayon::prelude::*;
 
-use std::arch::asm;
-
+/// Deposits the low bits of `x` in the positions of the bits set in `mask`,
+/// starting from the least significant one, as the BMI2 `pdep` instruction.
 #[inline(always)]
-fn pdep(x: u64, mask: u64) -> u64 {
-    let mut res;
-    unsafe {
-        asm!(
-            "pdep {res}, {x}, {mask}",
-            x = in(reg) x,
-            mask = in(reg) mask,
-            res = lateout(reg) res,
-        )
+fn pdep_portable(mut x: u64, mut mask: u64) -> u64 {
+    let mut result = 0;
+    while mask != 0 {
+        let lowest_bit = mask & mask.wrapping_neg();
+        if x & 1 == 1 {
+            result |= lowest_bit;
+        }
+        x >>= 1;
+        mask ^= lowest_bit;
+    }
+    result
+}
+
+/// Deposits the low bits of `x` in the positions of the bits set in `mask`
+/// using the BMI2 `pdep` instruction.
+///
+/// # Safety
+/// The current CPU must support BMI2.
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "bmi2")]
+#[inline]
+unsafe fn pdep_bmi2(x: u64, mask: u64) -> u64 {
+    std::arch::x86_64::_pdep_u64(x, mask)
+}
+
+/// Returns the result of the `cpuid` instruction for the provided leaf.
+///
+/// # Safety
+/// The current CPU must support `cpuid`, as every x86_64 CPU does. The wrapper
+/// keeps the calls valid with the toolchains where `__cpuid` is an unsafe function.
+#[cfg(target_arch = "x86_64")]
+unsafe fn cpuid(leaf: u32) -> std::arch::x86_64::CpuidResult {
+    std::arch::x86_64::__cpuid(leaf)
+}
+
+/// Returns whether the current CPU has a fast BMI2 `pdep` instruction.
+///
+/// The AMD CPUs before Zen 3 support BMI2, but implement `pdep` in microcode
+/// with a latency that grows with the number of bits set in the mask, making
+/// it slower than the portable implementation. The same holds for the Hygon
+/// CPUs, which are based on Zen 1.
+#[cfg(target_arch = "x86_64")]
+fn has_fast_pdep() -> bool {
+    if !is_x86_feature_detected!("bmi2") {
+        return false;
+    }
+    let vendor = unsafe { cpuid(0) };
+    let vendor = [vendor.ebx, vendor.edx, vendor.ecx]
+        .iter()
+        .flat_map(|register| register.to_le_bytes())
+        .collect::<Vec<u8>>();
+    if vendor != *b"AuthenticAMD" && vendor != *b"HygonGenuine" {
+        return true;
+    }
+    let signature = unsafe { cpuid(1) }.eax;
+    let base_family = (signature >> 8) & 0xF;
+    let family = if base_family == 0xF {
+        base_family + ((signature >> 20) & 0xFF)
+    } else {
+        base_family
     };
-    res
+    // Zen 3 is the first AMD family with a hardware pdep, while the Hygon
+    // CPUs report the family 0x18.
+    family >= 0x19
+}
+
+/// Implementation of `pdep` used by the grids to compute the cell IDs.
+///
+/// The grids are generic over the implementation, which is chosen once
+/// before training, so that the cell IDs are computed with direct calls.
+pub(crate) trait Pdep: Copy + Send + Sync {
+    /// Deposits the low bits of `x` in the positions of the bits set in `mask`.
+    fn deposit(self, x: u64, mask: u64) -> u64;
+}
+
+/// Portable implementation of `pdep`, available on every CPU.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct PortablePdep;
+
+impl Pdep for PortablePdep {
+    #[inline(always)]
+    fn deposit(self, x: u64, mask: u64) -> u64 {
+        pdep_portable(x, mask)
+    }
+}
+
+/// Implementation of `pdep` with the BMI2 instruction, which
+/// can only be built when the current CPU has a fast `pdep`.
+#[cfg(target_arch = "x86_64")]
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Bmi2Pdep(());
+
+#[cfg(target_arch = "x86_64")]
+impl Bmi2Pdep {
+    /// Returns the BMI2 implementation if the current CPU has a fast `pdep`.
+    pub(crate) fn detect() -> Option<Self> {
+        if has_fast_pdep() {
+            Some(Self(()))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+impl Pdep for Bmi2Pdep {
+    #[inline(always)]
+    fn deposit(self, x: u64, mask: u64) -> u64 {
+        // The implementation is only built when the CPU supports BMI2.
+        unsafe { pdep_bmi2(x, mask) }
+    }
+}
+
+/// Largest target dimension for which every layer of the grid splits all of
+/// the axes, producing a 2^d-ary tree (a quadtree in 2D and an octree in 3D).
+/// For higher dimensions, the 2^d children of each cell would make even shallow
+/// grids too large, so every layer splits a single axis, cycling over the axes
+/// as in a kd-tree.
+const MAXIMUM_ORTHANT_TREE_DIMENSION: usize = 3;
+
+/// Returns the number of splits in each layer of a grid with the provided target dimension.
+pub(crate) fn get_bits_per_layer(target_dimension: usize) -> usize {
+    if target_dimension <= MAXIMUM_ORTHANT_TREE_DIMENSION {
+        target_dimension
+    } else {
+        1
+    }
+}
+
+/// Returns the mask of the bits of the cell IDs of the provided layer
+/// that are associated to the provided axis.
+///
+/// The splits of a layer are assigned to the axes cyclically, and the first
+/// split is the most significant bit of the cell ID.
+fn compute_axis_mask(
+    layer: usize,
+    axis: usize,
+    target_dimension: usize,
+    bits_per_layer: usize,
+) -> u64 {
+    let splits = layer * bits_per_layer;
+    (axis..splits)
+        .step_by(target_dimension)
+        .fold(0_u64, |mask, split| mask | (1 << (splits - 1 - split)))
+}
+
+/// Returns the coordinate along an axis of the cell containing the provided value.
+///
+/// # Arguments
+/// * `value`: The value of the point along the axis.
+/// * `min_value`: The minimum value of the grid along the axis.
+/// * `max_value`: The maximum value of the grid along the axis.
+/// * `splits`: The number of times the axis was split.
+fn get_cell_coordinate<Target>(
+    value: Target,
+    min_value: Target,
+    max_value: Target,
+    splits: u32,
+) -> usize
+where
+    Target: Float + AsPrimitive<usize>,
+    usize: AsPrimitive<Target>,
+{
+    let grid_side: Target = (1_usize << splits).as_();
+    (grid_side * ((value - min_value) / (Target::epsilon() + max_value - min_value)))
+        .floor()
+        .min(grid_side - Target::one())
+        .as_()
+}
+
+/// Returns the length of the longest side of the cells split the provided number of times.
+fn get_cell_size<Target>(
+    min_values: &[Target],
+    max_values: &[Target],
+    splits: impl Fn(usize) -> u32,
+) -> Target
+where
+    Target: Float + GenericFeature,
+    usize: AsPrimitive<Target>,
+{
+    min_values
+        .iter()
+        .zip(max_values.iter())
+        .enumerate()
+        .map(|(axis, (&min_value, &max_value))| {
+            let grid_side: Target = (1_usize << splits(axis)).as_();
+            (max_value - min_value) / grid_side
+        })
+        .fold(Target::zero(), Target::max)
+}
+
+/// Grid partitioning the target space used to approximate the far away
+/// samples with the averages of the cells.
+pub(crate) trait BarnesHutGrid<Target, Original> {
+    /// Updates the cells with the current positions of the samples.
+    fn prepare<M>(
+        &mut self,
+        target_features: &[Target],
+        original: &M,
+    ) -> Result<(), DimensionalReductionError>
+    where
+        Original: GenericFeature + Float,
+        M: OriginalMatrix<Original>;
+
+    /// Returns the IDs of the cells whose averages approximate the samples far away
+    /// from the provided sample, and the IDs of the leafs whose samples
+    /// must be considered one by one.
+    ///
+    /// # Arguments
+    /// * `sample_number`: The number of the sample whose interactions are to be computed.
+    /// * `point`: The current position of the sample.
+    /// * `theta`: The Barnes-Hut opening criterion. When provided, a cell not containing
+    ///   the sample is approximated by its average when the length of its longest side is
+    ///   smaller than `theta` times the distance between the sample and the cell average,
+    ///   and is otherwise opened to its children. When not provided, the siblings of the
+    ///   cells containing the sample are approximated and only the leaf containing the
+    ///   sample is considered one by one.
+    fn get_far_away_cells_and_near_leafs(
+        &self,
+        sample_number: usize,
+        point: &[Target],
+        theta: Option<Target>,
+    ) -> (Vec<usize>, Vec<usize>);
+
+    /// Returns the samples in the provided leaf.
+    fn get_leaf_samples(&self, leaf: usize) -> &[usize];
+
+    /// Returns the number of cells in the grid.
+    fn get_number_of_cells(&self) -> usize;
+
+    /// Returns the target average, the original average and the population of the provided cell.
+    fn get_cell_properties(&self, cell: usize) -> (&[Target], &[Original], usize);
+
+    /// Returns the target average, the original average, the gradient
+    /// and the population of the provided cell.
+    ///
+    /// # Safety
+    /// The gradient is shared among threads, which may update it concurrently.
+    #[allow(clippy::mut_from_ref)]
+    unsafe fn get_mut_cell_properties(
+        &self,
+        cell: usize,
+    ) -> (&[Target], &[Original], &mut [Target], usize);
+
+    /// Propagates down to the leafs of the grid the partial gradients.
+    fn downpropagate_gradient(&mut self);
+
+    /// Applies to the samples the gradients of the leafs containing them.
+    fn apply_gradient(&self, target_features: &mut [Target]);
+
+    /// Return iterator on the samples in the provided leafs.
+    fn iter_leafs_samples<'a>(&'a self, leafs: &'a [usize]) -> impl Iterator<Item = usize> + 'a {
+        leafs
+            .iter()
+            .flat_map(move |&leaf| self.get_leaf_samples(leaf).iter().copied())
+    }
+
+    /// Return the properties of the provided cells.
+    fn iter_mut_cells_properties<'a>(
+        &'a self,
+        cells: &'a [usize],
+    ) -> impl Iterator<Item = (&'a [Target], &'a [Original], &'a mut [Target], usize)> + 'a
+    where
+        Target: 'a,
+        Original: 'a,
+    {
+        cells
+            .iter()
+            .map(|&cell| unsafe { self.get_mut_cell_properties(cell) })
+    }
 }
 
-struct GradientGrid<Target, Original> {
+/// Grid of cells where the cell IDs are the Morton codes of the cell coordinates,
+/// so that the ID of the parent of a cell is the prefix of the ID of the cell.
+pub(crate) struct GradientGrid<Target, Original, P> {
     depth: usize,
     target_dimension: usize,
     original_dimension: usize,
+    bits_per_layer: usize,
+    layers_mask: u64,
+    pdep: P,
+    axis_masks: Vec<u64>,
     gradients: DataRaceAware<Vec<Target>>,
     target_averages: DataRaceAware<Vec<Target>>,
     original_averages: DataRaceAware<Vec<Original>>,
@@ -39,8 +312,9 @@ struct GradientGrid<Target, Original> {
     max_values: Vec<Target>,
 }
 
-impl<Target, Original> GradientGrid<Target, Original>
+impl<Target, Original, P> GradientGrid<Target, Original, P>
 where
+    P: Pdep,
     Target: Float + GenericFeature,
     Original: GenericFeature + Zero,
     usize: AsPrimitive<Target>,
@@ -48,32 +322,68 @@ where
     Target: AsPrimitive<usize>,
     Original: AsPrimitive<usize>,
 {
-    fn new(depth: usize, target_dimension: usize, original_dimension: usize) -> Self {
-        if target_dimension != 2 {
-            unimplemented!("Only target_dimension == 2!");
+    pub(crate) fn new(
+        pdep: P,
+        depth: usize,
+        target_dimension: usize,
+        original_dimension: usize,
+    ) -> Result<Self, DimensionalReductionError> {
+        let bits_per_layer = get_bits_per_layer(target_dimension);
+
+        if depth == 0 || (depth + 1) * bits_per_layer >= 64 {
+            return Err(DimensionalReductionError::InvalidParameter {
+                parameter: "depth",
+                value: depth.to_string(),
+                expected: "strictly positive and such that the cell IDs fit in 64 bits",
+            });
         }
 
-        let total_number_of_elements = Self::get_number_of_elements_before_layer(depth + 1);
-        let grid_size = 2_u32.pow(2 * depth as u32) as usize;
+        // The mask with a bit set every `bits_per_layer` bits, which is used
+        // to compute the number of cells before each layer.
+        let layers_mask = (0..64)
+            .step_by(bits_per_layer)
+            .fold(0_u64, |mask, position| mask | (1 << position));
 
-        Self {
+        // For each layer, the mask of the bits of the cell ID associated to each axis.
+        let axis_masks = (0..=depth)
+            .flat_map(|layer| {
+                (0..target_dimension).map(move |axis| {
+                    compute_axis_mask(layer, axis, target_dimension, bits_per_layer)
+                })
+            })
+            .collect::<Vec<u64>>();
+
+        let mut grid = Self {
             depth,
             target_dimension,
             original_dimension,
-            gradients: DataRaceAware::from(vec![
-                Target::zero();
-                target_dimension * total_number_of_elements
-            ]),
-            target_averages: DataRaceAware::from(vec![Target::zero(); 0]),
-            original_averages: DataRaceAware::from(vec![Original::zero(); 0]),
-            populations: (0..total_number_of_elements)
-                .map(|_| AtomicUsize::new(0))
-                .collect::<Vec<AtomicUsize>>(),
-            reverse_index: vec![Vec::new(); grid_size],
+            bits_per_layer,
+            layers_mask,
+            pdep,
+            axis_masks,
+            gradients: DataRaceAware::from(Vec::new()),
+            target_averages: DataRaceAware::from(Vec::new()),
+            original_averages: DataRaceAware::from(Vec::new()),
+            populations: Vec::new(),
+            reverse_index: Vec::new(),
             index: Vec::new(),
             min_values: Vec::new(),
             max_values: Vec::new(),
-        }
+        };
+
+        let total_number_of_elements = grid.get_number_of_elements_before_layer(depth + 1);
+        let grid_size = 1 << (depth * bits_per_layer);
+
+        grid.gradients = DataRaceAware::from(vec![
+            Target::zero();
+            target_dimension * total_number_of_elements
+        ]);
+        grid.populations = (0..total_number_of_elements)
+            .map(|_| AtomicUsize::new(0))
+            .collect::<Vec<AtomicUsize>>();
+        grid.reverse_index = vec![Vec::new(); grid_size];
+
+        Ok(grid)
     }
 
     unsafe fn reset(&mut self) {
@@ -97,33 +407,42 @@ where
         self.reverse_index.iter_mut().for_each(|r| r.clear());
     }
 
+    /// Returns the number of children of each cell.
+    fn get_branching_factor(&self) -> usize {
+        1 << self.bits_per_layer
+    }
+
+    /// Returns the mask of the bits of the cell IDs of the provided layer
+    /// that are associated to the provided axis.
+    fn get_axis_mask(&self, layer: usize, axis: usize) -> u64 {
+        self.axis_masks[layer * self.target_dimension + axis]
+    }
+
     /// Return the cordinates of the cell containing the provided point.
     ///
     /// # Safety
     /// If the minimum and maximum values bonds are not properly
     /// updated when this function is called, the index returned
     /// may cause out-of-bounds exceptions.
-    fn get_cell_coordinates_unchecked(&self, x: Target, y: Target, layer: usize) -> (usize, usize) {
-        let grid_side: Target = (2_usize.pow(layer as u32)).as_();
-        (
-            (grid_side
-                * ((x - self.min_values[0])
-                    / (Target::epsilon() + self.max_values[0] - self.min_values[0])))
-                .floor()
-                .min(grid_side - Target::one())
-                .as_(),
-            (grid_side
-                * ((y - self.min_values[1])
-                    / (Target::epsilon() + self.max_values[1] - self.min_values[1])))
-                .floor()
-                .min(grid_side - Target::one())
-                .as_(),
-        )
+    fn iter_cell_coordinates_unchecked<'a>(
+        &'a self,
+        point: &'a [Target],
+        layer: usize,
+    ) -> impl Iterator<Item = usize> + 'a {
+        point.iter().copied().enumerate().map(move |(axis, value)| {
+            get_cell_coordinate(
+                value,
+                self.min_values[axis],
+                self.max_values[axis],
+                // The number of times this axis was split up to this layer.
+                self.get_axis_mask(layer, axis).count_ones(),
+            )
+        })
     }
 
     /// Returns number of elements before layer.
-    fn get_number_of_elements_before_layer(layer: usize) -> usize {
-        pdep((1 << layer as u64) - 1, 6148914691236517205) as usize - 1
+    fn get_number_of_elements_before_layer(&self, layer: usize) -> usize {
+        self.pdep.deposit((1 << layer as u64) - 1, self.layers_mask) as usize - 1
     }
 
     /// Return the ID of the cell containing the provided point.
@@ -132,11 +451,15 @@ where
     /// If the minimum and maximum values bonds are not properly
     /// updated when this function is called, the index returned
     /// may cause out-of-bounds exceptions.
-    fn get_relative_cell_id_unchecked(&self, x: Target, y: Target, layer: usize) -> usize {
-        let (row_number, column_number) = self.get_cell_coordinates_unchecked(x, y, layer);
-
-        (pdep(row_number as u64, 12297829382473034410)
-            | pdep(column_number as u64, 6148914691236517205)) as usize
+    fn get_relative_cell_id_unchecked(&self, point: &[Target], layer: usize) -> usize {
+        self.iter_cell_coordinates_unchecked(point, layer)
+            .enumerate()
+            .fold(0, |cell_id, (axis, coordinate)| {
+                cell_id
+                    | self
+                        .pdep
+                        .deposit(coordinate as u64, self.get_axis_mask(layer, axis))
+            }) as usize
     }
 
     /// Return the ID of the cell containing the provided point.
@@ -145,61 +468,146 @@ where
     /// If the minimum and maximum values bonds are not properly
     /// updated when this function is called, the index returned
     /// may cause out-of-bounds exceptions.
-    fn get_absolute_cell_id_unchecked(&self, x: Target, y: Target, layer: usize) -> usize {
-        Self::get_number_of_elements_before_layer(layer)
-            + self.get_relative_cell_id_unchecked(x, y, layer)
+    fn get_absolute_cell_id_unchecked(&self, point: &[Target], layer: usize) -> usize {
+        self.get_number_of_elements_before_layer(layer)
+            + self.get_relative_cell_id_unchecked(point, layer)
     }
 
     /// Return iterator on the child indices.
+    ///
+    /// Since each layer starts right after the cells of the previous layers,
+    /// the children of the cell with absolute ID `id` start at `(id + 1) * branching_factor`.
     fn iter_child_cells(&self, id: usize) -> impl Iterator<Item = usize> + '_ {
-        id * 4..(id + 1) * 4
+        (id + 1) * self.get_branching_factor()..(id + 2) * self.get_branching_factor()
     }
 
-    /// Return iterator on the child indices.
+    /// Return iterator on the sibling indices, excluding the provided cell.
     fn iter_siblings_cells(&self, id: usize) -> impl Iterator<Item = usize> + '_ {
-        self.iter_child_cells(id / 4)
-            .filter(move |&sybling_id| sybling_id == id)
+        (id / self.get_branching_factor() * self.get_branching_factor()
+            ..(id / self.get_branching_factor() + 1) * self.get_branching_factor())
+            .filter(move |&sibling_id| sibling_id != id)
     }
 
-    /// Return all far-away leafs IDs from given point.
-    fn iter_far_away_leafs(&self, x: Target, y: Target) -> impl Iterator<Item = usize> + '_ {
-        (1..self.depth).flat_map(move |layer| {
-            self.iter_siblings_cells(self.get_absolute_cell_id_unchecked(x, y, layer))
+    /// Return all far-away cells IDs from given point, that is the siblings
+    /// of the cells containing the point in every layer, including the leafs.
+    fn iter_far_away_leafs<'a>(&'a self, point: &'a [Target]) -> impl Iterator<Item = usize> + 'a {
+        (1..=self.depth).flat_map(move |layer| {
+            self.iter_siblings_cells(self.get_absolute_cell_id_unchecked(point, layer))
         })
     }
 
-    /// Return iterator on the child indices.
-    fn iter_siblings(&self, x: Target, y: Target) -> impl Iterator<Item = usize> + '_ {
-        self.reverse_index[self.get_relative_cell_id_unchecked(x, y, self.depth)]
-            .iter()
-            .copied()
+    /// Returns the length of the longest side of the cells of the provided layer.
+    fn get_cell_size(&self, layer: usize) -> Target {
+        get_cell_size(&self.min_values, &self.max_values, |axis| {
+            self.get_axis_mask(layer, axis).count_ones()
+        })
     }
+}
 
-    /// Return all far-away leafs IDs and their properties.
-    fn iter_mut_far_away_leafs_properties(
+impl<Target, Original, P> BarnesHutGrid<Target, Original> for GradientGrid<Target, Original, P>
+where
+    P: Pdep,
+    Target: Float + GenericFeature,
+    Original: GenericFeature + Zero,
+    usize: AsPrimitive<Target>,
+    usize: AsPrimitive<Original>,
+    Target: AsPrimitive<usize>,
+    Original: AsPrimitive<usize>,
+{
+    fn get_far_away_cells_and_near_leafs(
         &self,
-        x: Target,
-        y: Target,
-    ) -> impl Iterator<Item = (&[Target], &[Original], &mut [Target], usize)> + '_ {
-        self.iter_far_away_leafs(x, y).map(|id| unsafe {
+        _sample_number: usize,
+        point: &[Target],
+        theta: Option<Target>,
+    ) -> (Vec<usize>, Vec<usize>) {
+        let theta = match theta {
+            Some(theta) => theta,
+            None => {
+                return (
+                    self.iter_far_away_leafs(point).collect(),
+                    vec![self.get_relative_cell_id_unchecked(point, self.depth)],
+                )
+            }
+        };
+
+        let number_of_elements_before_last_layer =
+            self.get_number_of_elements_before_layer(self.depth);
+        let mut far_away_cells = Vec::new();
+        let mut near_leafs = Vec::new();
+        let mut cells_to_visit = (self.get_number_of_elements_before_layer(1)
+            ..self.get_number_of_elements_before_layer(2))
+            .map(|cell| (cell, 1))
+            .collect::<Vec<(usize, usize)>>();
+
+        while let Some((cell, layer)) = cells_to_visit.pop() {
+            if self.populations[cell].load(Ordering::Relaxed) == 0 {
+                continue;
+            }
+            let cell_target_average = unsafe {
+                &(*self.target_averages.get())
+                    [cell * self.target_dimension..(cell + 1) * self.target_dimension]
+            };
+            let squared_distance = point
+                .iter()
+                .copied()
+                .zip(cell_target_average.iter().copied())
+                .map(|(left, right)| (left - right) * (left - right))
+                .fold(Target::zero(), |total, value| total + value);
+            let contains_point = cell == self.get_absolute_cell_id_unchecked(point, layer);
+            let cell_size = self.get_cell_size(layer);
+
+            if !contains_point && cell_size * cell_size < theta * theta * squared_distance {
+                far_away_cells.push(cell);
+            } else if layer == self.depth {
+                near_leafs.push(cell - number_of_elements_before_last_layer);
+            } else {
+                cells_to_visit.extend(self.iter_child_cells(cell).map(|child| (child, layer + 1)));
+            }
+        }
+
+        (far_away_cells, near_leafs)
+    }
+
+    fn get_leaf_samples(&self, leaf: usize) -> &[usize] {
+        &self.reverse_index[leaf]
+    }
+
+    fn get_number_of_cells(&self) -> usize {
+        self.populations.len()
+    }
+
+    fn get_cell_properties(&self, cell: usize) -> (&[Target], &[Original], usize) {
+        unsafe {
             (
                 &(*self.target_averages.get())
-                    [id * self.target_dimension..(id + 1) * self.target_dimension],
+                    [cell * self.target_dimension..(cell + 1) * self.target_dimension],
                 &(*self.original_averages.get())
-                    [id * self.original_dimension..(id + 1) * self.original_dimension],
-                &mut (*self.gradients.get())
-                    [id * self.target_dimension..(id + 1) * self.target_dimension],
-                self.populations[id].load(Ordering::Relaxed),
+                    [cell * self.original_dimension..(cell + 1) * self.original_dimension],
+                self.populations[cell].load(Ordering::Relaxed),
             )
-        })
+        }
+    }
+
+    unsafe fn get_mut_cell_properties(
+        &self,
+        cell: usize,
+    ) -> (&[Target], &[Original], &mut [Target], usize) {
+        (
+            &(*self.target_averages.get())
+                [cell * self.target_dimension..(cell + 1) * self.target_dimension],
+            &(*self.original_averages.get())
+                [cell * self.original_dimension..(cell + 1) * self.original_dimension],
+            &mut (*self.gradients.get())
+                [cell * self.target_dimension..(cell + 1) * self.target_dimension],
+            self.populations[cell].load(Ordering::Relaxed),
+        )
     }
 
-    /// Propagates down to the leafs of the grid the partial gradients.
     fn downpropagate_gradient(&mut self) {
         (1..self.depth).for_each(|layer| {
             // We iterate on the elements of this layer.
-            (Self::get_number_of_elements_before_layer(layer)
-                ..Self::get_number_of_elements_before_layer(1 + layer))
+            (self.get_number_of_elements_before_layer(layer)
+                ..self.get_number_of_elements_before_layer(1 + layer))
                 .into_par_iter()
                 .map(|cell| unsafe {
                     (
@@ -223,12 +631,17 @@ where
     }
 
     fn apply_gradient(&self, target_features: &mut [Target]) {
+        // The index contains the relative IDs of the leafs, while
+        // the gradients are stored by absolute cell ID.
+        let number_of_elements_before_last_layer =
+            self.get_number_of_elements_before_layer(self.depth);
         target_features
             .par_chunks_mut(self.target_dimension)
             .zip(self.index.par_iter())
-            .for_each(|(target_feature, index)| unsafe {
+            .for_each(|(target_feature, &index)| unsafe {
+                let cell = number_of_elements_before_last_layer + index;
                 (*self.gradients.get())
-                    [index * self.target_dimension..(index + 1) * self.target_dimension]
+                    [cell * self.target_dimension..(cell + 1) * self.target_dimension]
                     .iter()
                     .copied()
                     .zip(target_feature.iter_mut())
@@ -238,11 +651,15 @@ where
             });
     }
 
-    fn prepare(
+    fn prepare<M>(
         &mut self,
         target_features: &[Target],
-        original_features: &[Original],
-    ) -> Result<(), String> {
+        original: &M,
+    ) -> Result<(), DimensionalReductionError>
+    where
+        Original: GenericFeature + Float,
+        M: OriginalMatrix<Original>,
+    {
         // First we clean up the grid.
         unsafe { self.reset() };
 
@@ -255,90 +672,62 @@ where
         // Now we start to iterate on the features, and we
         // update the various populations and averages.
 
-        let (mut target_cell_sums, mut original_cell_sums) = target_features
+        let cell_indices = target_features
             .par_chunks(self.target_dimension)
-            .zip(original_features.par_chunks(self.original_dimension))
-            .map(|(target_feature, original_feature)| {
-                let cell_index = self.get_absolute_cell_id_unchecked(
-                    target_feature[0],
-                    target_feature[1],
-                    self.depth,
-                );
+            .map(|target_feature| {
+                let cell_index = self.get_absolute_cell_id_unchecked(target_feature, self.depth);
                 self.populations[cell_index].fetch_add(1, Ordering::Relaxed);
 
                 debug_assert!(cell_index < self.populations.len());
 
-                (cell_index, target_feature, original_feature)
+                cell_index
             })
-            .fold(
-                || {
-                    (
-                        vec![Target::zero(); self.populations.len() * self.target_dimension],
-                        vec![Original::zero(); self.populations.len() * self.original_dimension],
-                    )
-                },
-                |(mut partial_target_sum, mut partial_original_sum): (
-                    Vec<Target>,
-                    Vec<Original>,
-                ),
-                 (cell_index, target_feature, original_feature): (
-                    usize,
-                    &[Target],
-                    &[Original],
-                )| {
+            .collect::<Vec<usize>>();
+
+        // The sums are reduced in a fixed order, so that the
+        // averages do not depend on the number of threads.
+        let (mut target_cell_sums, _) = deterministic_partitioned_sum(
+            cell_indices.len(),
+            self.populations.len() * self.target_dimension,
+            |samples, partial_target_sum: &mut [Target]| {
+                samples.for_each(|sample_number| {
+                    let cell_index = cell_indices[sample_number];
                     partial_target_sum[cell_index * self.target_dimension
                         ..(cell_index + 1) * self.target_dimension]
                         .iter_mut()
-                        .zip(target_feature.iter().copied())
-                        .for_each(|(p, v)| {
-                            *p += v;
-                        });
-
-                    partial_original_sum[cell_index * self.original_dimension
-                        ..(cell_index + 1) * self.original_dimension]
-                        .iter_mut()
-                        .zip(original_feature.iter().copied())
-                        .for_each(|(p, v)| {
-                            *p += v;
-                        });
-
-                    (partial_target_sum, partial_original_sum)
-                },
-            )
-            .reduce(
-                || {
-                    (
-                        vec![Target::zero(); self.populations.len() * self.target_dimension],
-                        vec![Original::zero(); self.populations.len() * self.original_dimension],
-                    )
-                },
-                |(mut partial_target_sum, mut partial_original_sum): (
-                    Vec<Target>,
-                    Vec<Original>,
-                ),
-                 (target_sum, original_sum): (Vec<Target>, Vec<Original>)| {
-                    partial_target_sum
-                        .iter_mut()
-                        .zip(target_sum.into_iter())
+                        .zip(
+                            target_features[sample_number * self.target_dimension
+                                ..(sample_number + 1) * self.target_dimension]
+                                .iter()
+                                .copied(),
+                        )
                         .for_each(|(p, v)| {
                             *p += v;
                         });
-
-                    partial_original_sum
-                        .iter_mut()
-                        .zip(original_sum.into_iter())
-                        .for_each(|(p, v)| {
-                            *p += v;
+                });
+            },
+        );
+        let (mut original_cell_sums, _) = deterministic_partitioned_sum(
+            cell_indices.len(),
+            self.populations.len() * self.original_dimension,
+            |samples, partial_original_sum: &mut [Original]| {
+                samples.for_each(|sample_number| {
+                    let cell_index = cell_indices[sample_number];
+                    let cell_sum = &mut partial_original_sum[cell_index * self.original_dimension
+                        ..(cell_index + 1) * self.original_dimension];
+                    original
+                        .get_sample(sample_number)
+                        .for_each_feature(|feature, value| {
+                            cell_sum[feature] += value;
                         });
-
-                    (partial_target_sum, partial_original_sum)
-                },
-            );
+                });
+            },
+        );
 
         // Now we update the total sums by dividing by the number
         // of elements in each cell, obtaining the cell average feature.
         let number_of_elements_before_last_layer =
-            Self::get_number_of_elements_before_layer(self.depth);
+            self.get_number_of_elements_before_layer(self.depth);
         self.populations[number_of_elements_before_last_layer..]
             .par_iter()
             .map(|population| population.load(Ordering::Relaxed))
@@ -372,8 +761,8 @@ where
         // We reverse the iteration as we need to start from the penultimate layer.
         (1..self.depth).rev().for_each(|layer| {
             // We iterate on the elements of this layer.
-            (Self::get_number_of_elements_before_layer(layer)
-                ..Self::get_number_of_elements_before_layer(1 + layer))
+            (self.get_number_of_elements_before_layer(layer)
+                ..self.get_number_of_elements_before_layer(1 + layer))
                 .into_par_iter()
                 .map(|cell| unsafe {
                     (
@@ -433,194 +822,1361 @@ where
             .chunks(self.target_dimension)
             .enumerate()
             .for_each(|(sample_index, target_feature)| {
-                let index = self.get_relative_cell_id_unchecked(
-                    target_feature[0],
-                    target_feature[1],
-                    self.depth,
-                );
+                let index = self.get_relative_cell_id_unchecked(target_feature, self.depth);
                 self.reverse_index[index].push(sample_index);
             });
 
         self.index = target_features
             .par_chunks(self.target_dimension)
-            .map(|feature| self.get_relative_cell_id_unchecked(feature[0], feature[1], self.depth))
+            .map(|feature| self.get_relative_cell_id_unchecked(feature, self.depth))
             .collect::<Vec<usize>>();
 
         Ok(())
     }
 }
 
+/// Cell of an adaptive grid.
+#[derive(Clone, Copy, Debug)]
+struct AdaptiveCell {
+    /// The ID of the parent cell, which for the root is the root itself.
+    parent: usize,
+    /// The ID of the first child cell, as the children are contiguous.
+    first_child: usize,
+    /// The number of non-empty children, zero for leafs.
+    number_of_children: usize,
+    /// The layer of the cell, zero for the root.
+    layer: usize,
+    /// The range of the samples of the cell in the samples sorted by Morton code.
+    start: usize,
+    end: usize,
+}
+
+/// Grid where a cell is split only when it contains more samples than the leaf capacity.
+///
+/// The samples are sorted by the Morton code of the cell containing them at the maximum
+/// depth, so that the samples of each cell are contiguous and only the non-empty cells
+/// are stored.
+struct AdaptiveGradientGrid<Target, Original, P> {
+    leaf_capacity: usize,
+    maximum_depth: usize,
+    target_dimension: usize,
+    original_dimension: usize,
+    bits_per_layer: usize,
+    pdep: P,
+    axis_masks: Vec<u64>,
+    cells: Vec<AdaptiveCell>,
+    gradients: DataRaceAware<Vec<Target>>,
+    target_averages: Vec<Target>,
+    original_averages: Vec<Original>,
+    sorted_samples: Vec<usize>,
+    morton_codes: Vec<u64>,
+    leafs: Vec<usize>,
+    min_values: Vec<Target>,
+    max_values: Vec<Target>,
+}
+
+impl<Target, Original, P> AdaptiveGradientGrid<Target, Original, P>
+where
+    P: Pdep,
+    Target: Float + GenericFeature,
+    Original: GenericFeature + Zero,
+    usize: AsPrimitive<Target>,
+    usize: AsPrimitive<Original>,
+    Target: AsPrimitive<usize>,
+    Original: AsPrimitive<usize>,
+{
+    fn new(
+        pdep: P,
+        leaf_capacity: usize,
+        maximum_depth: usize,
+        target_dimension: usize,
+        original_dimension: usize,
+    ) -> Result<Self, DimensionalReductionError> {
+        let bits_per_layer = get_bits_per_layer(target_dimension);
+
+        if leaf_capacity == 0 {
+            return Err(DimensionalReductionError::InvalidParameter {
+                parameter: "leaf capacity",
+                value: leaf_capacity.to_string(),
+                expected: "strictly positive",
+            });
+        }
+
+        if maximum_depth == 0 || maximum_depth * bits_per_layer >= 64 {
+            return Err(DimensionalReductionError::InvalidParameter {
+                parameter: "depth",
+                value: maximum_depth.to_string(),
+                expected: "strictly positive and such that the cell IDs fit in 64 bits",
+            });
+        }
+
+        Ok(Self {
+            leaf_capacity,
+            maximum_depth,
+            target_dimension,
+            original_dimension,
+            bits_per_layer,
+            pdep,
+            axis_masks: (0..target_dimension)
+                .map(|axis| {
+                    compute_axis_mask(maximum_depth, axis, target_dimension, bits_per_layer)
+                })
+                .collect(),
+            cells: Vec::new(),
+            gradients: DataRaceAware::from(Vec::new()),
+            target_averages: Vec::new(),
+            original_averages: Vec::new(),
+            sorted_samples: Vec::new(),
+            morton_codes: Vec::new(),
+            leafs: Vec::new(),
+            min_values: Vec::new(),
+            max_values: Vec::new(),
+        })
+    }
+
+    /// Returns the number of times the provided axis is split up to the provided layer.
+    fn get_number_of_splits(&self, layer: usize, axis: usize) -> u32 {
+        ((layer * self.bits_per_layer + self.target_dimension - 1 - axis) / self.target_dimension)
+            as u32
+    }
+
+    /// Return the Morton code of the cell containing the provided point at the maximum depth.
+    fn get_morton_code_unchecked(&self, point: &[Target]) -> u64 {
+        point
+            .iter()
+            .copied()
+            .enumerate()
+            .fold(0, |morton_code, (axis, value)| {
+                let coordinate = get_cell_coordinate(
+                    value,
+                    self.min_values[axis],
+                    self.max_values[axis],
+                    self.axis_masks[axis].count_ones(),
+                );
+                morton_code | self.pdep.deposit(coordinate as u64, self.axis_masks[axis])
+            })
+    }
+
+    /// Returns the children of the provided cell.
+    fn iter_child_cells(&self, cell: usize) -> std::ops::Range<usize> {
+        self.cells[cell].first_child
+            ..self.cells[cell].first_child + self.cells[cell].number_of_children
+    }
+
+    /// Returns whether the provided cell contains the provided sample.
+    fn contains_sample(&self, cell: usize, sample_number: usize) -> bool {
+        let leaf = &self.cells[self.leafs[sample_number]];
+        self.cells[cell].start <= leaf.start && leaf.end <= self.cells[cell].end
+    }
+
+    /// Splits the provided cell in the children containing at least a sample.
+    fn split(&mut self, cell: usize) {
+        let AdaptiveCell {
+            layer, start, end, ..
+        } = self.cells[cell];
+        let shift = (self.maximum_depth - layer - 1) * self.bits_per_layer;
+        let child_mask = (1 << self.bits_per_layer) - 1;
+        let first_child = self.cells.len();
+
+        let mut child_start = start;
+        while child_start < end {
+            let child_key = (self.morton_codes[child_start] >> shift) & child_mask;
+            // Since the samples are sorted by Morton code, the samples
+            // of each child are contiguous.
+            let child_end = child_start
+                + self.morton_codes[child_start..end]
+                    .partition_point(|&code| (code >> shift) & child_mask == child_key);
+            self.cells.push(AdaptiveCell {
+                parent: cell,
+                first_child: 0,
+                number_of_children: 0,
+                layer: layer + 1,
+                start: child_start,
+                end: child_end,
+            });
+            child_start = child_end;
+        }
+
+        self.cells[cell].first_child = first_child;
+        self.cells[cell].number_of_children = self.cells.len() - first_child;
+    }
+}
+
+impl<Target, Original, P> BarnesHutGrid<Target, Original>
+    for AdaptiveGradientGrid<Target, Original, P>
+where
+    P: Pdep,
+    Target: Float + GenericFeature,
+    Original: GenericFeature + Zero,
+    usize: AsPrimitive<Target>,
+    usize: AsPrimitive<Original>,
+    Target: AsPrimitive<usize>,
+    Original: AsPrimitive<usize>,
+{
+    fn prepare<M>(
+        &mut self,
+        target_features: &[Target],
+        original: &M,
+    ) -> Result<(), DimensionalReductionError>
+    where
+        Original: GenericFeature + Float,
+        M: OriginalMatrix<Original>,
+    {
+        // We update the cells minimum and maximum values,
+        // which define the borders of the cell.
+        let (min_values, max_values) = target_features.matrix_min_max(self.target_dimension)?;
+        self.min_values = min_values;
+        self.max_values = max_values;
+
+        // We sort the samples by the Morton code of their cell at the maximum depth.
+        let morton_codes = target_features
+            .par_chunks(self.target_dimension)
+            .map(|target_feature| self.get_morton_code_unchecked(target_feature))
+            .collect::<Vec<u64>>();
+        let number_of_samples = morton_codes.len();
+        self.sorted_samples = (0..number_of_samples).collect();
+        self.sorted_samples
+            .par_sort_unstable_by_key(|&sample_number| {
+                (morton_codes[sample_number], sample_number)
+            });
+        self.morton_codes = self
+            .sorted_samples
+            .par_iter()
+            .map(|&sample_number| morton_codes[sample_number])
+            .collect();
+
+        // We build the tree top-down, splitting the cells exceeding the
+        // leaf capacity up until the maximum depth. Since the children
+        // are always added after their parent, the cells are sorted by layer.
+        self.cells.clear();
+        self.cells.push(AdaptiveCell {
+            parent: 0,
+            first_child: 0,
+            number_of_children: 0,
+            layer: 0,
+            start: 0,
+            end: number_of_samples,
+        });
+        let mut cell = 0;
+        while cell < self.cells.len() {
+            let AdaptiveCell {
+                layer, start, end, ..
+            } = self.cells[cell];
+            if end - start > self.leaf_capacity && layer < self.maximum_depth {
+                self.split(cell);
+            }
+            cell += 1;
+        }
+
+        self.leafs = vec![0; number_of_samples];
+        self.cells
+            .iter()
+            .enumerate()
+            .filter(|(_, cell)| cell.number_of_children == 0)
+            .for_each(|(leaf, cell)| {
+                self.sorted_samples[cell.start..cell.end]
+                    .iter()
+                    .for_each(|&sample_number| {
+                        self.leafs[sample_number] = leaf;
+                    });
+            });
+
+        // We compute the averages of the samples of each cell.
+        let (target_averages, original_averages): (Vec<Vec<Target>>, Vec<Vec<Original>>) = self
+            .cells
+            .par_iter()
+            .map(|cell| {
+                let mut target_average = vec![Target::zero(); self.target_dimension];
+                let mut original_average = vec![Original::zero(); self.original_dimension];
+                self.sorted_samples[cell.start..cell.end]
+                    .iter()
+                    .for_each(|&sample_number| {
+                        target_average
+                            .iter_mut()
+                            .zip(
+                                target_features[sample_number * self.target_dimension
+                                    ..(sample_number + 1) * self.target_dimension]
+                                    .iter()
+                                    .copied(),
+                            )
+                            .for_each(|(average, value)| {
+                                *average += value;
+                            });
+                        original
+                            .get_sample(sample_number)
+                            .for_each_feature(|feature, value| {
+                                original_average[feature] += value;
+                            });
+                    });
+                let population = cell.end - cell.start;
+                target_average.iter_mut().for_each(|average| {
+                    *average /= population.as_();
+                });
+                original_average.iter_mut().for_each(|average| {
+                    *average /= population.as_();
+                });
+                (target_average, original_average)
+            })
+            .unzip();
+        self.target_averages = target_averages.concat();
+        self.original_averages = original_averages.concat();
+        self.gradients = DataRaceAware::from(vec![
+            Target::zero();
+            self.cells.len() * self.target_dimension
+        ]);
+
+        Ok(())
+    }
+
+    fn get_far_away_cells_and_near_leafs(
+        &self,
+        sample_number: usize,
+        point: &[Target],
+        theta: Option<Target>,
+    ) -> (Vec<usize>, Vec<usize>) {
+        let leaf = self.leafs[sample_number];
+        let mut far_away_cells = Vec::new();
+
+        let theta = match theta {
+            Some(theta) => theta,
+            None => {
+                // We approximate the siblings of all of the cells containing the sample.
+                let mut cell = leaf;
+                while cell != 0 {
+                    let parent = self.cells[cell].parent;
+                    far_away_cells.extend(self.iter_child_cells(parent).filter(|&c| c != cell));
+                    cell = parent;
+                }
+                return (far_away_cells, vec![leaf]);
+            }
+        };
+
+        let mut near_leafs = Vec::new();
+        let mut cells_to_visit = self.iter_child_cells(0).collect::<Vec<usize>>();
+
+        while let Some(cell) = cells_to_visit.pop() {
+            let cell_target_average = &self.target_averages
+                [cell * self.target_dimension..(cell + 1) * self.target_dimension];
+            let squared_distance = point
+                .iter()
+                .copied()
+                .zip(cell_target_average.iter().copied())
+                .map(|(left, right)| (left - right) * (left - right))
+                .fold(Target::zero(), |total, value| total + value);
+            let cell_size: Target = get_cell_size(&self.min_values, &self.max_values, |axis| {
+                self.get_number_of_splits(self.cells[cell].layer, axis)
+            });
+
+            if !self.contains_sample(cell, sample_number)
+                && cell_size * cell_size < theta * theta * squared_distance
+            {
+                far_away_cells.push(cell);
+            } else if self.cells[cell].number_of_children == 0 {
+                near_leafs.push(cell);
+            } else {
+                cells_to_visit.extend(self.iter_child_cells(cell));
+            }
+        }
+
+        // When the root is a leaf, all of the samples are near.
+        if self.cells.len() == 1 {
+            near_leafs.push(0);
+        }
+
+        (far_away_cells, near_leafs)
+    }
+
+    fn get_leaf_samples(&self, leaf: usize) -> &[usize] {
+        &self.sorted_samples[self.cells[leaf].start..self.cells[leaf].end]
+    }
+
+    fn get_number_of_cells(&self) -> usize {
+        self.cells.len()
+    }
+
+    fn get_cell_properties(&self, cell: usize) -> (&[Target], &[Original], usize) {
+        (
+            &self.target_averages[cell * self.target_dimension..(cell + 1) * self.target_dimension],
+            &self.original_averages
+                [cell * self.original_dimension..(cell + 1) * self.original_dimension],
+            self.cells[cell].end - self.cells[cell].start,
+        )
+    }
+
+    unsafe fn get_mut_cell_properties(
+        &self,
+        cell: usize,
+    ) -> (&[Target], &[Original], &mut [Target], usize) {
+        (
+            &self.target_averages[cell * self.target_dimension..(cell + 1) * self.target_dimension],
+            &self.original_averages
+                [cell * self.original_dimension..(cell + 1) * self.original_dimension],
+            &mut (*self.gradients.get())
+                [cell * self.target_dimension..(cell + 1) * self.target_dimension],
+            self.cells[cell].end - self.cells[cell].start,
+        )
+    }
+
+    fn downpropagate_gradient(&mut self) {
+        let gradients = unsafe { &mut *self.gradients.get() };
+        // Since the children are always after their parent,
+        // a single pass propagates the gradients down to the leafs.
+        (0..self.cells.len()).for_each(|cell| {
+            self.iter_child_cells(cell).for_each(|child| {
+                (0..self.target_dimension).for_each(|dimension| {
+                    let gradient = gradients[cell * self.target_dimension + dimension];
+                    gradients[child * self.target_dimension + dimension] += gradient;
+                });
+            });
+        });
+    }
+
+    fn apply_gradient(&self, target_features: &mut [Target]) {
+        target_features
+            .par_chunks_mut(self.target_dimension)
+            .zip(self.leafs.par_iter())
+            .for_each(|(target_feature, &leaf)| unsafe {
+                (*self.gradients.get())
+                    [leaf * self.target_dimension..(leaf + 1) * self.target_dimension]
+                    .iter()
+                    .copied()
+                    .zip(target_feature.iter_mut())
+                    .for_each(|(gradient, t)| {
+                        *t += gradient;
+                    });
+            });
+    }
+}
+
+/// Sigmoid Decomposition where the far away samples are approximated
+/// by the averages of the cells of a grid.
+///
+/// The grid is a quadtree for 2D embeddings and an octree for 3D embeddings,
+/// while for higher-dimensional embeddings each layer splits a single axis.
+/// The grid is either complete, with all of the cells up to the provided depth,
+/// or adaptive, splitting only the cells with more samples than the leaf capacity.
 #[derive(Clone)]
-pub struct BarnesHutSigmoidDecomposition {
+pub struct BarnesHutSigmoidDecomposition<S = StandardizedDot, K = SigmoidDot> {
     decomposition: BasicIterativeDecomposition,
+    similarity: S,
+    target_similarity: K,
     depth: usize,
+    theta: Option<f32>,
+    leaf_capacity: Option<usize>,
 }
 
-impl BarnesHutSigmoidDecomposition {
-    pub fn new(decomposition: BasicIterativeDecomposition, depth: Option<usize>) -> Self {
-        Self {
+impl<S: Similarity, K: TargetSimilarity> BarnesHutSigmoidDecomposition<S, K> {
+    /// Returns a new Barnes-Hut Sigmoid Decomposition model.
+    ///
+    /// # Arguments
+    /// * `decomposition`: The basic iterative decomposition parameters.
+    /// * `similarity`: The similarity between the original samples, which is also
+    ///   used to compare the samples with the original averages of the far away cells.
+    /// * `target_similarity`: The similarity between the embedded samples.
+    /// * `depth`: The depth of the grid, or its maximum depth when a leaf capacity is provided.
+    ///   By default, 3 for the complete grid and 20 for the adaptive grid.
+    /// * `theta`: The Barnes-Hut opening criterion, where smaller values are more accurate
+    ///   and slower, with zero considering every pair of samples exactly. By default, the
+    ///   siblings of the cells containing each sample are approximated by their averages.
+    /// * `leaf_capacity`: The maximum number of samples in a leaf of the adaptive grid,
+    ///   which is rebuilt at every epoch splitting only the cells exceeding it.
+    ///   By default, the complete grid of the provided depth is used.
+    pub fn new(
+        decomposition: BasicIterativeDecomposition,
+        similarity: S,
+        target_similarity: K,
+        depth: Option<usize>,
+        theta: Option<f32>,
+        leaf_capacity: Option<usize>,
+    ) -> Result<Self, DimensionalReductionError> {
+        if let Some(theta) = theta {
+            if !theta.is_finite() || theta < 0.0 {
+                return Err(DimensionalReductionError::InvalidParameter {
+                    parameter: "theta",
+                    value: theta.to_string(),
+                    expected: "a finite non-negative value",
+                });
+            }
+        }
+
+        Ok(Self {
             decomposition,
-            depth: depth.unwrap_or(3),
+            similarity,
+            target_similarity,
+            depth: depth.unwrap_or(if leaf_capacity.is_some() { 20 } else { 3 }),
+            theta,
+            leaf_capacity,
+        })
+    }
+}
+
+impl<S: Similarity, K: TargetSimilarity> BarnesHutSigmoidDecomposition<S, K> {
+    /// Fits the embedding with the grid of this model computing the cell IDs with the provided `pdep`.
+    fn fit_transform_with_pdep<Original, Target, M, P>(
+        &self,
+        pdep: P,
+        target: &mut [Target],
+        target_dimension: usize,
+        original: &M,
+    ) -> Result<Vec<f32>, DimensionalReductionError>
+    where
+        Original: AsPrimitive<Target> + GenericFeature + Float,
+        Target: Float + GenericFeature,
+        M: OriginalMatrix<Original>,
+        usize: AsPrimitive<Original> + AsPrimitive<Target>,
+        f32: AsPrimitive<Target>,
+        P: Pdep,
+    {
+        match self.leaf_capacity {
+            Some(leaf_capacity) => self.fit_transform_with_grid(
+                AdaptiveGradientGrid::new(
+                    pdep,
+                    leaf_capacity,
+                    self.depth,
+                    target_dimension,
+                    original.get_dimension(),
+                )?,
+                target,
+                target_dimension,
+                original,
+            ),
+            None => self.fit_transform_with_grid(
+                GradientGrid::new(pdep, self.depth, target_dimension, original.get_dimension())?,
+                target,
+                target_dimension,
+                original,
+            ),
         }
     }
+
+    /// Fits the embedding approximating the far away samples with the cells of the provided grid.
+    fn fit_transform_with_grid<Original, Target, M, G>(
+        &self,
+        mut grid: G,
+        target: &mut [Target],
+        target_dimension: usize,
+        original: &M,
+    ) -> Result<Vec<f32>, DimensionalReductionError>
+    where
+        Original: AsPrimitive<Target> + GenericFeature + Float,
+        Target: Float + GenericFeature,
+        M: OriginalMatrix<Original>,
+        usize: AsPrimitive<Original> + AsPrimitive<Target>,
+        f32: AsPrimitive<Target>,
+        G: BarnesHutGrid<Target, Original> + Sync,
+    {
+        let statistics = original.get_statistics()?;
+
+        // We wrap the features object in an unsafe cell so
+        // it may be shared among threads.
+        let mut optimizer = OptimizerState::new(self.get_optimizer(), target.len());
+        let wrapped_target = DataRaceAware::from(target);
+
+        let number_of_samples = original.get_number_of_samples();
+        let theta: Option<Target> = self.theta.map(|theta| theta.as_());
+
+        // Returns the variations of the provided pair of samples, or of
+        // sample and cell average, and its loss.
+        let get_variations_and_loss =
+            |left_target_sample: &[Target],
+             right_target_sample: &[Target],
+             left_original_sample: Sample<'_, Original>,
+             right_original_sample: Sample<'_, Original>,
+             learning_rate: Target| {
+                let original_similarity: Target = self
+                    .similarity
+                    .similarity(left_original_sample, right_original_sample, &statistics)
+                    .as_();
+                let (target_probability, own_coefficient, other_coefficient) = self
+                    .target_similarity
+                    .similarity_and_gradient(left_target_sample, right_target_sample);
+                let original_probability = sigmoid(original_similarity);
+                let variation = (target_probability - original_probability) * learning_rate;
+                (
+                    own_coefficient * variation,
+                    other_coefficient * variation,
+                    binary_cross_entropy(original_probability, target_probability),
+                )
+            };
+
+        self.start_iterations(|_, learning_rate| {
+            // When the descent directions are batched, the optimizer applies the learning rate.
+            let variations_scale: Target = if self.is_batched() {
+                Target::one()
+            } else {
+                learning_rate.as_()
+            };
+
+            grid.prepare(unsafe { *wrapped_target.get() }, original)?;
+
+            if self.is_batched() {
+                let current_target: &[Target] = unsafe { *wrapped_target.get() };
+                // The variations of the samples are followed by those of the cells.
+                let (variations, losses) = deterministic_partitioned_sum(
+                    number_of_samples,
+                    current_target.len() + grid.get_number_of_cells() * target_dimension,
+                    |samples, variations: &mut [Target]| {
+                        let (samples_variations, cells_variations) =
+                            variations.split_at_mut(current_target.len());
+                        samples.fold(
+                            (Target::zero(), Target::zero()),
+                            |(mut loss, mut number_of_pairs), sample_number| {
+                                let left_target_sample = &current_target[sample_number
+                                    * target_dimension
+                                    ..(sample_number + 1) * target_dimension];
+                                let left_original_sample = original.get_sample(sample_number);
+                                let (far_away_cells, near_leafs) = grid
+                                    .get_far_away_cells_and_near_leafs(
+                                        sample_number,
+                                        left_target_sample,
+                                        theta,
+                                    );
+
+                                far_away_cells.into_iter().for_each(|cell| {
+                                    let (cell_target_average, cell_original_average, population) =
+                                        grid.get_cell_properties(cell);
+                                    let (own_variation, other_variation, pair_loss) =
+                                        get_variations_and_loss(
+                                            left_target_sample,
+                                            cell_target_average,
+                                            left_original_sample,
+                                            cell_original_average.into(),
+                                            variations_scale,
+                                        );
+                                    (0..target_dimension).for_each(|dimension| {
+                                        let (left, right) = (
+                                            left_target_sample[dimension],
+                                            cell_target_average[dimension],
+                                        );
+                                        samples_variations
+                                            [sample_number * target_dimension + dimension] -=
+                                            (left * own_variation + right * other_variation)
+                                                * population.as_();
+                                        cells_variations[cell * target_dimension + dimension] -=
+                                            right * own_variation + left * other_variation;
+                                    });
+                                    loss += pair_loss * population.as_();
+                                    number_of_pairs += population.as_();
+                                });
+
+                                grid.iter_leafs_samples(&near_leafs)
+                                    .filter(|&sibling_id| sibling_id != sample_number)
+                                    .for_each(|sibling_id| {
+                                        let sibling_target = &current_target[sibling_id
+                                            * target_dimension
+                                            ..(sibling_id + 1) * target_dimension];
+                                        let (own_variation, other_variation, pair_loss) =
+                                            get_variations_and_loss(
+                                                left_target_sample,
+                                                sibling_target,
+                                                left_original_sample,
+                                                original.get_sample(sibling_id),
+                                                variations_scale,
+                                            );
+                                        (0..target_dimension).for_each(|dimension| {
+                                            let (left, right) = (
+                                                left_target_sample[dimension],
+                                                sibling_target[dimension],
+                                            );
+                                            samples_variations
+                                                [sample_number * target_dimension + dimension] -=
+                                                left * own_variation + right * other_variation;
+                                            samples_variations
+                                                [sibling_id * target_dimension + dimension] -=
+                                                right * own_variation + left * other_variation;
+                                        });
+                                        loss += pair_loss;
+                                        number_of_pairs += Target::one();
+                                    });
+
+                                (loss, number_of_pairs)
+                            },
+                        )
+                    },
+                );
+
+                let (samples_variations, cells_variations) =
+                    variations.split_at(current_target.len());
+                cells_variations
+                    .par_chunks(target_dimension)
+                    .enumerate()
+                    .for_each(|(cell, cell_variations)| {
+                        unsafe { grid.get_mut_cell_properties(cell) }
+                            .2
+                            .iter_mut()
+                            .zip(cell_variations.iter().copied())
+                            .for_each(|(gradient, variation)| {
+                                *gradient += variation;
+                            });
+                    });
+                grid.downpropagate_gradient();
+                // The cells variations are added to those of the samples they contain.
+                let mut descent_directions = samples_variations.to_vec();
+                grid.apply_gradient(&mut descent_directions);
+                optimizer.update(
+                    unsafe { &mut **wrapped_target.get() },
+                    &descent_directions,
+                    learning_rate,
+                );
+
+                let (total_loss, number_of_pairs) = losses.into_iter().fold(
+                    (Target::zero(), Target::zero()),
+                    |(left_loss, left_pairs), (right_loss, right_pairs)| {
+                        (left_loss + right_loss, left_pairs + right_pairs)
+                    },
+                );
+                return Ok((total_loss / number_of_pairs.max(Target::one()))
+                    .to_f32()
+                    .unwrap());
+            }
+
+            let (total_loss, number_of_pairs) = (0..number_of_samples)
+                .into_par_iter()
+                .map(|sample_number| unsafe {
+                    (
+                        sample_number,
+                        &mut (*wrapped_target.get())[(sample_number * target_dimension)
+                            ..((sample_number + 1) * target_dimension)],
+                        original.get_sample(sample_number),
+                    )
+                })
+                .map(
+                    |(sample_number, left_target_sample, left_original_sample)| {
+                        let mut loss = Target::zero();
+                        let mut number_of_pairs = Target::zero();
+
+                        // The far away cells are those of the position of the
+                        // sample before it is updated in this epoch.
+                        let (far_away_cells, near_leafs) = grid.get_far_away_cells_and_near_leafs(
+                            sample_number,
+                            left_target_sample,
+                            theta,
+                        );
+
+                        // First we iterate on the far away elements averages.
+                        grid.iter_mut_cells_properties(&far_away_cells).for_each(
+                            |(cell_target_average, cell_original_average, gradient, population)| {
+                                let (own_variation, other_variation, pair_loss) =
+                                    get_variations_and_loss(
+                                        left_target_sample,
+                                        cell_target_average,
+                                        left_original_sample,
+                                        cell_original_average.into(),
+                                        variations_scale,
+                                    );
+                                left_target_sample
+                                    .iter_mut()
+                                    .zip(cell_target_average.iter().zip(gradient.iter_mut()))
+                                    .for_each(|(left, (right, gradient))| {
+                                        let left_tmp = *left;
+                                        *left -= (left_tmp * own_variation
+                                            + *right * other_variation)
+                                            * population.as_();
+                                        *gradient -=
+                                            *right * own_variation + left_tmp * other_variation;
+                                    });
+                                // The cell average stands for all of the samples in the cell.
+                                loss += pair_loss * population.as_();
+                                number_of_pairs += population.as_();
+                            },
+                        );
+
+                        // First we iterate on the far away elements averages.
+                        grid.iter_leafs_samples(&near_leafs)
+                            .filter(|&sibling_id| sibling_id != sample_number)
+                            .map(|sibling_id| unsafe {
+                                (
+                                    &mut (*wrapped_target.get())[(sibling_id * target_dimension)
+                                        ..((sibling_id + 1) * target_dimension)],
+                                    original.get_sample(sibling_id),
+                                )
+                            })
+                            .for_each(|(sibling_target, sibling_original)| {
+                                let (own_variation, other_variation, pair_loss) =
+                                    get_variations_and_loss(
+                                        left_target_sample,
+                                        sibling_target,
+                                        left_original_sample,
+                                        sibling_original,
+                                        variations_scale,
+                                    );
+                                left_target_sample
+                                    .iter_mut()
+                                    .zip(sibling_target.iter_mut())
+                                    .for_each(|(left, right)| {
+                                        let left_tmp = *left;
+                                        *left -=
+                                            left_tmp * own_variation + *right * other_variation;
+                                        *right -=
+                                            *right * own_variation + left_tmp * other_variation;
+                                    });
+                                loss += pair_loss;
+                                number_of_pairs += Target::one();
+                            });
+
+                        (loss, number_of_pairs)
+                    },
+                )
+                .reduce(
+                    || (Target::zero(), Target::zero()),
+                    |(left_loss, left_pairs), (right_loss, right_pairs)| {
+                        (left_loss + right_loss, left_pairs + right_pairs)
+                    },
+                );
+            grid.downpropagate_gradient();
+            grid.apply_gradient(unsafe { &mut *wrapped_target.get() });
+            Ok((total_loss / number_of_pairs.max(Target::one()))
+                .to_f32()
+                .unwrap())
+        })
+    }
 }
 
-impl IterativeDecomposition for BarnesHutSigmoidDecomposition {
+impl<S, K> IterativeDecomposition for BarnesHutSigmoidDecomposition<S, K> {
     fn get_iterative_basic_decomposition(&self) -> &BasicIterativeDecomposition {
         &self.decomposition
     }
 }
 
-impl DimensionalReduction for BarnesHutSigmoidDecomposition {
-    fn fit_transform<Original, Target>(
+impl<S: Similarity, K: TargetSimilarity> SimilarityDecomposition
+    for BarnesHutSigmoidDecomposition<S, K>
+{
+    type Similarity = S;
+    type TargetSimilarity = K;
+
+    fn get_similarity(&self) -> &S {
+        &self.similarity
+    }
+
+    fn get_target_similarity(&self) -> &K {
+        &self.target_similarity
+    }
+}
+
+impl<S: Similarity, K: TargetSimilarity> DimensionalReduction
+    for BarnesHutSigmoidDecomposition<S, K>
+{
+    fn fit_transform_matrix<Original, Target, M>(
         &self,
-        mut target: &mut [Target],
+        target: &mut [Target],
         target_dimension: usize,
-        original: &[Original],
-        original_dimension: usize,
-    ) -> Result<(), String>
+        original: &M,
+    ) -> Result<Vec<f32>, DimensionalReductionError>
     where
         Original: AsPrimitive<Target> + GenericFeature + Float,
         Target: Float + GenericFeature,
+        M: OriginalMatrix<Original>,
         usize: AsPrimitive<Original> + AsPrimitive<Target>,
         f32: AsPrimitive<Target>,
     {
-        if target.len() % target_dimension != 0 {
-            return Err(format!(
-                concat!(
-                    "The provided target slice has length {} ",
-                    "which is not compatible with the provided ",
-                    "target dimension {}."
-                ),
-                target.len(),
-                target_dimension
-            ));
+        validate_fit_transform_matrix_arguments(target, target_dimension, original)?;
+
+        self.initialize_target(target, target_dimension, original)?;
+
+        #[cfg(target_arch = "x86_64")]
+        if let Some(pdep) = Bmi2Pdep::detect() {
+            return self.fit_transform_with_pdep(pdep, target, target_dimension, original);
+        }
+        self.fit_transform_with_pdep(PortablePdep, target, target_dimension, original)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basic_iterative_decomposition::IterativeParameters;
+    use crate::{
+        initialization::Initialization, matrices::DenseMatrix, SigmoidDecomposition, StudentT,
+    };
+    use vec_rand::{random_f32, splitmix64};
+
+    /// Returns the provided number of random values in [-1, 1].
+    fn random_points(number_of_values: usize, random_state: u64) -> Vec<f32> {
+        (0..number_of_values as u64)
+            .map(|i| 2.0 * random_f32(splitmix64(random_state.wrapping_add(i))) - 1.0)
+            .collect()
+    }
+
+    /// Returns the IDs of the cells of each layer containing each of the provided points.
+    fn get_cell_ids<P: Pdep>(grid: &GradientGrid<f32, f32, P>, target: &[f32]) -> Vec<usize> {
+        target
+            .chunks(grid.target_dimension)
+            .flat_map(|point| {
+                (1..=grid.depth).map(move |layer| grid.get_absolute_cell_id_unchecked(point, layer))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_portable_pdep() {
+        assert_eq!(pdep_portable(0b1011, 0b1111_0000), 0b1011_0000);
+        assert_eq!(pdep_portable(0b111, 0b1010_1000), 0b1010_1000);
+        assert_eq!(pdep_portable(0b101, 0b0101_0101), 0b0001_0001);
+        assert_eq!(pdep_portable(u64::MAX, 0), 0);
+        assert_eq!(pdep_portable(u64::MAX, u64::MAX), u64::MAX);
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    fn test_portable_pdep_matches_bmi2() {
+        if !is_x86_feature_detected!("bmi2") {
+            return;
         }
+        for i in 0..100_000 {
+            let x = splitmix64(i);
+            let mask = splitmix64(x) & splitmix64(x.wrapping_add(1));
+            assert_eq!(pdep_portable(x, mask), unsafe { pdep_bmi2(x, mask) });
+        }
+    }
 
-        if target_dimension != 2 {
-            return Err("Currently we only support 2.".to_string());
+    #[test]
+    fn test_cell_ids_interleave_coordinates() {
+        for target_dimension in 1..=5 {
+            for depth in 1..=4 {
+                let mut grid: GradientGrid<f32, f32, PortablePdep> =
+                    GradientGrid::new(PortablePdep, depth, target_dimension, 3).unwrap();
+                let target = random_points(100 * target_dimension, 42);
+                grid.prepare(
+                    &target,
+                    &DenseMatrix::new(&random_points(300, 7), 3).unwrap(),
+                )
+                .unwrap();
+                for point in target.chunks(target_dimension) {
+                    for layer in 1..=depth {
+                        let coordinates = grid
+                            .iter_cell_coordinates_unchecked(point, layer)
+                            .collect::<Vec<usize>>();
+                        let splits = layer * grid.bits_per_layer;
+                        // The most significant bit of the ID is the first split,
+                        // and each axis is split in turn starting from its most
+                        // significant coordinate bit.
+                        let expected = (0..splits).fold(0, |cell_id, split| {
+                            let axis = split % target_dimension;
+                            let axis_splits = (axis..splits).step_by(target_dimension).count();
+                            let bit = (coordinates[axis]
+                                >> (axis_splits - 1 - split / target_dimension))
+                                & 1;
+                            cell_id | (bit << (splits - 1 - split))
+                        });
+                        assert_eq!(grid.get_relative_cell_id_unchecked(point, layer), expected);
+                    }
+                }
+            }
         }
+    }
 
-        target.random_init(self.get_random_state());
+    #[cfg(target_arch = "x86_64")]
+    #[test]
+    fn test_cell_ids_are_independent_of_pdep() {
+        if !is_x86_feature_detected!("bmi2") {
+            return;
+        }
+        for target_dimension in 1..=5 {
+            for depth in 1..=4 {
+                let target = random_points(100 * target_dimension, 42);
+                let original = random_points(300, 7);
+                let original = DenseMatrix::new(&original, 3).unwrap();
+                let mut portable_grid: GradientGrid<f32, f32, PortablePdep> =
+                    GradientGrid::new(PortablePdep, depth, target_dimension, 3).unwrap();
+                portable_grid.prepare(&target, &original).unwrap();
+                // The CPU supports BMI2, even if its pdep may be slow.
+                let mut bmi2_grid: GradientGrid<f32, f32, Bmi2Pdep> =
+                    GradientGrid::new(Bmi2Pdep(()), depth, target_dimension, 3).unwrap();
+                bmi2_grid.prepare(&target, &original).unwrap();
 
-        let mean = original.matrix_mean(original_dimension)?;
-        let variance = original.matrix_var(original_dimension)?;
+                assert_eq!(
+                    get_cell_ids(&portable_grid, &target),
+                    get_cell_ids(&bmi2_grid, &target)
+                );
+                assert_eq!(
+                    (1..=depth + 1)
+                        .map(|layer| portable_grid.get_number_of_elements_before_layer(layer))
+                        .collect::<Vec<usize>>(),
+                    (1..=depth + 1)
+                        .map(|layer| bmi2_grid.get_number_of_elements_before_layer(layer))
+                        .collect::<Vec<usize>>()
+                );
+            }
+        }
+    }
 
-        // We wrap the features object in an unsafe cell so
-        // it may be shared among threads.
-        let wrapped_target = DataRaceAware::from(target);
+    /// Returns the layer of the cell with the provided absolute ID.
+    fn get_layer(grid: &GradientGrid<f32, f32, PortablePdep>, cell: usize) -> usize {
+        (1..=grid.depth)
+            .find(|&layer| cell < grid.get_number_of_elements_before_layer(layer + 1))
+            .unwrap()
+    }
 
-        if original.len() % original_dimension != 0 {
-            return Err(format!(
-                concat!(
-                    "The provided original slice has length {} ",
-                    "which is not compatible with the provided ",
-                    "original dimension {}."
-                ),
-                original.len(),
-                original_dimension
+    /// Returns the relative IDs of the leafs below the cell with the provided absolute ID.
+    fn get_leafs_below(
+        grid: &GradientGrid<f32, f32, PortablePdep>,
+        cell: usize,
+    ) -> std::ops::Range<usize> {
+        let layer = get_layer(grid, cell);
+        let relative_cell = cell - grid.get_number_of_elements_before_layer(layer);
+        let leafs_per_cell = 1 << ((grid.depth - layer) * grid.bits_per_layer);
+        relative_cell * leafs_per_cell..(relative_cell + 1) * leafs_per_cell
+    }
+
+    #[test]
+    fn test_layer_offsets() {
+        for target_dimension in 1..=5 {
+            for depth in 1..=4 {
+                let grid: GradientGrid<f32, f32, PortablePdep> =
+                    GradientGrid::new(PortablePdep, depth, target_dimension, 3).unwrap();
+                let branching_factor = grid.get_branching_factor();
+                // The root is not stored, so the first layer starts at zero.
+                let mut expected_offset = 0;
+                for layer in 1..=depth + 1 {
+                    assert_eq!(
+                        grid.get_number_of_elements_before_layer(layer),
+                        expected_offset
+                    );
+                    expected_offset += branching_factor.pow(layer as u32);
+                }
+                assert_eq!(
+                    grid.get_number_of_cells(),
+                    grid.get_number_of_elements_before_layer(depth + 1)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_children_and_siblings() {
+        for target_dimension in 1..=5 {
+            let depth = 3;
+            let grid: GradientGrid<f32, f32, PortablePdep> =
+                GradientGrid::new(PortablePdep, depth, target_dimension, 3).unwrap();
+            let branching_factor = grid.get_branching_factor();
+            for cell in 0..grid.get_number_of_elements_before_layer(depth) {
+                let children = grid.iter_child_cells(cell).collect::<Vec<usize>>();
+                assert_eq!(children.len(), branching_factor);
+                for &child in &children {
+                    assert_eq!(get_layer(&grid, child), get_layer(&grid, cell) + 1);
+                    // The leafs below each child are among those below its parent.
+                    let (child_leafs, leafs) =
+                        (get_leafs_below(&grid, child), get_leafs_below(&grid, cell));
+                    assert!(leafs.start <= child_leafs.start && child_leafs.end <= leafs.end);
+                    // The siblings of a child are the other children of its parent.
+                    let siblings = grid.iter_siblings_cells(child).collect::<Vec<usize>>();
+                    assert_eq!(
+                        siblings,
+                        children
+                            .iter()
+                            .copied()
+                            .filter(|&other| other != child)
+                            .collect::<Vec<usize>>()
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_far_away_cells_partition_the_other_leafs() {
+        for target_dimension in 1..=5 {
+            for depth in 1..=4 {
+                let mut grid: GradientGrid<f32, f32, PortablePdep> =
+                    GradientGrid::new(PortablePdep, depth, target_dimension, 3).unwrap();
+                let target = random_points(50 * target_dimension, 42);
+                grid.prepare(
+                    &target,
+                    &DenseMatrix::new(&random_points(150, 7), 3).unwrap(),
+                )
+                .unwrap();
+                let number_of_leafs = grid.reverse_index.len();
+                for (sample_number, point) in target.chunks(target_dimension).enumerate() {
+                    let (far_away_cells, near_leafs) =
+                        grid.get_far_away_cells_and_near_leafs(sample_number, point, None);
+                    assert_eq!(near_leafs.len(), 1);
+                    assert!(grid
+                        .get_leaf_samples(near_leafs[0])
+                        .contains(&sample_number));
+                    // Every leaf is either the one of the sample or
+                    // below exactly one of the far away cells.
+                    let mut leafs = far_away_cells
+                        .iter()
+                        .flat_map(|&cell| get_leafs_below(&grid, cell))
+                        .chain(near_leafs)
+                        .collect::<Vec<usize>>();
+                    leafs.sort_unstable();
+                    assert_eq!(leafs, (0..number_of_leafs).collect::<Vec<usize>>());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_population_up_propagation() {
+        for target_dimension in 1..=5 {
+            for depth in 1..=4 {
+                let mut grid: GradientGrid<f32, f32, PortablePdep> =
+                    GradientGrid::new(PortablePdep, depth, target_dimension, 3).unwrap();
+                let target = random_points(100 * target_dimension, 42);
+                grid.prepare(
+                    &target,
+                    &DenseMatrix::new(&random_points(300, 7), 3).unwrap(),
+                )
+                .unwrap();
+
+                // Each layer contains all of the samples.
+                for layer in 1..=depth {
+                    let population = (grid.get_number_of_elements_before_layer(layer)
+                        ..grid.get_number_of_elements_before_layer(layer + 1))
+                        .map(|cell| grid.get_cell_properties(cell).2)
+                        .sum::<usize>();
+                    assert_eq!(population, 100);
+                }
+
+                // Each cell has the population and averages of the union of its children.
+                for cell in 0..grid.get_number_of_elements_before_layer(depth) {
+                    let (target_average, original_average, population) =
+                        grid.get_cell_properties(cell);
+                    let mut target_sum = vec![0.0; target_dimension];
+                    let mut original_sum = vec![0.0; 3];
+                    let children_population =
+                        grid.iter_child_cells(cell)
+                            .map(|child| {
+                                let (child_target, child_original, child_population) =
+                                    grid.get_cell_properties(child);
+                                target_sum.iter_mut().zip(child_target.iter()).for_each(
+                                    |(sum, &value)| *sum += value * child_population as f32,
+                                );
+                                original_sum.iter_mut().zip(child_original.iter()).for_each(
+                                    |(sum, &value)| *sum += value * child_population as f32,
+                                );
+                                child_population
+                            })
+                            .sum::<usize>();
+                    assert_eq!(population, children_population);
+                    if population > 0 {
+                        target_average
+                            .iter()
+                            .chain(original_average.iter())
+                            .zip(target_sum.iter().chain(original_sum.iter()))
+                            .for_each(|(&average, &sum)| {
+                                assert!((average - sum / population as f32).abs() < 1e-5);
+                            });
+                    }
+                }
+
+                // The averages of the leafs are those of their samples.
+                for leaf in 0..grid.reverse_index.len() {
+                    let cell = grid.get_number_of_elements_before_layer(depth) + leaf;
+                    let (target_average, _, population) = grid.get_cell_properties(cell);
+                    assert_eq!(population, grid.get_leaf_samples(leaf).len());
+                    for (dimension, &average) in target_average.iter().enumerate() {
+                        let sum = grid
+                            .get_leaf_samples(leaf)
+                            .iter()
+                            .map(|&sample| target[sample * target_dimension + dimension])
+                            .sum::<f32>();
+                        if population > 0 {
+                            assert!((average - sum / population as f32).abs() < 1e-5);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_gradient_down_propagation() {
+        for target_dimension in 1..=5 {
+            for depth in 1..=4 {
+                let mut grid: GradientGrid<f32, f32, PortablePdep> =
+                    GradientGrid::new(PortablePdep, depth, target_dimension, 3).unwrap();
+                let target = random_points(100 * target_dimension, 42);
+                grid.prepare(
+                    &target,
+                    &DenseMatrix::new(&random_points(300, 7), 3).unwrap(),
+                )
+                .unwrap();
+
+                // We assign to each cell a gradient equal to its ID.
+                for cell in 0..grid.get_number_of_cells() {
+                    unsafe { grid.get_mut_cell_properties(cell) }
+                        .2
+                        .iter_mut()
+                        .for_each(|gradient| *gradient = cell as f32);
+                }
+                grid.downpropagate_gradient();
+                let mut variations = vec![0.0; target.len()];
+                grid.apply_gradient(&mut variations);
+
+                // Each sample receives the gradients of all of the cells containing it.
+                for (point, variation) in target
+                    .chunks(target_dimension)
+                    .zip(variations.chunks(target_dimension))
+                {
+                    let expected = (1..=depth)
+                        .map(|layer| grid.get_absolute_cell_id_unchecked(point, layer) as f32)
+                        .sum::<f32>();
+                    assert!(variation.iter().all(|&value| value == expected));
+                }
+            }
+        }
+    }
+
+    /// Checks that a single epoch of the Barnes-Hut model with the provided target
+    /// similarity moves the embedding as the exact model, up to the provided relative
+    /// tolerance for each of the provided values of theta.
+    fn assert_barnes_hut_gradient_matches_exact_gradient<K>(
+        target_similarity: K,
+        thetas_and_tolerances: &[(Option<f32>, f32)],
+    ) where
+        K: TargetSimilarity + Copy,
+    {
+        let (number_of_samples, target_dimension, original_dimension) = (64, 2, 4);
+        let original = random_points(number_of_samples * original_dimension, 7);
+        let initial_target = random_points(number_of_samples * target_dimension, 42);
+        let get_decomposition = || {
+            BasicIterativeDecomposition::new(
+                "test",
+                IterativeParameters {
+                    iterations: Some(1),
+                    learning_rate: Some(1e-3),
+                    initialization: Some(Initialization::Provided),
+                    deterministic: Some(true),
+                    verbose: Some(false),
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+        };
+        // Returns the variation of the embedding after a single epoch.
+        let get_variations = |model: &dyn Fn(&mut [f32])| {
+            let mut target = initial_target.clone();
+            model(&mut target);
+            target
+                .iter()
+                .zip(initial_target.iter())
+                .map(|(&after, &before)| after - before)
+                .collect::<Vec<f32>>()
+        };
+
+        // The exact model considers each unordered pair once, while the Barnes-Hut
+        // model considers both orders. The exact model also pairs each sample with
+        // itself, which moves the samples for the dot product similarity, while the
+        // Barnes-Hut model skips it, so we remove its variation.
+        let original_matrix = DenseMatrix::new(&original, original_dimension).unwrap();
+        let statistics = original_matrix.get_statistics().unwrap();
+        let exact = get_variations(&|target| {
+            SigmoidDecomposition::new(get_decomposition(), StandardizedDot, target_similarity)
+                .fit_transform(target, target_dimension, &original, original_dimension)
+                .unwrap();
+        })
+        .chunks(target_dimension)
+        .zip(initial_target.chunks(target_dimension))
+        .enumerate()
+        .flat_map(|(sample_number, (variation, point))| {
+            let (probability, own_coefficient, other_coefficient) =
+                target_similarity.similarity_and_gradient(point, point);
+            let original_probability = sigmoid(StandardizedDot.similarity(
+                original_matrix.get_sample(sample_number),
+                original_matrix.get_sample(sample_number),
+                &statistics,
             ));
+            let self_coefficient = -2.0
+                * 1e-3
+                * (own_coefficient + other_coefficient)
+                * (probability - original_probability);
+            variation
+                .iter()
+                .zip(point.iter())
+                .map(move |(&variation, &value)| 2.0 * (variation - self_coefficient * value))
+                .collect::<Vec<f32>>()
+        })
+        .collect::<Vec<f32>>();
+        let exact_norm = exact.iter().map(|value| value * value).sum::<f32>().sqrt();
+
+        for &(theta, tolerance) in thetas_and_tolerances {
+            let approximated = get_variations(&|target| {
+                BarnesHutSigmoidDecomposition::new(
+                    get_decomposition(),
+                    StandardizedDot,
+                    target_similarity,
+                    Some(3),
+                    theta,
+                    None,
+                )
+                .unwrap()
+                .fit_transform(target, target_dimension, &original, original_dimension)
+                .unwrap();
+            });
+            let error = approximated
+                .iter()
+                .zip(exact.iter())
+                .map(|(&left, &right)| (left - right) * (left - right))
+                .sum::<f32>()
+                .sqrt();
+            assert!(
+                error <= tolerance * exact_norm,
+                "theta {:?}: relative error {}",
+                theta,
+                error / exact_norm
+            );
         }
+    }
 
-        let mut grid: GradientGrid<Target, Original> =
-            GradientGrid::new(self.depth, 2, original_dimension);
-        let learning_rate: Target = self.get_learning_rate().as_();
+    #[test]
+    fn test_barnes_hut_gradient_matches_exact_gradient() {
+        assert_barnes_hut_gradient_matches_exact_gradient(
+            StudentT,
+            &[(Some(0.0), 1e-4), (Some(0.5), 0.2), (None, 0.6)],
+        );
+        assert_barnes_hut_gradient_matches_exact_gradient(
+            SigmoidDot,
+            &[(Some(0.0), 1e-4), (Some(0.5), 0.25), (None, 0.35)],
+        );
+    }
 
-        self.start_iterations()
-            .map(|_| {
-                grid.prepare(unsafe { *wrapped_target.get() }, original)?;
-                original
-                    .par_chunks(original_dimension)
+    #[test]
+    fn test_adaptive_grid_leafs_respect_capacity() {
+        for target_dimension in 1..=5 {
+            for leaf_capacity in [1, 4, 16] {
+                let mut grid: AdaptiveGradientGrid<f32, f32, PortablePdep> =
+                    AdaptiveGradientGrid::new(PortablePdep, leaf_capacity, 10, target_dimension, 3)
+                        .unwrap();
+                let target = random_points(100 * target_dimension, 42);
+                grid.prepare(
+                    &target,
+                    &DenseMatrix::new(&random_points(300, 7), 3).unwrap(),
+                )
+                .unwrap();
+
+                let mut samples = grid
+                    .cells
+                    .iter()
                     .enumerate()
-                    .map(|(sample_number, left_original_sample)| unsafe {
-                        (
-                            sample_number,
-                            &mut (*wrapped_target.get())[(sample_number * target_dimension)
-                                ..((sample_number + 1) * target_dimension)],
-                            left_original_sample,
-                        )
+                    .filter(|(_, cell)| cell.number_of_children == 0)
+                    .flat_map(|(leaf, cell)| {
+                        assert!(
+                            cell.end - cell.start <= leaf_capacity
+                                || cell.layer == grid.maximum_depth
+                        );
+                        grid.get_leaf_samples(leaf).to_vec()
                     })
-                    .map(
-                        |(sample_number, left_target_sample, left_original_sample)| {
-                            // First we iterate on the far away elements averages.
-                            grid.iter_mut_far_away_leafs_properties(
-                                left_target_sample[0],
-                                left_target_sample[1],
-                            )
-                            .for_each(
-                                |(
-                                    cell_target_average,
-                                    cell_original_average,
-                                    gradient,
-                                    population,
-                                )| {
-                                    let target_dot = dot(
-                                        left_target_sample.iter().copied(),
-                                        cell_target_average.iter().copied(),
-                                    );
-                                    let original_dot: Target = normal_dot(
-                                        left_original_sample,
-                                        cell_original_average,
-                                        &mean,
-                                        &variance,
-                                    )
-                                    .as_();
-                                    let mut variation = sigmoid(target_dot) - sigmoid(original_dot);
-                                    variation *= learning_rate;
-                                    left_target_sample
-                                        .iter_mut()
-                                        .zip(cell_target_average.iter().zip(gradient.iter_mut()))
-                                        .for_each(|(left, (right, gradient))| {
-                                            let left_tmp = *left;
-                                            *left -= *right * variation * population.as_();
-                                            *gradient -= left_tmp * variation;
-                                        });
-                                },
-                            );
-
-                            // First we iterate on the far away elements averages.
-                            grid.iter_siblings(left_target_sample[0], left_target_sample[1])
-                                .filter(|&sibling_id| sibling_id != sample_number)
-                                .map(|sibling_id| unsafe {
-                                    (
-                                        &mut (*wrapped_target.get())[(sibling_id * target_dimension)
-                                            ..((sibling_id + 1) * target_dimension)],
-                                        &original[(sibling_id * original_dimension)
-                                            ..((sibling_id + 1) * original_dimension)],
-                                    )
-                                })
-                                .for_each(|(sibling_target, sibling_original)| {
-                                    let target_dot = dot(
-                                        left_target_sample.iter().copied(),
-                                        sibling_target.iter().copied(),
-                                    );
-                                    let original_dot: Target = normal_dot(
-                                        left_original_sample,
-                                        sibling_original,
-                                        &mean,
-                                        &variance,
-                                    )
-                                    .as_();
-                                    let mut variation = sigmoid(target_dot) - sigmoid(original_dot);
-                                    variation *= learning_rate;
-                                    left_target_sample
-                                        .iter_mut()
-                                        .zip(sibling_target.iter_mut())
-                                        .for_each(|(left, right)| {
-                                            let left_tmp = *left;
-                                            *left -= *right * variation;
-                                            *right -= left_tmp * variation;
-                                        });
-                                });
+                    .collect::<Vec<usize>>();
+                samples.sort_unstable();
+                assert_eq!(samples, (0..100).collect::<Vec<usize>>());
 
-                            Ok(())
-                        },
-                    )
-                    .collect::<Result<(), String>>()?;
-                grid.downpropagate_gradient();
-                grid.apply_gradient(unsafe { &mut *wrapped_target.get() });
-                Ok(())
-            })
-            .collect::<Result<(), String>>()
+                // The children of each cell partition the samples of the cell.
+                grid.cells
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, cell)| cell.number_of_children > 0)
+                    .for_each(|(cell_id, cell)| {
+                        let children = grid.iter_child_cells(cell_id).collect::<Vec<usize>>();
+                        assert_eq!(grid.cells[children[0]].start, cell.start);
+                        assert_eq!(grid.cells[*children.last().unwrap()].end, cell.end);
+                        children.iter().for_each(|&child| {
+                            assert_eq!(grid.cells[child].parent, cell_id);
+                            assert_eq!(grid.cells[child].layer, cell.layer + 1);
+                        });
+                    });
+            }
+        }
     }
 }
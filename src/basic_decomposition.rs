@@ -1,3 +1,5 @@
+use crate::errors::DimensionalReductionError;
+
 #[derive(Clone)]
 pub struct BasicDecomposition {
     pub(crate) model_name: String,
@@ -10,9 +12,9 @@ impl BasicDecomposition {
         model_name: &str,
         random_state: Option<u64>,
         verbose: Option<bool>,
-    ) -> Result<Self, String> {
+    ) -> Result<Self, DimensionalReductionError> {
         if model_name.is_empty() {
-            return Err("The provided model name is empty.".to_string());
+            return Err(DimensionalReductionError::EmptyModelName);
         }
 
         Ok(Self {
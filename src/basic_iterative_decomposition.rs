@@ -1,24 +1,112 @@
 use crate::basic_decomposition::BasicDecomposition;
+use crate::errors::DimensionalReductionError;
+use crate::initialization::Initialization;
+use crate::optimizers::Optimizer;
+use crate::schedules::LearningRateSchedule;
 
 #[derive(Clone)]
 pub struct BasicIterativeDecomposition {
     pub(crate) basic_decomposition: BasicDecomposition,
     pub(crate) iterations: usize,
     pub(crate) learning_rate: f32,
+    pub(crate) learning_rate_schedule: Option<LearningRateSchedule>,
+    pub(crate) tolerance: Option<f32>,
+    pub(crate) patience: usize,
+    pub(crate) initialization: Initialization,
+    pub(crate) deterministic: bool,
+    pub(crate) optimizer: Optimizer,
+}
+
+/// The optional parameters of the iterative decompositions, where the
+/// parameters left to `None` take their default values.
+///
+/// New parameters are added as new fields, so that the models can be
+/// built by listing only the provided parameters and `..Default::default()`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct IterativeParameters {
+    /// The maximum number of epochs to run. By default, 50.
+    pub iterations: Option<usize>,
+    /// The learning rate of the model. By default, 1.0.
+    pub learning_rate: Option<f32>,
+    /// The schedule scaling the learning rate in each epoch.
+    /// By default, the one of the model, which for most models is constant.
+    pub learning_rate_schedule: Option<LearningRateSchedule>,
+    /// The relative change of the epoch loss under which an epoch is
+    /// considered not to improve the model. By default, no early stopping is done.
+    ///
+    /// Only the loss is monitored: the change of the embedding is deliberately not
+    /// used as a criterion, as the losses are invariant to rotations of the embedding,
+    /// which may therefore keep moving after the loss has converged.
+    pub tolerance: Option<f32>,
+    /// The number of consecutive epochs without improvement after which
+    /// the training is stopped. By default, 5.
+    pub patience: Option<usize>,
+    /// The strategy used to initialize the embedding. By default, random uniform.
+    pub initialization: Option<Initialization>,
+    /// Whether to compute the updates of each epoch from the embedding at its start and
+    /// apply them in a fixed order, so that the results do not depend on the number of
+    /// threads, at the cost of memory and speed. By default, false.
+    pub deterministic: Option<bool>,
+    /// The rule used to update the embedding. The stateful optimizers accumulate the
+    /// descent direction of each epoch before updating the embedding. By default, SGD.
+    pub optimizer: Option<Optimizer>,
+    /// The random state to reproduce the model. By default, 42.
+    pub random_state: Option<u64>,
+    /// Whether to show the loading bar. By default, true.
+    pub verbose: Option<bool>,
 }
 
 impl BasicIterativeDecomposition {
+    /// Returns new basic iterative decomposition.
+    ///
+    /// # Arguments
+    /// * `model_name`: The name of the model.
+    /// * `parameters`: The optional parameters of the decomposition.
     pub fn new(
-        iterations: Option<usize>,
-        learning_rate: Option<f32>,
         model_name: &str,
-        random_state: Option<u64>,
-        verbose: Option<bool>,
-    ) -> Result<Self, String> {
+        parameters: IterativeParameters,
+    ) -> Result<Self, DimensionalReductionError> {
+        let IterativeParameters {
+            iterations,
+            learning_rate,
+            learning_rate_schedule,
+            tolerance,
+            patience,
+            initialization,
+            deterministic,
+            optimizer,
+            random_state,
+            verbose,
+        } = parameters;
+
+        if let Some(tolerance) = tolerance {
+            if !tolerance.is_finite() || tolerance < 0.0 {
+                return Err(DimensionalReductionError::InvalidParameter {
+                    parameter: "tolerance",
+                    value: tolerance.to_string(),
+                    expected: "a finite non-negative value",
+                });
+            }
+        }
+
+        if patience == Some(0) {
+            return Err(DimensionalReductionError::InvalidParameter {
+                parameter: "patience",
+                value: 0.to_string(),
+                expected: "strictly positive",
+            });
+        }
+
         Ok(Self {
             basic_decomposition: BasicDecomposition::new(model_name, random_state, verbose)?,
             iterations: iterations.unwrap_or(50),
             learning_rate: learning_rate.unwrap_or(1.0),
+            learning_rate_schedule,
+            tolerance,
+            patience: patience.unwrap_or(5),
+            initialization: initialization.unwrap_or_default(),
+            deterministic: deterministic.unwrap_or(false),
+            optimizer: optimizer.unwrap_or_default(),
         })
     }
 }
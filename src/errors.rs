@@ -0,0 +1,103 @@
+use std::fmt::{Display, Formatter};
+
+/// Errors that may be raised by the decompositions.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DimensionalReductionError {
+    /// The provided model name is empty.
+    EmptyModelName,
+    /// The provided matrix has no samples.
+    EmptyInput,
+    /// The provided dimension of the named matrix is zero.
+    ZeroDimension { name: &'static str },
+    /// The length of the named matrix is not a multiple of its dimension.
+    IncompatibleDimension {
+        name: &'static str,
+        length: usize,
+        dimension: usize,
+    },
+    /// The original and target matrices have a different number of samples.
+    SamplesNumberMismatch {
+        original_samples: usize,
+        target_samples: usize,
+    },
+    /// The model does not support the requested target dimension.
+    UnsupportedTargetDimension {
+        target_dimension: usize,
+        supported_target_dimensions: &'static str,
+    },
+    /// The named matrix contains a non-finite value.
+    NonFiniteValue {
+        name: &'static str,
+        index: usize,
+        value: f64,
+    },
+    /// The named parameter has a value outside of its domain.
+    InvalidParameter {
+        parameter: &'static str,
+        value: String,
+        expected: &'static str,
+    },
+}
+
+impl Display for DimensionalReductionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::EmptyModelName => write!(f, "The provided model name is empty."),
+            Self::EmptyInput => write!(f, "The provided object is empty."),
+            Self::ZeroDimension { name } => {
+                write!(f, "The provided {} dimension is zero.", name)
+            }
+            Self::IncompatibleDimension {
+                name,
+                length,
+                dimension,
+            } => write!(
+                f,
+                concat!(
+                    "The provided {} slice has length {} ",
+                    "which is not compatible with the provided ",
+                    "{} dimension {}."
+                ),
+                name, length, name, dimension
+            ),
+            Self::SamplesNumberMismatch {
+                original_samples,
+                target_samples,
+            } => write!(
+                f,
+                concat!(
+                    "The provided original slice has {} samples ",
+                    "while the provided target slice has {} samples."
+                ),
+                original_samples, target_samples
+            ),
+            Self::UnsupportedTargetDimension {
+                target_dimension,
+                supported_target_dimensions,
+            } => write!(
+                f,
+                concat!(
+                    "The provided target dimension {} is not supported ",
+                    "by this model, which supports {}."
+                ),
+                target_dimension, supported_target_dimensions
+            ),
+            Self::NonFiniteValue { name, index, value } => write!(
+                f,
+                "The provided {} slice contains the non-finite value {} at index {}.",
+                name, value, index
+            ),
+            Self::InvalidParameter {
+                parameter,
+                value,
+                expected,
+            } => write!(
+                f,
+                "The provided {} {} is not valid, as it must be {}.",
+                parameter, value, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DimensionalReductionError {}
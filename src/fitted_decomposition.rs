@@ -0,0 +1,222 @@
+use crate::errors::DimensionalReductionError;
+use crate::matrices::FeatureStatistics;
+use crate::similarities::Similarity;
+use crate::target_similarities::TargetSimilarity;
+use crate::traits::*;
+use crate::utils::{binary_cross_entropy, sigmoid, validate_fit_transform_arguments};
+use num_traits::{AsPrimitive, Float};
+use rayon::prelude::*;
+use vec_rand::splitmix64;
+
+/// A decomposition fitted on a set of original samples.
+///
+/// The fitted decomposition keeps the original samples used for training,
+/// their mean and variance, and the learned embedding, so that new samples
+/// can be placed in the same target space without retraining the model.
+#[derive(Clone)]
+pub struct FittedDecomposition<M, Original, Target> {
+    model: M,
+    original: Vec<Original>,
+    original_dimension: usize,
+    target: Vec<Target>,
+    target_dimension: usize,
+    mean: Vec<Original>,
+    variance: Vec<Original>,
+    loss_history: Vec<f32>,
+}
+
+impl<M, Original, Target> FittedDecomposition<M, Original, Target>
+where
+    Original: GenericFeature,
+    usize: AsPrimitive<Original>,
+{
+    /// Returns a new fitted decomposition.
+    ///
+    /// # Arguments
+    /// * `model`: The model used to learn the embedding.
+    /// * `original`: The original samples used to train the model.
+    /// * `original_dimension`: The dimensionality of the original samples.
+    /// * `target`: The embedding learned for the original samples.
+    /// * `target_dimension`: The dimensionality of the embedding.
+    /// * `loss_history`: The loss history of the fit.
+    pub fn new(
+        model: M,
+        original: Vec<Original>,
+        original_dimension: usize,
+        target: Vec<Target>,
+        target_dimension: usize,
+        loss_history: Vec<f32>,
+    ) -> Result<Self, DimensionalReductionError> {
+        if target_dimension == 0 {
+            return Err(DimensionalReductionError::ZeroDimension { name: "target" });
+        }
+
+        if original_dimension == 0 {
+            return Err(DimensionalReductionError::ZeroDimension { name: "original" });
+        }
+
+        if original.len() / original_dimension != target.len() / target_dimension {
+            return Err(DimensionalReductionError::SamplesNumberMismatch {
+                original_samples: original.len() / original_dimension,
+                target_samples: target.len() / target_dimension,
+            });
+        }
+
+        let mean = original.as_slice().matrix_mean(original_dimension)?;
+        let variance = original.as_slice().matrix_var(original_dimension)?;
+
+        Ok(Self {
+            model,
+            original,
+            original_dimension,
+            target,
+            target_dimension,
+            mean,
+            variance,
+            loss_history,
+        })
+    }
+}
+
+impl<M, Original, Target> FittedDecomposition<M, Original, Target> {
+    /// Returns the model used to learn the embedding.
+    pub fn get_model(&self) -> &M {
+        &self.model
+    }
+
+    /// Returns the original samples used to train the model.
+    pub fn get_original(&self) -> &[Original] {
+        &self.original
+    }
+
+    /// Returns the dimensionality of the original samples.
+    pub fn get_original_dimension(&self) -> usize {
+        self.original_dimension
+    }
+
+    /// Returns the embedding learned for the original samples.
+    pub fn get_target(&self) -> &[Target] {
+        &self.target
+    }
+
+    /// Returns the dimensionality of the embedding.
+    pub fn get_target_dimension(&self) -> usize {
+        self.target_dimension
+    }
+
+    /// Returns the mean of the original samples.
+    pub fn get_mean(&self) -> &[Original] {
+        &self.mean
+    }
+
+    /// Returns the variance of the original samples.
+    pub fn get_variance(&self) -> &[Original] {
+        &self.variance
+    }
+
+    /// Returns the loss history of the fit.
+    pub fn get_loss_history(&self) -> &[f32] {
+        &self.loss_history
+    }
+
+    /// Returns the number of epochs that were run to fit the model.
+    pub fn get_number_of_epochs(&self) -> usize {
+        self.loss_history.len()
+    }
+
+    /// Returns the number of samples used to train the model.
+    pub fn get_number_of_samples(&self) -> usize {
+        self.target.len() / self.target_dimension
+    }
+}
+
+impl<M, Original, Target> FittedDecomposition<M, Original, Target>
+where
+    M: IterativeDecomposition + SimilarityDecomposition,
+    Original: AsPrimitive<Target> + GenericFeature + Float,
+    Target: Float + GenericFeature,
+    usize: AsPrimitive<Target>,
+    f32: AsPrimitive<Target>,
+{
+    /// Places the provided new samples in the learned target space.
+    ///
+    /// Only the new samples are optimized, while the embedding of the
+    /// samples used to train the model is kept frozen. The loss history
+    /// of the optimization of the new samples is returned.
+    ///
+    /// # Arguments
+    /// * `target`: The slice where to write the embedding of the new samples.
+    /// * `original`: The new samples to embed.
+    pub fn transform(
+        &self,
+        mut target: &mut [Target],
+        original: &[Original],
+    ) -> Result<Vec<f32>, DimensionalReductionError> {
+        validate_fit_transform_arguments(
+            target,
+            self.target_dimension,
+            original,
+            self.original_dimension,
+        )?;
+
+        // We use a different random state from the one used in training,
+        // so that the new samples do not start from the same positions
+        // of the first training samples.
+        target.random_init(splitmix64(self.model.get_random_state()));
+
+        let statistics = FeatureStatistics::new(self.mean.clone(), self.variance.clone());
+        let similarity = self.model.get_similarity();
+        let target_similarity = self.model.get_target_similarity();
+        let (target_dimension, original_dimension) =
+            (self.target_dimension, self.original_dimension);
+        let (training_target, training_original) =
+            (self.target.as_slice(), self.original.as_slice());
+        // Every new sample is paired with all of the training samples.
+        let number_of_pairs: Target =
+            (original.len() / original_dimension * self.get_number_of_samples()).as_();
+
+        // Since the training embedding is frozen, every new sample
+        // is only updated by its own thread and no data race may happen.
+        self.model.start_iterations(|_, learning_rate| {
+            let learning_rate: Target = learning_rate.as_();
+            let total_loss = target
+                .par_chunks_mut(target_dimension)
+                .zip(original.par_chunks(original_dimension))
+                .map(|(left_target_sample, left_original_sample)| {
+                    training_target
+                        .chunks(target_dimension)
+                        .zip(training_original.chunks(original_dimension))
+                        .map(|(right_target_sample, right_original_sample)| {
+                            let original_similarity: Target = similarity
+                                .similarity(
+                                    left_original_sample.into(),
+                                    right_original_sample.into(),
+                                    &statistics,
+                                )
+                                .as_();
+                            let (target_probability, own_coefficient, other_coefficient) =
+                                target_similarity.similarity_and_gradient(
+                                    left_target_sample,
+                                    right_target_sample,
+                                );
+                            let original_probability = sigmoid(original_similarity);
+                            let variation =
+                                (target_probability - original_probability) * learning_rate;
+                            left_target_sample
+                                .iter_mut()
+                                .zip(right_target_sample.iter().copied())
+                                .for_each(|(left, right)| {
+                                    *left -= (*left * own_coefficient + right * other_coefficient)
+                                        * variation;
+                                });
+                            binary_cross_entropy(original_probability, target_probability)
+                        })
+                        .sum::<Target>()
+                })
+                .sum::<Target>();
+            Ok((total_loss / number_of_pairs.max(Target::one()))
+                .to_f32()
+                .unwrap())
+        })
+    }
+}
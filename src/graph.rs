@@ -0,0 +1,223 @@
+use crate::{
+    errors::DimensionalReductionError,
+    matrices::{CSRMatrix, OriginalMatrix},
+    similarities::logit,
+    traits::{GenericFeature, MatrixIsFinite},
+};
+use num_traits::Float;
+
+/// Weighted graph whose nodes are embedded instead of original samples.
+///
+/// The graph is stored as its adjacency matrix in Compressed Sparse Row format,
+/// where the value in the i-th row and j-th column is the weight of the edge
+/// from the i-th to the j-th node.
+#[derive(Clone, Debug)]
+pub struct Graph<F> {
+    adjacency: CSRMatrix<F>,
+}
+
+impl<F> Graph<F>
+where
+    F: GenericFeature + Float,
+{
+    /// Returns a new graph with the provided adjacency matrix.
+    ///
+    /// # Arguments
+    /// * `adjacency`: The square adjacency matrix, with non-negative weights.
+    pub fn new(adjacency: CSRMatrix<F>) -> Result<Self, DimensionalReductionError> {
+        if adjacency.get_number_of_samples() != adjacency.get_dimension() {
+            return Err(DimensionalReductionError::InvalidParameter {
+                parameter: "adjacency matrix shape",
+                value: format!(
+                    "({}, {})",
+                    adjacency.get_number_of_samples(),
+                    adjacency.get_dimension()
+                ),
+                expected: "a square matrix",
+            });
+        }
+
+        adjacency.matrix_is_finite("adjacency")?;
+
+        if let Some(weight) = adjacency
+            .get_data()
+            .iter()
+            .find(|&&weight| weight < F::zero())
+        {
+            return Err(DimensionalReductionError::InvalidParameter {
+                parameter: "edge weight",
+                value: weight.to_string(),
+                expected: "non-negative",
+            });
+        }
+
+        Ok(Self { adjacency })
+    }
+
+    /// Returns a new undirected graph with the provided edges.
+    ///
+    /// Each edge is stored in both directions, and the weights of
+    /// repeated edges are summed.
+    ///
+    /// # Arguments
+    /// * `number_of_nodes`: The number of nodes of the graph.
+    /// * `sources`: The source node of each edge.
+    /// * `destinations`: The destination node of each edge.
+    /// * `weights`: The weight of each edge. By default, all edges have unit weight.
+    pub fn from_edge_list(
+        number_of_nodes: usize,
+        sources: &[usize],
+        destinations: &[usize],
+        weights: Option<&[F]>,
+    ) -> Result<Self, DimensionalReductionError> {
+        if number_of_nodes == 0 {
+            return Err(DimensionalReductionError::EmptyInput);
+        }
+
+        if destinations.len() != sources.len() {
+            return Err(DimensionalReductionError::InvalidParameter {
+                parameter: "destinations",
+                value: destinations.len().to_string(),
+                expected: "as many destinations as sources",
+            });
+        }
+
+        if let Some(weights) = weights {
+            if weights.len() != sources.len() {
+                return Err(DimensionalReductionError::InvalidParameter {
+                    parameter: "weights",
+                    value: weights.len().to_string(),
+                    expected: "as many weights as sources",
+                });
+            }
+        }
+
+        if let Some(&node) = sources
+            .iter()
+            .chain(destinations.iter())
+            .find(|&&node| node >= number_of_nodes)
+        {
+            return Err(DimensionalReductionError::InvalidParameter {
+                parameter: "node",
+                value: node.to_string(),
+                expected: "smaller than the number of nodes",
+            });
+        }
+
+        // We count the edges of each node, storing the self-loops only once.
+        let mut indptr = vec![0; number_of_nodes + 1];
+        sources
+            .iter()
+            .zip(destinations.iter())
+            .for_each(|(&source, &destination)| {
+                indptr[source + 1] += 1;
+                if source != destination {
+                    indptr[destination + 1] += 1;
+                }
+            });
+        (1..indptr.len()).for_each(|node| {
+            indptr[node] += indptr[node - 1];
+        });
+
+        let number_of_stored_edges = indptr[number_of_nodes];
+        let mut indices = vec![0; number_of_stored_edges];
+        let mut data = vec![F::zero(); number_of_stored_edges];
+        let mut offsets = indptr[..number_of_nodes].to_vec();
+        sources
+            .iter()
+            .zip(destinations.iter())
+            .enumerate()
+            .for_each(|(edge_number, (&source, &destination))| {
+                let weight = weights.map_or(F::one(), |weights| weights[edge_number]);
+                let mut push = |node: usize, neighbour: usize| {
+                    indices[offsets[node]] = neighbour;
+                    data[offsets[node]] = weight;
+                    offsets[node] += 1;
+                };
+                push(source, destination);
+                if source != destination {
+                    push(destination, source);
+                }
+            });
+
+        Self::new(CSRMatrix::new(indptr, indices, data, number_of_nodes)?)
+    }
+
+    /// Returns the adjacency matrix of the graph.
+    pub fn get_adjacency(&self) -> &CSRMatrix<F> {
+        &self.adjacency
+    }
+
+    /// Returns the number of nodes of the graph.
+    pub fn get_number_of_nodes(&self) -> usize {
+        self.adjacency.get_number_of_samples()
+    }
+
+    /// Returns the weight of the edge between the provided nodes, zero if there is none.
+    ///
+    /// # Arguments
+    /// * `source`: The source node of the edge.
+    /// * `destination`: The destination node of the edge.
+    pub fn get_weight(&self, source: usize, destination: usize) -> F {
+        self.adjacency
+            .get_sample(source)
+            .get_feature(destination)
+            .unwrap_or_else(F::zero)
+    }
+
+    /// Returns the similarity of the provided nodes as a logit, that is the logit
+    /// of one when they are connected or identical, and of zero otherwise.
+    ///
+    /// # Arguments
+    /// * `left`: The first node.
+    /// * `right`: The second node.
+    pub fn get_similarity<T>(&self, left: usize, right: usize) -> T
+    where
+        T: Float,
+    {
+        logit(
+            if left == right
+                || self.get_weight(left, right) > F::zero()
+                || self.get_weight(right, left) > F::zero()
+            {
+                T::one()
+            } else {
+                T::zero()
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_graph_from_edge_list() {
+        let graph = Graph::<f32>::from_edge_list(
+            4,
+            &[0, 1, 1, 2],
+            &[1, 0, 2, 2],
+            Some(&[1.0, 2.0, 0.5, 4.0]),
+        )
+        .unwrap();
+        let adjacency = graph.get_adjacency();
+        assert_eq!(adjacency.get_indptr(), &[0, 1, 3, 5, 5]);
+        assert_eq!(adjacency.get_indices(), &[1, 0, 2, 1, 2]);
+        assert_eq!(adjacency.get_data(), &[3.0, 3.0, 0.5, 0.5, 4.0]);
+        assert_eq!(graph.get_weight(1, 2), 0.5);
+        assert_eq!(graph.get_weight(0, 3), 0.0);
+        assert!(graph.get_similarity::<f32>(0, 1) > 0.0);
+        assert!(graph.get_similarity::<f32>(3, 3) > 0.0);
+        assert!(graph.get_similarity::<f32>(0, 3) < 0.0);
+
+        assert!(Graph::<f32>::from_edge_list(0, &[], &[], None).is_err());
+        assert!(Graph::<f32>::from_edge_list(2, &[0, 1], &[1], None).is_err());
+        assert!(Graph::<f32>::from_edge_list(2, &[0], &[1], Some(&[1.0, 2.0])).is_err());
+        assert!(Graph::<f32>::from_edge_list(2, &[0], &[2], None).is_err());
+        assert!(Graph::<f32>::from_edge_list(2, &[0], &[1], Some(&[-1.0])).is_err());
+        assert!(
+            Graph::new(CSRMatrix::<f32>::new(vec![0, 1], vec![1], vec![1.0], 2).unwrap()).is_err()
+        );
+    }
+}
@@ -0,0 +1,503 @@
+use crate::traits::*;
+use crate::{
+    basic_decomposition::BasicDecomposition,
+    errors::DimensionalReductionError,
+    graph::Graph,
+    knn::{approximate_nearest_neighbours_by, NNDescent},
+    matrices::{OriginalMatrix, PrecomputedMatrix},
+    pca_decomposition::{orthonormalize, PCADecomposition},
+    similarities::{Similarity, StandardizedDot},
+    traits::{DimensionalReduction, GenericFeature},
+    utils::{sigmoid, SparseRows},
+};
+use num_traits::{AsPrimitive, Float};
+use rayon::prelude::*;
+use vec_rand::{random_f32, splitmix64};
+
+/// Strategy used to initialize the embedding of the iterative decompositions.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Initialization {
+    /// Values drawn uniformly in the range [-1, 1].
+    #[default]
+    RandomUniform,
+    /// Values drawn from a Gaussian with zero mean and the provided standard deviation.
+    RandomGaussian { scale: f32 },
+    /// Principal components of the standardized original samples.
+    PCA,
+    /// Eigenvectors of the normalized affinity matrix of the nearest neighbours
+    /// graph of the original samples.
+    Spectral,
+    /// Values already provided by the caller in the target slice.
+    Provided,
+}
+
+impl Initialization {
+    /// Returns the initialization strategy with the provided name.
+    ///
+    /// # Arguments
+    /// * `name`: The name of the strategy, one of `random_uniform`,
+    ///   `random_gaussian`, `pca`, `spectral` and `provided`.
+    /// * `scale`: The standard deviation of the `random_gaussian` strategy. By default, 1e-4.
+    ///   It must not be provided with the other strategies.
+    pub fn from_name(name: &str, scale: Option<f32>) -> Result<Self, DimensionalReductionError> {
+        if let Some(scale) = scale {
+            if name != "random_gaussian" {
+                return Err(DimensionalReductionError::InvalidParameter {
+                    parameter: "initialization scale",
+                    value: scale.to_string(),
+                    expected: "only provided with the random_gaussian initialization",
+                });
+            }
+            if !scale.is_finite() || scale <= 0.0 {
+                return Err(DimensionalReductionError::InvalidParameter {
+                    parameter: "initialization scale",
+                    value: scale.to_string(),
+                    expected: "a finite strictly positive value",
+                });
+            }
+        }
+
+        Ok(match name {
+            "random_uniform" => Self::RandomUniform,
+            "random_gaussian" => Self::RandomGaussian {
+                scale: scale.unwrap_or(1e-4),
+            },
+            "pca" => Self::PCA,
+            "spectral" => Self::Spectral,
+            "provided" => Self::Provided,
+            name => {
+                return Err(DimensionalReductionError::InvalidParameter {
+                    parameter: "initialization",
+                    value: name.to_string(),
+                    expected: concat!(
+                        "one of random_uniform, random_gaussian, ",
+                        "pca, spectral and provided"
+                    ),
+                })
+            }
+        })
+    }
+
+    /// Writes the initial embedding of the provided original samples in the target slice.
+    ///
+    /// # Arguments
+    /// * `target`: The slice where to write the initial embedding.
+    /// * `target_dimension`: The dimensionality of the embedding.
+    /// * `original`: The original samples.
+    /// * `random_state`: The random state to reproduce the initialization.
+    pub fn initialize<Original, Target, M>(
+        &self,
+        mut target: &mut [Target],
+        target_dimension: usize,
+        original: &M,
+        random_state: u64,
+    ) -> Result<(), DimensionalReductionError>
+    where
+        Original: AsPrimitive<Target> + GenericFeature + Float,
+        Target: Float + GenericFeature,
+        M: OriginalMatrix<Original>,
+        usize: AsPrimitive<Original> + AsPrimitive<Target>,
+        f32: AsPrimitive<Target>,
+    {
+        match self {
+            Self::RandomUniform => {
+                target.random_init(random_state);
+            }
+            Self::RandomGaussian { scale } => {
+                target.random_gaussian_init(random_state, *scale);
+            }
+            Self::PCA => {
+                // Since the principal components are computed on the centered samples,
+                // scaling the features is enough to standardize them, and keeps them sparse.
+                let inverse_std = original
+                    .get_statistics()?
+                    .get_variance()
+                    .iter()
+                    .map(|&variance| variance.sqrt().max(Original::epsilon()).recip())
+                    .collect::<Vec<Original>>();
+                PCADecomposition::new(
+                    BasicDecomposition::new("PCA initialization", Some(random_state), Some(false))?,
+                    None,
+                )
+                .fit_transform_matrix(
+                    target,
+                    target_dimension,
+                    &original.scale_features(&inverse_std),
+                )?;
+                rescale(target);
+            }
+            Self::Spectral => {
+                // The affinities are restricted to the approximate nearest neighbours,
+                // so that the similarities of all of the pairs are never computed.
+                let statistics = original.get_statistics()?;
+                let number_of_samples = original.get_number_of_samples();
+                let similarity = |left: usize, right: usize| -> f64 {
+                    StandardizedDot
+                        .similarity(
+                            original.get_sample(left),
+                            original.get_sample(right),
+                            &statistics,
+                        )
+                        .to_f64()
+                        .unwrap()
+                };
+                let nearest_neighbours = approximate_nearest_neighbours_by(
+                    number_of_samples,
+                    SPECTRAL_NEIGHBOURS.min(number_of_samples.saturating_sub(1)),
+                    |left, right| -similarity(left, right),
+                    NNDescent::new(None, None, Some(random_state))?,
+                )?;
+                spectral_initialization(
+                    target,
+                    target_dimension,
+                    (0..number_of_samples)
+                        .map(|sample_number| {
+                            nearest_neighbours
+                                .get_neighbours(sample_number)
+                                .iter()
+                                .zip(nearest_neighbours.get_distances(sample_number))
+                                .map(|(&neighbour, &distance)| (neighbour, sigmoid(-distance)))
+                                .collect()
+                        })
+                        .collect(),
+                    random_state,
+                )?;
+                rescale(target);
+            }
+            Self::Provided => {
+                let provided: &[Target] = target;
+                provided.matrix_is_finite("target")?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the initial embedding of the samples of the provided precomputed
+    /// matrix in the target slice.
+    ///
+    /// Since the samples have no features, the PCA initialization is not supported,
+    /// while the spectral initialization uses the largest precomputed affinities
+    /// among the stored entries of each row.
+    ///
+    /// # Arguments
+    /// * `target`: The slice where to write the initial embedding.
+    /// * `target_dimension`: The dimensionality of the embedding.
+    /// * `precomputed`: The precomputed affinities or distances between the samples.
+    /// * `random_state`: The random state to reproduce the initialization.
+    pub fn initialize_precomputed<Original, Target, M>(
+        &self,
+        target: &mut [Target],
+        target_dimension: usize,
+        precomputed: &PrecomputedMatrix<M>,
+        random_state: u64,
+    ) -> Result<(), DimensionalReductionError>
+    where
+        Original: AsPrimitive<Target> + GenericFeature + Float,
+        Target: Float + GenericFeature,
+        M: OriginalMatrix<Original>,
+        usize: AsPrimitive<Original> + AsPrimitive<Target>,
+        f32: AsPrimitive<Target>,
+    {
+        match self {
+            Self::PCA => Err(DimensionalReductionError::InvalidParameter {
+                parameter: "initialization",
+                value: "pca".to_string(),
+                expected: "not pca when the input is a precomputed matrix",
+            }),
+            Self::Spectral => {
+                spectral_initialization(
+                    target,
+                    target_dimension,
+                    precomputed
+                        .get_most_similar::<Original>(SPECTRAL_NEIGHBOURS)
+                        .into_iter()
+                        .map(|row| {
+                            row.into_iter()
+                                .map(|(neighbour, affinity)| {
+                                    (neighbour, affinity.to_f64().unwrap())
+                                })
+                                .collect()
+                        })
+                        .collect(),
+                    random_state,
+                )?;
+                rescale(target);
+                Ok(())
+            }
+            // The other strategies do not depend on the original samples.
+            _ => self.initialize(
+                target,
+                target_dimension,
+                precomputed.get_matrix(),
+                random_state,
+            ),
+        }
+    }
+
+    /// Writes the initial embedding of the nodes of the provided graph in the target slice.
+    ///
+    /// Since the nodes have no features, the PCA initialization is not supported,
+    /// while the spectral initialization uses the adjacency of the nodes.
+    ///
+    /// # Arguments
+    /// * `target`: The slice where to write the initial embedding.
+    /// * `target_dimension`: The dimensionality of the embedding.
+    /// * `graph`: The graph whose nodes are embedded.
+    /// * `random_state`: The random state to reproduce the initialization.
+    pub fn initialize_graph<Weight, Target>(
+        &self,
+        target: &mut [Target],
+        target_dimension: usize,
+        graph: &Graph<Weight>,
+        random_state: u64,
+    ) -> Result<(), DimensionalReductionError>
+    where
+        Weight: AsPrimitive<Target> + GenericFeature + Float,
+        Target: Float + GenericFeature,
+        usize: AsPrimitive<Weight> + AsPrimitive<Target>,
+        f32: AsPrimitive<Target>,
+    {
+        match self {
+            Self::PCA => Err(DimensionalReductionError::InvalidParameter {
+                parameter: "initialization",
+                value: "pca".to_string(),
+                expected: "not pca when the input is a graph",
+            }),
+            Self::Spectral => {
+                // The connected nodes have unit affinity, as in their similarity.
+                spectral_initialization(
+                    target,
+                    target_dimension,
+                    (0..graph.get_number_of_nodes())
+                        .map(|node| {
+                            let mut neighbours = Vec::new();
+                            graph.get_adjacency().get_sample(node).for_each_feature(
+                                |neighbour, weight| {
+                                    if neighbour != node && weight > Weight::zero() {
+                                        neighbours.push((neighbour, 1.0));
+                                    }
+                                },
+                            );
+                            neighbours
+                        })
+                        .collect(),
+                    random_state,
+                )?;
+                rescale(target);
+                Ok(())
+            }
+            // The other strategies do not depend on the original samples.
+            _ => self.initialize(
+                target,
+                target_dimension,
+                graph.get_adjacency(),
+                random_state,
+            ),
+        }
+    }
+}
+
+/// Rescales the provided embedding so that its largest absolute value is one,
+/// matching the range of the random uniform initialization.
+fn rescale<F>(target: &mut [F])
+where
+    F: Float + GenericFeature,
+{
+    let largest = target
+        .par_iter()
+        .map(|value| value.abs())
+        .reduce(F::zero, F::max)
+        .max(F::epsilon());
+    target.par_iter_mut().for_each(|value| {
+        *value /= largest;
+    });
+}
+
+/// Number of subspace iterations used to compute the spectral embedding.
+const SPECTRAL_ITERATIONS: usize = 100;
+
+/// Number of neighbours of each sample in the graph of the spectral embedding.
+const SPECTRAL_NEIGHBOURS: usize = 15;
+
+/// Writes in the target slice the spectral embedding of the samples connected
+/// by the provided affinities.
+///
+/// The embedding is given by the eigenvectors of the normalized matrix
+/// D^-1/2 S D^-1/2, where S is the symmetrized matrix of the provided affinities,
+/// with a unit affinity between each sample and itself, and D is its degree matrix.
+/// The trivial eigenvector associated to the eigenvalue one is discarded.
+///
+/// Since the affinities are sparse, each iteration takes time linear
+/// in their number times the dimensionality of the embedding.
+///
+/// # Arguments
+/// * `target`: The slice where to write the spectral embedding.
+/// * `target_dimension`: The dimensionality of the embedding.
+/// * `affinities`: The other samples with a non-zero affinity from each sample, with their affinity.
+/// * `random_state`: The random state of the initial vectors.
+fn spectral_initialization<Target>(
+    target: &mut [Target],
+    target_dimension: usize,
+    affinities: Vec<Vec<(usize, f64)>>,
+    random_state: u64,
+) -> Result<(), DimensionalReductionError>
+where
+    Target: Float + GenericFeature,
+    f32: AsPrimitive<Target>,
+{
+    let number_of_samples = affinities.len();
+    let number_of_vectors = target_dimension + 1;
+
+    if number_of_vectors > number_of_samples {
+        return Err(DimensionalReductionError::UnsupportedTargetDimension {
+            target_dimension,
+            supported_target_dimensions: "less than the number of samples",
+        });
+    }
+
+    // We add both directions of each affinity, keeping the largest one
+    // when the two samples are neighbours of each other.
+    let affinities = SparseRows::<Target>::from_entries(
+        number_of_samples,
+        affinities
+            .into_iter()
+            .enumerate()
+            .flat_map(|(sample_number, row)| {
+                row.into_iter()
+                    .flat_map(move |(other_sample_number, affinity)| {
+                        [
+                            (sample_number, other_sample_number, affinity),
+                            (other_sample_number, sample_number, affinity),
+                        ]
+                    })
+                    .chain([(sample_number, sample_number, 1.0)])
+            })
+            .collect(),
+        f64::max,
+    );
+
+    let inverse_square_root_degrees = (0..number_of_samples)
+        .into_par_iter()
+        .map(|sample_number| {
+            affinities
+                .get_row(sample_number)
+                .1
+                .iter()
+                .copied()
+                .sum::<Target>()
+                .sqrt()
+                .recip()
+        })
+        .collect::<Vec<Target>>();
+
+    // We store the vectors as column-major, so that each vector is contiguous.
+    let mut vectors = (0..number_of_vectors * number_of_samples)
+        .map(|i| {
+            (2.0_f32 * random_f32(splitmix64(random_state.wrapping_add(i as u64))) - 1.0_f32).as_()
+        })
+        .collect::<Vec<Target>>();
+    orthonormalize(&mut vectors, number_of_samples);
+
+    let two: Target = 2.0_f32.as_();
+
+    for _ in 0..SPECTRAL_ITERATIONS {
+        // We multiply by (I + D^-1/2 S D^-1/2) / 2, whose eigenvalues are
+        // non-negative and sorted as those of the normalized similarity matrix.
+        let products = (0..number_of_samples)
+            .into_par_iter()
+            .map(|sample_number| {
+                let mut row_products = vectors
+                    .chunks(number_of_samples)
+                    .map(|vector| vector[sample_number])
+                    .collect::<Vec<Target>>();
+                let (neighbours, affinities) = affinities.get_row(sample_number);
+                neighbours
+                    .iter()
+                    .zip(affinities.iter())
+                    .for_each(|(&neighbour, &affinity)| {
+                        let normalized_affinity = affinity
+                            * inverse_square_root_degrees[sample_number]
+                            * inverse_square_root_degrees[neighbour];
+                        row_products
+                            .iter_mut()
+                            .zip(vectors.chunks(number_of_samples))
+                            .for_each(|(product, vector)| {
+                                *product += normalized_affinity * vector[neighbour];
+                            });
+                    });
+                row_products
+            })
+            .collect::<Vec<Vec<Target>>>();
+        vectors
+            .chunks_mut(number_of_samples)
+            .enumerate()
+            .for_each(|(vector_number, vector)| {
+                vector
+                    .iter_mut()
+                    .zip(products.iter())
+                    .for_each(|(value, row_products)| {
+                        *value = row_products[vector_number] / two;
+                    });
+            });
+        orthonormalize(&mut vectors, number_of_samples);
+    }
+
+    // We skip the first, trivial, eigenvector.
+    target
+        .par_chunks_mut(target_dimension)
+        .enumerate()
+        .for_each(|(sample_number, target_sample)| {
+            target_sample
+                .iter_mut()
+                .zip(vectors.chunks(number_of_samples).skip(1))
+                .for_each(|(value, vector)| {
+                    *value = vector[sample_number];
+                });
+        });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matrices::DenseMatrix;
+
+    #[test]
+    fn test_spectral_initialization_separates_clusters() {
+        let number_of_samples = 60;
+        // Two clusters on opposite sides of the origin.
+        let samples = (0..number_of_samples * 4)
+            .map(|i| {
+                let noise = random_f32(splitmix64(i as u64)) - 0.5;
+                if i / 4 < number_of_samples / 2 {
+                    3.0 + noise
+                } else {
+                    -3.0 + noise
+                }
+            })
+            .collect::<Vec<f32>>();
+        let original = DenseMatrix::new(&samples, 4).unwrap();
+        let mut target = vec![0.0_f32; number_of_samples];
+        Initialization::Spectral
+            .initialize(&mut target, 1, &original, 42)
+            .unwrap();
+        let (first, second) = target.split_at(number_of_samples / 2);
+        assert!(
+            first.iter().all(|&value| value * first[0] > 0.0)
+                && second.iter().all(|&value| value * first[0] < 0.0),
+            "{:?}",
+            target
+        );
+    }
+
+    #[test]
+    fn test_initialization_scale_requires_random_gaussian() {
+        assert_eq!(
+            Initialization::from_name("random_gaussian", Some(0.5)).unwrap(),
+            Initialization::RandomGaussian { scale: 0.5 }
+        );
+        assert!(Initialization::from_name("pca", Some(0.5)).is_err());
+        assert!(Initialization::from_name("random_uniform", Some(0.5)).is_err());
+    }
+}
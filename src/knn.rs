@@ -0,0 +1,619 @@
+use crate::{errors::DimensionalReductionError, matrices::OriginalMatrix, traits::GenericFeature};
+use num_traits::Float;
+use rayon::prelude::*;
+use vec_rand::splitmix64;
+
+/// Number of samples compared at once by the exact search, so that
+/// the compared samples remain in cache.
+const BLOCK_SIZE: usize = 64;
+
+/// Neighbours of a sample and their distances, with whether they
+/// were found in the last iteration of NN-descent.
+type FlaggedRow = Vec<(f64, usize, bool)>;
+
+/// Nearest neighbours of each sample and their distances, sorted by increasing distance.
+///
+/// Ties are broken by the sample number, so that the neighbours are deterministic.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NearestNeighbours<F> {
+    number_of_samples: usize,
+    number_of_neighbours: usize,
+    neighbours: Vec<usize>,
+    distances: Vec<F>,
+}
+
+impl<F: Copy> NearestNeighbours<F> {
+    /// Returns the nearest neighbours with the provided sorted rows.
+    fn from_rows(number_of_neighbours: usize, rows: Vec<Vec<(f64, usize)>>) -> Self
+    where
+        F: Float,
+    {
+        let number_of_samples = rows.len();
+        let (distances, neighbours) = rows
+            .into_iter()
+            .flatten()
+            .map(|(distance, neighbour)| (F::from(distance).unwrap(), neighbour))
+            .unzip();
+        Self {
+            number_of_samples,
+            number_of_neighbours,
+            neighbours,
+            distances,
+        }
+    }
+
+    /// Returns the number of samples.
+    pub fn get_number_of_samples(&self) -> usize {
+        self.number_of_samples
+    }
+
+    /// Returns the number of neighbours of each sample.
+    pub fn get_number_of_neighbours(&self) -> usize {
+        self.number_of_neighbours
+    }
+
+    /// Returns the nearest neighbours of the provided sample.
+    pub fn get_neighbours(&self, sample_number: usize) -> &[usize] {
+        &self.neighbours[sample_number * self.number_of_neighbours
+            ..(sample_number + 1) * self.number_of_neighbours]
+    }
+
+    /// Returns the distances of the provided sample from its nearest neighbours.
+    pub fn get_distances(&self, sample_number: usize) -> &[F] {
+        &self.distances[sample_number * self.number_of_neighbours
+            ..(sample_number + 1) * self.number_of_neighbours]
+    }
+}
+
+/// Parameters of the approximate nearest neighbours search with NN-descent.
+///
+/// The neighbours of each sample are initialized at random and then iteratively
+/// improved with the neighbours of its neighbours, until few of them change.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NNDescent {
+    iterations: usize,
+    tolerance: f64,
+    random_state: u64,
+}
+
+impl Default for NNDescent {
+    fn default() -> Self {
+        Self {
+            iterations: 10,
+            tolerance: 0.001,
+            random_state: 42,
+        }
+    }
+}
+
+impl NNDescent {
+    /// Returns new NN-descent parameters.
+    ///
+    /// # Arguments
+    /// * `iterations`: The maximum number of improvements of the neighbours. By default, 10.
+    /// * `tolerance`: The fraction of the neighbours that must change in an iteration
+    ///   for the search to continue. By default, 0.001.
+    /// * `random_state`: The random state of the initial neighbours. By default, 42.
+    pub fn new(
+        iterations: Option<usize>,
+        tolerance: Option<f64>,
+        random_state: Option<u64>,
+    ) -> Result<Self, DimensionalReductionError> {
+        let default = Self::default();
+
+        let iterations = iterations.unwrap_or(default.iterations);
+        if iterations == 0 {
+            return Err(DimensionalReductionError::InvalidParameter {
+                parameter: "iterations",
+                value: iterations.to_string(),
+                expected: "strictly positive",
+            });
+        }
+
+        let tolerance = tolerance.unwrap_or(default.tolerance);
+        if !tolerance.is_finite() || tolerance < 0.0 {
+            return Err(DimensionalReductionError::InvalidParameter {
+                parameter: "tolerance",
+                value: tolerance.to_string(),
+                expected: "finite and non-negative",
+            });
+        }
+
+        Ok(Self {
+            iterations,
+            tolerance,
+            random_state: random_state.unwrap_or(default.random_state),
+        })
+    }
+}
+
+/// Checks that the provided number of neighbours can be found among the provided samples.
+fn validate_number_of_neighbours(
+    number_of_samples: usize,
+    number_of_neighbours: usize,
+) -> Result<(), DimensionalReductionError> {
+    if number_of_samples == 0 {
+        return Err(DimensionalReductionError::EmptyInput);
+    }
+    if number_of_neighbours >= number_of_samples {
+        return Err(DimensionalReductionError::InvalidParameter {
+            parameter: "number of neighbours",
+            value: number_of_neighbours.to_string(),
+            expected: "smaller than the number of samples",
+        });
+    }
+    Ok(())
+}
+
+/// Inserts the provided candidate in the sorted row of the nearest neighbours,
+/// keeping at most the provided number of neighbours, and returns whether it was inserted.
+fn insert_candidate(
+    row: &mut Vec<(f64, usize)>,
+    number_of_neighbours: usize,
+    candidate: (f64, usize),
+) -> bool {
+    let compare = |left: &(f64, usize), right: &(f64, usize)| {
+        left.0.total_cmp(&right.0).then(left.1.cmp(&right.1))
+    };
+    if number_of_neighbours == 0
+        || row.len() == number_of_neighbours
+            && compare(&candidate, &row[number_of_neighbours - 1]).is_ge()
+    {
+        return false;
+    }
+    let position = row.partition_point(|neighbour| compare(neighbour, &candidate).is_lt());
+    if row.get(position).map(|neighbour| neighbour.1) == Some(candidate.1) {
+        return false;
+    }
+    row.insert(position, candidate);
+    row.truncate(number_of_neighbours);
+    true
+}
+
+/// Returns the exact nearest neighbours of each sample according to the provided distance.
+///
+/// The samples are compared in blocks, so that the distance of each pair is
+/// computed once per direction while the samples of the two blocks remain in cache.
+///
+/// # Arguments
+/// * `number_of_samples`: The number of samples.
+/// * `number_of_neighbours`: The number of neighbours of each sample.
+/// * `distance`: The distance between the two samples with the provided numbers.
+pub fn exact_nearest_neighbours_by<D>(
+    number_of_samples: usize,
+    number_of_neighbours: usize,
+    distance: D,
+) -> Result<NearestNeighbours<f64>, DimensionalReductionError>
+where
+    D: Fn(usize, usize) -> f64 + Sync,
+{
+    validate_number_of_neighbours(number_of_samples, number_of_neighbours)?;
+
+    let rows = (0..number_of_samples.div_ceil(BLOCK_SIZE))
+        .into_par_iter()
+        .flat_map_iter(|block_number| {
+            let block_start = block_number * BLOCK_SIZE;
+            let block_end = (block_start + BLOCK_SIZE).min(number_of_samples);
+            let mut rows =
+                vec![Vec::with_capacity(number_of_neighbours + 1); block_end - block_start];
+            (0..number_of_samples)
+                .step_by(BLOCK_SIZE)
+                .for_each(|other_block_start| {
+                    let other_block_end = (other_block_start + BLOCK_SIZE).min(number_of_samples);
+                    (block_start..block_end).zip(rows.iter_mut()).for_each(
+                        |(sample_number, row)| {
+                            (other_block_start..other_block_end)
+                                .filter(|&other_sample_number| other_sample_number != sample_number)
+                                .for_each(|other_sample_number| {
+                                    insert_candidate(
+                                        row,
+                                        number_of_neighbours,
+                                        (
+                                            distance(sample_number, other_sample_number),
+                                            other_sample_number,
+                                        ),
+                                    );
+                                });
+                        },
+                    );
+                });
+            rows
+        })
+        .collect::<Vec<Vec<(f64, usize)>>>();
+
+    Ok(NearestNeighbours::from_rows(number_of_neighbours, rows))
+}
+
+/// Returns the approximate nearest neighbours of each sample according to the
+/// provided distance, found with NN-descent.
+///
+/// In each iteration, every sample compares itself with the neighbours of its
+/// neighbours, considering both directions of the edges of the neighbours graph,
+/// and skipping the candidates reached only through edges that did not change.
+///
+/// # Arguments
+/// * `number_of_samples`: The number of samples.
+/// * `number_of_neighbours`: The number of neighbours of each sample.
+/// * `distance`: The distance between the two samples with the provided numbers.
+/// * `parameters`: The parameters of the search.
+pub fn approximate_nearest_neighbours_by<D>(
+    number_of_samples: usize,
+    number_of_neighbours: usize,
+    distance: D,
+    parameters: NNDescent,
+) -> Result<NearestNeighbours<f64>, DimensionalReductionError>
+where
+    D: Fn(usize, usize) -> f64 + Sync,
+{
+    validate_number_of_neighbours(number_of_samples, number_of_neighbours)?;
+
+    // The neighbours of each sample, with whether they were found in the last iteration.
+    let mut rows = (0..number_of_samples)
+        .into_par_iter()
+        .map(|sample_number| {
+            let mut row = Vec::with_capacity(number_of_neighbours + 1);
+            let mut random_state =
+                splitmix64(parameters.random_state.wrapping_add(sample_number as u64));
+            while row.len() < number_of_neighbours {
+                random_state = splitmix64(random_state);
+                let other_sample_number = random_state as usize % number_of_samples;
+                if other_sample_number != sample_number {
+                    insert_candidate(
+                        &mut row,
+                        number_of_neighbours,
+                        (
+                            distance(sample_number, other_sample_number),
+                            other_sample_number,
+                        ),
+                    );
+                }
+            }
+            row.into_iter()
+                .map(|(distance, neighbour)| (distance, neighbour, true))
+                .collect::<FlaggedRow>()
+        })
+        .collect::<Vec<FlaggedRow>>();
+
+    for _ in 0..parameters.iterations {
+        // The samples having each sample among their neighbours, limited
+        // to the closest ones so that the cost of an iteration is bounded.
+        let mut reverse_rows = vec![Vec::new(); number_of_samples];
+        rows.iter().enumerate().for_each(|(sample_number, row)| {
+            row.iter().for_each(|&(distance, neighbour, is_new)| {
+                reverse_rows[neighbour].push((distance, sample_number, is_new));
+            });
+        });
+        reverse_rows.par_iter_mut().for_each(|reverse_row| {
+            reverse_row.sort_unstable_by(|left: &(f64, usize, bool), right| {
+                left.0.total_cmp(&right.0).then(left.1.cmp(&right.1))
+            });
+            reverse_row.truncate(number_of_neighbours);
+        });
+
+        let (new_rows, updates): (Vec<FlaggedRow>, Vec<usize>) = (0..number_of_samples)
+            .into_par_iter()
+            .map(|sample_number| {
+                let adjacent = |sample_number: usize| {
+                    rows[sample_number]
+                        .iter()
+                        .chain(reverse_rows[sample_number].iter())
+                        .map(|&(_, neighbour, is_new)| (neighbour, is_new))
+                };
+                let mut candidates = adjacent(sample_number)
+                    .flat_map(|(neighbour, is_new)| {
+                        adjacent(neighbour)
+                            .filter(move |&(_, is_other_new)| is_new || is_other_new)
+                            .map(|(candidate, _)| candidate)
+                    })
+                    .filter(|&candidate| candidate != sample_number)
+                    .collect::<Vec<usize>>();
+                candidates.sort_unstable();
+                candidates.dedup();
+
+                let mut row = rows[sample_number]
+                    .iter()
+                    .map(|&(distance, neighbour, _)| (distance, neighbour))
+                    .collect::<Vec<(f64, usize)>>();
+                let mut inserted = Vec::new();
+                candidates.into_iter().for_each(|candidate| {
+                    if insert_candidate(
+                        &mut row,
+                        number_of_neighbours,
+                        (distance(sample_number, candidate), candidate),
+                    ) {
+                        inserted.push(candidate);
+                    }
+                });
+                let row = row
+                    .into_iter()
+                    .map(|(distance, neighbour)| {
+                        (distance, neighbour, inserted.contains(&neighbour))
+                    })
+                    .collect::<FlaggedRow>();
+                let updates = row.iter().filter(|&&(_, _, is_new)| is_new).count();
+                (row, updates)
+            })
+            .unzip();
+
+        rows = new_rows;
+        let updates = updates.into_iter().sum::<usize>();
+        if (updates as f64)
+            <= parameters.tolerance * (number_of_samples * number_of_neighbours) as f64
+        {
+            break;
+        }
+    }
+
+    Ok(NearestNeighbours::from_rows(
+        number_of_neighbours,
+        rows.into_iter()
+            .map(|row| {
+                row.into_iter()
+                    .map(|(distance, neighbour, _)| (distance, neighbour))
+                    .collect()
+            })
+            .collect(),
+    ))
+}
+
+/// Returns the Euclidean distance of each pair of samples of the provided matrix,
+/// summing the squared differences of their features in double precision.
+pub(crate) fn euclidean_distance<'a, F, M>(
+    original: &'a M,
+) -> impl Fn(usize, usize) -> f64 + Sync + 'a
+where
+    F: GenericFeature + Float,
+    M: OriginalMatrix<F>,
+{
+    move |left, right| {
+        let mut squared_distance = 0.0;
+        original
+            .get_sample(left)
+            .zip_features(&original.get_sample(right), |_, left, right| {
+                let difference = left.to_f64().unwrap() - right.to_f64().unwrap();
+                squared_distance += difference * difference;
+            });
+        squared_distance.sqrt()
+    }
+}
+
+/// Returns the exact nearest neighbours of each sample of the provided
+/// matrix, either dense or sparse, according to the Euclidean distance.
+///
+/// # Arguments
+/// * `original`: The samples whose neighbours are searched.
+/// * `number_of_neighbours`: The number of neighbours of each sample.
+pub fn exact_nearest_neighbours<F, M>(
+    original: &M,
+    number_of_neighbours: usize,
+) -> Result<NearestNeighbours<f64>, DimensionalReductionError>
+where
+    F: GenericFeature + Float,
+    M: OriginalMatrix<F>,
+{
+    exact_nearest_neighbours_by(
+        original.get_number_of_samples(),
+        number_of_neighbours,
+        euclidean_distance(original),
+    )
+}
+
+/// Returns the approximate nearest neighbours of each sample of the provided
+/// matrix, either dense or sparse, according to the Euclidean distance.
+///
+/// # Arguments
+/// * `original`: The samples whose neighbours are searched.
+/// * `number_of_neighbours`: The number of neighbours of each sample.
+/// * `parameters`: The parameters of the search.
+pub fn approximate_nearest_neighbours<F, M>(
+    original: &M,
+    number_of_neighbours: usize,
+    parameters: NNDescent,
+) -> Result<NearestNeighbours<f64>, DimensionalReductionError>
+where
+    F: GenericFeature + Float,
+    M: OriginalMatrix<F>,
+{
+    approximate_nearest_neighbours_by(
+        original.get_number_of_samples(),
+        number_of_neighbours,
+        euclidean_distance(original),
+        parameters,
+    )
+}
+
+/// Strategy used by the models to find the nearest neighbours of the original samples.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum NeighbourSearch {
+    /// Compares every pair of samples, taking quadratic time in the number of samples.
+    #[default]
+    Exact,
+    /// Approximates the neighbours with NN-descent, for larger numbers of samples.
+    Approximate(NNDescent),
+}
+
+impl NeighbourSearch {
+    /// Returns the neighbour search with the provided name.
+    ///
+    /// # Arguments
+    /// * `name`: The name of the search, one of `exact` and `approximate`.
+    /// * `iterations`: The maximum number of improvements of the `approximate` search.
+    ///   By default, 10. It must not be provided with the `exact` search.
+    /// * `tolerance`: The fraction of the neighbours that must change in an iteration for the
+    ///   `approximate` search to continue. By default, 0.001. It must not be provided with
+    ///   the `exact` search.
+    /// * `random_state`: The random state of the initial neighbours of the `approximate`
+    ///   search. By default, 42.
+    pub fn from_name(
+        name: &str,
+        iterations: Option<usize>,
+        tolerance: Option<f64>,
+        random_state: Option<u64>,
+    ) -> Result<Self, DimensionalReductionError> {
+        match name {
+            "exact" => {
+                if let Some(iterations) = iterations {
+                    return Err(DimensionalReductionError::InvalidParameter {
+                        parameter: "iterations",
+                        value: iterations.to_string(),
+                        expected: "only provided with the approximate neighbour search",
+                    });
+                }
+                if let Some(tolerance) = tolerance {
+                    return Err(DimensionalReductionError::InvalidParameter {
+                        parameter: "tolerance",
+                        value: tolerance.to_string(),
+                        expected: "only provided with the approximate neighbour search",
+                    });
+                }
+                Ok(Self::Exact)
+            }
+            "approximate" => Ok(Self::Approximate(NNDescent::new(
+                iterations,
+                tolerance,
+                random_state,
+            )?)),
+            name => Err(DimensionalReductionError::InvalidParameter {
+                parameter: "neighbour search",
+                value: name.to_string(),
+                expected: "one of exact and approximate",
+            }),
+        }
+    }
+
+    /// Returns the nearest neighbours of each sample of the provided
+    /// matrix, either dense or sparse, according to the Euclidean distance.
+    ///
+    /// # Arguments
+    /// * `original`: The samples whose neighbours are searched.
+    /// * `number_of_neighbours`: The number of neighbours of each sample.
+    pub fn search<F, M>(
+        &self,
+        original: &M,
+        number_of_neighbours: usize,
+    ) -> Result<NearestNeighbours<f64>, DimensionalReductionError>
+    where
+        F: GenericFeature + Float,
+        M: OriginalMatrix<F>,
+    {
+        match self {
+            Self::Exact => exact_nearest_neighbours(original, number_of_neighbours),
+            Self::Approximate(parameters) => {
+                approximate_nearest_neighbours(original, number_of_neighbours, *parameters)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matrices::DenseMatrix;
+    use vec_rand::random_f32;
+
+    const NUMBER_OF_SAMPLES: usize = 300;
+    const DIMENSION: usize = 4;
+
+    fn get_samples() -> Vec<f32> {
+        (0..(NUMBER_OF_SAMPLES * DIMENSION) as u64)
+            .map(|i| random_f32(splitmix64(i)))
+            .collect()
+    }
+
+    #[test]
+    fn test_exact_nearest_neighbours() {
+        let samples = get_samples();
+        let original = DenseMatrix::new(&samples, DIMENSION).unwrap();
+        let nearest_neighbours = exact_nearest_neighbours(&original, 5).unwrap();
+        assert_eq!(
+            nearest_neighbours.get_number_of_samples(),
+            NUMBER_OF_SAMPLES
+        );
+        for sample_number in 0..NUMBER_OF_SAMPLES {
+            let sample = &samples[sample_number * DIMENSION..(sample_number + 1) * DIMENSION];
+            let mut expected = (0..NUMBER_OF_SAMPLES)
+                .filter(|&other| other != sample_number)
+                .map(|other| {
+                    let distance = sample
+                        .iter()
+                        .zip(&samples[other * DIMENSION..(other + 1) * DIMENSION])
+                        .map(|(left, right)| (left - right).powi(2))
+                        .sum::<f32>()
+                        .sqrt();
+                    (distance, other)
+                })
+                .collect::<Vec<(f32, usize)>>();
+            expected.sort_by(|left, right| left.0.total_cmp(&right.0));
+            assert_eq!(
+                nearest_neighbours.get_neighbours(sample_number),
+                expected[..5]
+                    .iter()
+                    .map(|&(_, other)| other)
+                    .collect::<Vec<usize>>()
+            );
+            nearest_neighbours
+                .get_distances(sample_number)
+                .iter()
+                .zip(expected.iter())
+                .for_each(|(distance, (expected, _))| {
+                    assert!((distance - *expected as f64).abs() < 1e-5);
+                });
+        }
+        assert!(exact_nearest_neighbours(&original, NUMBER_OF_SAMPLES).is_err());
+    }
+
+    #[test]
+    fn test_approximate_nearest_neighbours() {
+        let samples = get_samples();
+        let original = DenseMatrix::new(&samples, DIMENSION).unwrap();
+        let exact = exact_nearest_neighbours(&original, 10).unwrap();
+        let approximate =
+            approximate_nearest_neighbours(&original, 10, NNDescent::default()).unwrap();
+        let recall = (0..NUMBER_OF_SAMPLES)
+            .map(|sample_number| {
+                approximate
+                    .get_neighbours(sample_number)
+                    .iter()
+                    .filter(|neighbour| exact.get_neighbours(sample_number).contains(neighbour))
+                    .count()
+            })
+            .sum::<usize>() as f64
+            / (NUMBER_OF_SAMPLES * 10) as f64;
+        assert!(recall > 0.9, "{}", recall);
+        assert_eq!(
+            approximate,
+            approximate_nearest_neighbours(&original, 10, NNDescent::default()).unwrap()
+        );
+        assert!(NNDescent::new(Some(0), None, None).is_err());
+    }
+
+    #[test]
+    fn test_neighbour_search() {
+        let samples = get_samples();
+        let original = DenseMatrix::new(&samples, DIMENSION).unwrap();
+        assert_eq!(
+            NeighbourSearch::from_name("exact", None, None, None)
+                .unwrap()
+                .search(&original, 5)
+                .unwrap(),
+            exact_nearest_neighbours(&original, 5).unwrap()
+        );
+        let approximate =
+            NeighbourSearch::from_name("approximate", Some(5), None, Some(7)).unwrap();
+        assert_eq!(
+            approximate,
+            NeighbourSearch::Approximate(NNDescent::new(Some(5), None, Some(7)).unwrap())
+        );
+        assert_eq!(
+            approximate.search(&original, 5).unwrap(),
+            approximate_nearest_neighbours(
+                &original,
+                5,
+                NNDescent::new(Some(5), None, Some(7)).unwrap()
+            )
+            .unwrap()
+        );
+        assert!(NeighbourSearch::from_name("exact", Some(5), None, None).is_err());
+        assert!(NeighbourSearch::from_name("annoy", None, None, None).is_err());
+    }
+}
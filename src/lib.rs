@@ -3,11 +3,41 @@
 pub mod barnes_hut_sigmoid_decomposition;
 pub mod basic_decomposition;
 pub mod basic_iterative_decomposition;
+pub mod errors;
+pub mod fitted_decomposition;
+pub mod graph;
+pub mod initialization;
+pub mod knn;
+pub mod matrices;
+pub mod mds_decomposition;
+pub mod metrics;
+pub mod optimizers;
+pub mod pca_decomposition;
 pub mod sampled_sigmoid_decomposition;
+pub mod schedules;
 pub mod sigmoid_decomposition;
+pub mod similarities;
+pub mod target_similarities;
 pub mod traits;
+pub mod tsne_decomposition;
+pub mod umap_decomposition;
 pub mod utils;
 
 pub use barnes_hut_sigmoid_decomposition::*;
+pub use errors::*;
+pub use fitted_decomposition::*;
+pub use graph::*;
+pub use initialization::*;
+pub use knn::*;
+pub use matrices::*;
+pub use mds_decomposition::*;
+pub use metrics::*;
+pub use optimizers::*;
+pub use pca_decomposition::*;
+pub use sampled_sigmoid_decomposition::*;
+pub use schedules::*;
 pub use sigmoid_decomposition::*;
-pub use sampled_sigmoid_decomposition::*;
\ No newline at end of file
+pub use similarities::*;
+pub use target_similarities::*;
+pub use tsne_decomposition::*;
+pub use umap_decomposition::*;
@@ -0,0 +1,929 @@
+use crate::{
+    errors::DimensionalReductionError,
+    similarities::{logit, PrecomputedKind},
+    traits::{GenericFeature, MatrixIsFinite, MatrixMean, MatrixSum, MatrixVariance},
+};
+use num_traits::{AsPrimitive, Float};
+use rayon::prelude::*;
+use std::{borrow::Cow, cmp::Ordering};
+
+/// Original sample, whose features are stored either densely or sparsely.
+#[derive(Clone, Copy, Debug)]
+pub enum Sample<'a, F> {
+    /// All of the features of the sample.
+    Dense(&'a [F]),
+    /// The features with the provided indices, sorted and unique,
+    /// while all of the other features of the sample are zero.
+    Sparse {
+        indices: &'a [usize],
+        values: &'a [F],
+        dimension: usize,
+    },
+}
+
+impl<'a, F> From<&'a [F]> for Sample<'a, F> {
+    fn from(values: &'a [F]) -> Self {
+        Self::Dense(values)
+    }
+}
+
+impl<'a, F> Sample<'a, F>
+where
+    F: GenericFeature + Float,
+{
+    /// Returns the number of features of the sample, including the zero ones.
+    pub fn get_dimension(&self) -> usize {
+        match self {
+            Self::Dense(values) => values.len(),
+            Self::Sparse { dimension, .. } => *dimension,
+        }
+    }
+
+    /// Returns the value of the provided feature, if it is stored in the sample.
+    pub fn get_feature(&self, index: usize) -> Option<F> {
+        match self {
+            Self::Dense(values) => values.get(index).copied(),
+            Self::Sparse {
+                indices, values, ..
+            } => indices
+                .binary_search(&index)
+                .ok()
+                .map(|position| values[position]),
+        }
+    }
+
+    /// Calls the provided operation on the index and value of each stored feature.
+    pub fn for_each_feature<O>(&self, mut operation: O)
+    where
+        O: FnMut(usize, F),
+    {
+        match self {
+            Self::Dense(values) => values
+                .iter()
+                .enumerate()
+                .for_each(|(index, &value)| operation(index, value)),
+            Self::Sparse {
+                indices, values, ..
+            } => indices
+                .iter()
+                .zip(values.iter())
+                .for_each(|(&index, &value)| operation(index, value)),
+        }
+    }
+
+    /// Calls the provided operation on the index and on the values of the two samples
+    /// of each feature stored in either sample, returning the number of such features.
+    ///
+    /// The features are visited by increasing index, and the features
+    /// missing from both samples, which are zero in both, are skipped.
+    ///
+    /// # Arguments
+    /// * `other`: The sample to pair with this one.
+    /// * `operation`: The operation to call on each of the features.
+    pub fn zip_features<O>(&self, other: &Sample<'_, F>, mut operation: O) -> usize
+    where
+        O: FnMut(usize, F, F),
+    {
+        match (self, other) {
+            (Self::Dense(left), Sample::Dense(right)) => {
+                left.iter()
+                    .zip(right.iter())
+                    .enumerate()
+                    .for_each(|(index, (&left, &right))| operation(index, left, right));
+                left.len()
+            }
+            (
+                Self::Dense(dense),
+                Sample::Sparse {
+                    indices, values, ..
+                },
+            ) => {
+                zip_dense_and_sparse(dense, indices, values, |index, dense, sparse| {
+                    operation(index, dense, sparse)
+                });
+                dense.len()
+            }
+            (
+                Self::Sparse {
+                    indices, values, ..
+                },
+                Sample::Dense(dense),
+            ) => {
+                zip_dense_and_sparse(dense, indices, values, |index, dense, sparse| {
+                    operation(index, sparse, dense)
+                });
+                dense.len()
+            }
+            (
+                Self::Sparse {
+                    indices: left_indices,
+                    values: left_values,
+                    ..
+                },
+                Sample::Sparse {
+                    indices: right_indices,
+                    values: right_values,
+                    ..
+                },
+            ) => {
+                let (mut left_position, mut right_position) = (0, 0);
+                let mut number_of_features = 0;
+                while left_position < left_indices.len() || right_position < right_indices.len() {
+                    // The exhausted sample is past all of the indices of the other one.
+                    let left_index = left_indices
+                        .get(left_position)
+                        .copied()
+                        .unwrap_or(usize::MAX);
+                    let right_index = right_indices
+                        .get(right_position)
+                        .copied()
+                        .unwrap_or(usize::MAX);
+                    match left_index.cmp(&right_index) {
+                        Ordering::Equal => {
+                            operation(
+                                left_index,
+                                left_values[left_position],
+                                right_values[right_position],
+                            );
+                            left_position += 1;
+                            right_position += 1;
+                        }
+                        Ordering::Less => {
+                            operation(left_index, left_values[left_position], F::zero());
+                            left_position += 1;
+                        }
+                        Ordering::Greater => {
+                            operation(right_index, F::zero(), right_values[right_position]);
+                            right_position += 1;
+                        }
+                    }
+                    number_of_features += 1;
+                }
+                number_of_features
+            }
+        }
+    }
+}
+
+/// Calls the provided operation on the index, dense value and sparse value of each feature.
+fn zip_dense_and_sparse<F, O>(dense: &[F], indices: &[usize], values: &[F], mut operation: O)
+where
+    F: GenericFeature + Float,
+    O: FnMut(usize, F, F),
+{
+    let mut position = 0;
+    dense.iter().enumerate().for_each(|(index, &dense)| {
+        let sparse = if indices.get(position) == Some(&index) {
+            position += 1;
+            values[position - 1]
+        } else {
+            F::zero()
+        };
+        operation(index, dense, sparse);
+    });
+}
+
+/// Mean and variance of the features of the original samples, together with
+/// the sums that allow comparing sparse samples without visiting all of the features.
+#[derive(Clone, Debug)]
+pub struct FeatureStatistics<F> {
+    mean: Vec<F>,
+    variance: Vec<F>,
+    squared_mean_sum: F,
+    standardized_squared_mean_sum: F,
+}
+
+impl<F> FeatureStatistics<F>
+where
+    F: GenericFeature + Float,
+{
+    /// Returns the statistics with the provided mean and variance of the features.
+    pub fn new(mean: Vec<F>, variance: Vec<F>) -> Self {
+        let (squared_mean_sum, standardized_squared_mean_sum) =
+            mean.iter().zip(variance.iter()).fold(
+                (F::zero(), F::zero()),
+                |(total, standardized_total), (&mean, &variance)| {
+                    (
+                        total + mean * mean,
+                        // The constant features carry no information on the samples.
+                        if variance > F::zero() {
+                            standardized_total + mean * mean / variance
+                        } else {
+                            standardized_total
+                        },
+                    )
+                },
+            );
+        Self {
+            mean,
+            variance,
+            squared_mean_sum,
+            standardized_squared_mean_sum,
+        }
+    }
+
+    /// Returns the mean of each feature.
+    pub fn get_mean(&self) -> &[F] {
+        &self.mean
+    }
+
+    /// Returns the variance of each feature.
+    pub fn get_variance(&self) -> &[F] {
+        &self.variance
+    }
+
+    /// Returns the sum of the squared means of the features.
+    pub fn get_squared_mean_sum(&self) -> F {
+        self.squared_mean_sum
+    }
+
+    /// Returns the sum of the squared means of the non-constant features
+    /// divided by their variance.
+    pub fn get_standardized_squared_mean_sum(&self) -> F {
+        self.standardized_squared_mean_sum
+    }
+}
+
+/// Matrix of original samples, with a row for each sample.
+pub trait OriginalMatrix<F>: MatrixIsFinite + Sync
+where
+    F: GenericFeature + Float,
+{
+    /// The matrix with the same samples and the features multiplied by a factor.
+    type Scaled: OriginalMatrix<F>;
+
+    /// Returns the number of samples of the matrix.
+    fn get_number_of_samples(&self) -> usize;
+
+    /// Returns the number of features of each sample.
+    fn get_dimension(&self) -> usize;
+
+    /// Returns the provided sample.
+    fn get_sample(&self, sample_number: usize) -> Sample<'_, F>;
+
+    /// Returns the mean and variance of the features of the samples.
+    fn get_statistics(&self) -> Result<FeatureStatistics<F>, DimensionalReductionError>;
+
+    /// Returns the matrix with each feature multiplied by the provided factor.
+    fn scale_features(&self, factors: &[F]) -> Self::Scaled;
+}
+
+/// Dense row-major matrix of original samples.
+#[derive(Clone, Debug)]
+pub struct DenseMatrix<'a, F>
+where
+    F: Clone,
+{
+    values: Cow<'a, [F]>,
+    dimension: usize,
+}
+
+impl<'a, F> DenseMatrix<'a, F>
+where
+    F: GenericFeature + Float,
+{
+    /// Returns the dense matrix with the provided row-major values.
+    ///
+    /// # Arguments
+    /// * `values`: The row-major values of the matrix.
+    /// * `dimension`: The number of features of each sample.
+    pub fn new(values: &'a [F], dimension: usize) -> Result<Self, DimensionalReductionError> {
+        if dimension == 0 {
+            return Err(DimensionalReductionError::ZeroDimension { name: "original" });
+        }
+
+        if !values.len().is_multiple_of(dimension) {
+            return Err(DimensionalReductionError::IncompatibleDimension {
+                name: "original",
+                length: values.len(),
+                dimension,
+            });
+        }
+
+        Ok(Self {
+            values: Cow::Borrowed(values),
+            dimension,
+        })
+    }
+
+    /// Returns the row-major values of the matrix.
+    pub fn get_values(&self) -> &[F] {
+        &self.values
+    }
+}
+
+impl<'a, F> MatrixIsFinite for DenseMatrix<'a, F>
+where
+    F: GenericFeature + Float,
+{
+    fn matrix_is_finite(&self, name: &'static str) -> Result<(), DimensionalReductionError> {
+        self.get_values().matrix_is_finite(name)
+    }
+}
+
+impl<'a, F> OriginalMatrix<F> for DenseMatrix<'a, F>
+where
+    F: GenericFeature + Float,
+    usize: AsPrimitive<F>,
+{
+    type Scaled = DenseMatrix<'static, F>;
+
+    fn get_number_of_samples(&self) -> usize {
+        self.values.len() / self.dimension
+    }
+
+    fn get_dimension(&self) -> usize {
+        self.dimension
+    }
+
+    fn get_sample(&self, sample_number: usize) -> Sample<'_, F> {
+        Sample::Dense(
+            &self.values[sample_number * self.dimension..(sample_number + 1) * self.dimension],
+        )
+    }
+
+    fn get_statistics(&self) -> Result<FeatureStatistics<F>, DimensionalReductionError> {
+        let values = self.get_values();
+        Ok(FeatureStatistics::new(
+            values.matrix_mean(self.dimension)?,
+            values.matrix_var(self.dimension)?,
+        ))
+    }
+
+    fn scale_features(&self, factors: &[F]) -> Self::Scaled {
+        DenseMatrix {
+            values: Cow::Owned(
+                self.values
+                    .par_chunks(self.dimension)
+                    .flat_map_iter(|sample| {
+                        sample
+                            .iter()
+                            .zip(factors.iter())
+                            .map(|(&value, &factor)| value * factor)
+                    })
+                    .collect(),
+            ),
+            dimension: self.dimension,
+        }
+    }
+}
+
+/// Sparse matrix of original samples in Compressed Sparse Row format.
+///
+/// The features of the i-th sample are stored in the positions
+/// from `indptr[i]` to `indptr[i + 1]` of the indices and data.
+#[derive(Clone, Debug)]
+pub struct CSRMatrix<F> {
+    indptr: Vec<usize>,
+    indices: Vec<usize>,
+    data: Vec<F>,
+    number_of_columns: usize,
+}
+
+impl<F> CSRMatrix<F>
+where
+    F: GenericFeature + Float,
+{
+    /// Returns a new sparse matrix in Compressed Sparse Row format.
+    ///
+    /// The indices of each row are sorted, and the values of repeated
+    /// indices in the same row are summed.
+    ///
+    /// # Arguments
+    /// * `indptr`: The offsets of the rows in the indices and data, starting with zero.
+    /// * `indices`: The column of each stored value.
+    /// * `data`: The stored values.
+    /// * `number_of_columns`: The number of features of each sample.
+    pub fn new(
+        indptr: Vec<usize>,
+        indices: Vec<usize>,
+        data: Vec<F>,
+        number_of_columns: usize,
+    ) -> Result<Self, DimensionalReductionError> {
+        if number_of_columns == 0 {
+            return Err(DimensionalReductionError::ZeroDimension { name: "original" });
+        }
+
+        if indptr.first() != Some(&0)
+            || indptr.windows(2).any(|window| window[0] > window[1])
+            || indptr.last() != Some(&indices.len())
+        {
+            return Err(DimensionalReductionError::InvalidParameter {
+                parameter: "indptr",
+                value: format!("{:?}", indptr),
+                expected: "non-decreasing offsets from zero to the number of stored values",
+            });
+        }
+
+        if indices.len() != data.len() {
+            return Err(DimensionalReductionError::IncompatibleDimension {
+                name: "data",
+                length: data.len(),
+                dimension: indices.len(),
+            });
+        }
+
+        if let Some(&index) = indices.iter().find(|&&index| index >= number_of_columns) {
+            return Err(DimensionalReductionError::InvalidParameter {
+                parameter: "indices",
+                value: index.to_string(),
+                expected: "smaller than the number of columns",
+            });
+        }
+
+        let is_canonical = indptr.windows(2).all(|window| {
+            indices[window[0]..window[1]]
+                .windows(2)
+                .all(|pair| pair[0] < pair[1])
+        });
+        if is_canonical {
+            return Ok(Self {
+                indptr,
+                indices,
+                data,
+                number_of_columns,
+            });
+        }
+
+        let mut canonical_indptr = Vec::with_capacity(indptr.len());
+        let mut canonical_indices = Vec::with_capacity(indices.len());
+        let mut canonical_data = Vec::with_capacity(data.len());
+        canonical_indptr.push(0);
+        for window in indptr.windows(2) {
+            let mut row = indices[window[0]..window[1]]
+                .iter()
+                .copied()
+                .zip(data[window[0]..window[1]].iter().copied())
+                .collect::<Vec<(usize, F)>>();
+            row.sort_by_key(|&(index, _)| index);
+            let row_start = canonical_indices.len();
+            for (index, value) in row {
+                if canonical_indices.len() > row_start && canonical_indices.last() == Some(&index) {
+                    *canonical_data.last_mut().unwrap() += value;
+                } else {
+                    canonical_indices.push(index);
+                    canonical_data.push(value);
+                }
+            }
+            canonical_indptr.push(canonical_indices.len());
+        }
+
+        Ok(Self {
+            indptr: canonical_indptr,
+            indices: canonical_indices,
+            data: canonical_data,
+            number_of_columns,
+        })
+    }
+
+    /// Returns the offsets of the rows in the indices and data.
+    pub fn get_indptr(&self) -> &[usize] {
+        &self.indptr
+    }
+
+    /// Returns the column of each stored value.
+    pub fn get_indices(&self) -> &[usize] {
+        &self.indices
+    }
+
+    /// Returns the stored values.
+    pub fn get_data(&self) -> &[F] {
+        &self.data
+    }
+
+    /// Returns the number of stored values.
+    pub fn get_number_of_stored_values(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Checks that the provided dimensionality is the number of columns of the matrix.
+    fn validate_dimensionality(
+        &self,
+        dimensionality: usize,
+    ) -> Result<(), DimensionalReductionError> {
+        if dimensionality != self.number_of_columns {
+            return Err(DimensionalReductionError::IncompatibleDimension {
+                name: "sparse matrix",
+                length: self.number_of_columns,
+                dimension: dimensionality,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl<F> MatrixSum<F> for CSRMatrix<F>
+where
+    F: GenericFeature + Float,
+{
+    fn matrix_sum(&self, dimensionality: usize) -> Result<Vec<F>, DimensionalReductionError> {
+        self.validate_dimensionality(dimensionality)?;
+        // The stored values are summed sequentially, so that the
+        // result does not depend on the number of threads.
+        let mut sum = vec![F::zero(); self.number_of_columns];
+        self.indices
+            .iter()
+            .zip(self.data.iter())
+            .for_each(|(&index, &value)| {
+                sum[index] += value;
+            });
+        Ok(sum)
+    }
+}
+
+impl<F> MatrixMean<F> for CSRMatrix<F>
+where
+    F: GenericFeature + Float,
+{
+    fn matrix_mean(&self, dimensionality: usize) -> Result<Vec<F>, DimensionalReductionError> {
+        if self.get_number_of_samples() == 0 {
+            return Err(DimensionalReductionError::EmptyInput);
+        }
+
+        let mut mean = self.matrix_sum(dimensionality)?;
+        let number_of_samples = F::from(self.get_number_of_samples()).unwrap();
+        mean.iter_mut().for_each(|value| {
+            *value /= number_of_samples;
+        });
+        Ok(mean)
+    }
+}
+
+impl<F> MatrixVariance<F> for CSRMatrix<F>
+where
+    F: GenericFeature + Float,
+{
+    fn matrix_var(&self, dimensionality: usize) -> Result<Vec<F>, DimensionalReductionError> {
+        let mean = self.matrix_mean(dimensionality)?;
+        let number_of_samples = self.get_number_of_samples();
+
+        // The stored values contribute their squared deviation, while
+        // each of the zeros contributes the squared mean of its feature.
+        let mut unnormalized_variance = vec![F::zero(); self.number_of_columns];
+        let mut number_of_stored_values = vec![0_usize; self.number_of_columns];
+        self.indices
+            .iter()
+            .zip(self.data.iter())
+            .for_each(|(&index, &value)| {
+                unnormalized_variance[index] += (value - mean[index]) * (value - mean[index]);
+                number_of_stored_values[index] += 1;
+            });
+
+        let number_of_samples_as_feature = F::from(number_of_samples).unwrap();
+        Ok(unnormalized_variance
+            .into_iter()
+            .zip(number_of_stored_values.into_iter().zip(mean))
+            .map(|(deviation, (stored_values, mean))| {
+                (deviation + F::from(number_of_samples - stored_values).unwrap() * mean * mean)
+                    / number_of_samples_as_feature
+            })
+            .collect())
+    }
+}
+
+impl<F> MatrixIsFinite for CSRMatrix<F>
+where
+    F: GenericFeature + Float,
+{
+    fn matrix_is_finite(&self, name: &'static str) -> Result<(), DimensionalReductionError> {
+        self.data.as_slice().matrix_is_finite(name)
+    }
+}
+
+impl<F> OriginalMatrix<F> for CSRMatrix<F>
+where
+    F: GenericFeature + Float,
+{
+    type Scaled = CSRMatrix<F>;
+
+    fn get_number_of_samples(&self) -> usize {
+        self.indptr.len() - 1
+    }
+
+    fn get_dimension(&self) -> usize {
+        self.number_of_columns
+    }
+
+    fn get_sample(&self, sample_number: usize) -> Sample<'_, F> {
+        let row = self.indptr[sample_number]..self.indptr[sample_number + 1];
+        Sample::Sparse {
+            indices: &self.indices[row.clone()],
+            values: &self.data[row],
+            dimension: self.number_of_columns,
+        }
+    }
+
+    fn get_statistics(&self) -> Result<FeatureStatistics<F>, DimensionalReductionError> {
+        Ok(FeatureStatistics::new(
+            self.matrix_mean(self.number_of_columns)?,
+            self.matrix_var(self.number_of_columns)?,
+        ))
+    }
+
+    fn scale_features(&self, factors: &[F]) -> Self::Scaled {
+        Self {
+            indptr: self.indptr.clone(),
+            indices: self.indices.clone(),
+            data: self
+                .indices
+                .iter()
+                .zip(self.data.iter())
+                .map(|(&index, &value)| value * factors[index])
+                .collect(),
+            number_of_columns: self.number_of_columns,
+        }
+    }
+}
+
+/// Square matrix of affinities or distances between the samples, precomputed by the caller
+/// instead of the features of the samples.
+///
+/// The pairs missing from a sparse matrix have zero affinity, that is an infinite
+/// distance, while each sample has unit affinity with itself regardless of the diagonal.
+#[derive(Clone, Debug)]
+pub struct PrecomputedMatrix<M> {
+    matrix: M,
+    kind: PrecomputedKind,
+}
+
+impl<M> PrecomputedMatrix<M> {
+    /// Returns a new precomputed matrix.
+    ///
+    /// # Arguments
+    /// * `matrix`: The square matrix, dense or sparse, with a row and a column for each sample.
+    /// * `kind`: Whether the matrix contains affinities or distances.
+    pub fn new<F>(matrix: M, kind: PrecomputedKind) -> Result<Self, DimensionalReductionError>
+    where
+        F: GenericFeature + Float,
+        M: OriginalMatrix<F>,
+    {
+        if matrix.get_number_of_samples() != matrix.get_dimension() {
+            return Err(DimensionalReductionError::InvalidParameter {
+                parameter: "precomputed matrix shape",
+                value: format!(
+                    "({}, {})",
+                    matrix.get_number_of_samples(),
+                    matrix.get_dimension()
+                ),
+                expected: "a square matrix",
+            });
+        }
+
+        matrix.matrix_is_finite("precomputed")?;
+
+        if let Some(value) = (0..matrix.get_number_of_samples())
+            .into_par_iter()
+            .find_map_first(|sample_number| {
+                let mut invalid_value = None;
+                matrix
+                    .get_sample(sample_number)
+                    .for_each_feature(|_, value| {
+                        if invalid_value.is_none() && !kind.is_valid(value) {
+                            invalid_value = Some(value);
+                        }
+                    });
+                invalid_value
+            })
+        {
+            return Err(DimensionalReductionError::InvalidParameter {
+                parameter: "precomputed value",
+                value: value.to_string(),
+                expected: match kind {
+                    PrecomputedKind::Affinity => "affinities in the range [0, 1]",
+                    PrecomputedKind::Distance { .. } => "non-negative distances",
+                },
+            });
+        }
+
+        Ok(Self { matrix, kind })
+    }
+
+    /// Returns the underlying square matrix.
+    pub fn get_matrix(&self) -> &M {
+        &self.matrix
+    }
+
+    /// Returns whether the matrix contains affinities or distances.
+    pub fn get_kind(&self) -> PrecomputedKind {
+        self.kind
+    }
+
+    /// Returns the similarity of the provided samples as a logit, like the
+    /// similarities computed on the features, from their symmetrized affinity.
+    ///
+    /// # Arguments
+    /// * `left`: The number of the first sample.
+    /// * `right`: The number of the second sample.
+    pub fn get_similarity<F>(&self, left: usize, right: usize) -> F
+    where
+        F: GenericFeature + Float,
+        M: OriginalMatrix<F>,
+    {
+        if left == right {
+            return logit(F::one());
+        }
+        logit(self.get_symmetrized_affinity(left, right))
+    }
+
+    /// Returns the mean of the affinities of the provided samples in the two directions.
+    fn get_symmetrized_affinity<F>(&self, left: usize, right: usize) -> F
+    where
+        F: GenericFeature + Float,
+        M: OriginalMatrix<F>,
+    {
+        let affinity = |row: usize, column: usize| {
+            self.kind
+                .get_affinity(self.matrix.get_sample(row).get_feature(column))
+        };
+        (affinity(left, right) + affinity(right, left)) / (F::one() + F::one())
+    }
+
+    /// Returns, for each sample, at most the provided number of other samples with
+    /// the largest non-zero symmetrized affinities, sorted by decreasing affinity.
+    ///
+    /// Only the entries stored in the row of each sample are considered, so that
+    /// sparse matrices are processed in time linear in their number of entries.
+    ///
+    /// # Arguments
+    /// * `number_of_neighbours`: The largest number of samples returned for each sample.
+    pub fn get_most_similar<F>(&self, number_of_neighbours: usize) -> Vec<Vec<(usize, F)>>
+    where
+        F: GenericFeature + Float,
+        M: OriginalMatrix<F>,
+    {
+        (0..self.matrix.get_number_of_samples())
+            .into_par_iter()
+            .map(|sample_number| {
+                let mut others = Vec::new();
+                self.matrix
+                    .get_sample(sample_number)
+                    .for_each_feature(|other_sample_number, _| {
+                        if other_sample_number != sample_number {
+                            others.push(other_sample_number);
+                        }
+                    });
+                // The sparse rows may contain repeated indices.
+                others.sort_unstable();
+                others.dedup();
+                let mut most_similar = others
+                    .into_iter()
+                    .map(|other_sample_number| {
+                        (
+                            other_sample_number,
+                            self.get_symmetrized_affinity::<F>(sample_number, other_sample_number),
+                        )
+                    })
+                    .filter(|&(_, affinity)| affinity > F::zero())
+                    .collect::<Vec<(usize, F)>>();
+                most_similar.sort_by(|left, right| {
+                    right
+                        .1
+                        .to_f64()
+                        .unwrap()
+                        .total_cmp(&left.1.to_f64().unwrap())
+                        .then(left.0.cmp(&right.0))
+                });
+                most_similar.truncate(number_of_neighbours);
+                most_similar
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a dense matrix and the same matrix in sparse format.
+    fn get_matrices() -> (Vec<f64>, CSRMatrix<f64>) {
+        let dense = vec![
+            1.0, 0.0, 0.0, 2.0, //
+            0.0, 0.0, 0.0, 0.0, //
+            0.0, 3.0, 0.0, -1.0,
+        ];
+        // The second row is unsorted and has a repeated index.
+        let sparse = CSRMatrix::new(
+            vec![0, 2, 2, 5],
+            vec![0, 3, 3, 1, 3],
+            vec![1.0, 2.0, -0.5, 3.0, -0.5],
+            4,
+        )
+        .unwrap();
+        (dense, sparse)
+    }
+
+    #[test]
+    fn test_sparse_statistics_match_dense_ones() {
+        let (dense, sparse) = get_matrices();
+        let dense = DenseMatrix::new(&dense, 4).unwrap();
+        let (dense_statistics, sparse_statistics) = (
+            dense.get_statistics().unwrap(),
+            sparse.get_statistics().unwrap(),
+        );
+        for (dense, sparse) in [
+            (dense_statistics.get_mean(), sparse_statistics.get_mean()),
+            (
+                dense_statistics.get_variance(),
+                sparse_statistics.get_variance(),
+            ),
+        ] {
+            dense.iter().zip(sparse.iter()).for_each(|(dense, sparse)| {
+                assert!((dense - sparse).abs() < 1e-12, "{:?} {:?}", dense, sparse);
+            });
+        }
+        assert_eq!(sparse.get_indices(), &[0, 3, 1, 3]);
+        assert_eq!(sparse.get_data(), &[1.0, 2.0, 3.0, -1.0]);
+    }
+
+    #[test]
+    fn test_zip_features() {
+        let (dense, sparse) = get_matrices();
+        let dense = DenseMatrix::new(&dense, 4).unwrap();
+        for left in 0..3 {
+            for right in 0..3 {
+                let mut expected = Vec::new();
+                dense.get_sample(left).zip_features(
+                    &dense.get_sample(right),
+                    |index, left, right| {
+                        if left != 0.0 || right != 0.0 {
+                            expected.push((index, left, right));
+                        }
+                    },
+                );
+                for (left_sample, right_sample) in [
+                    (sparse.get_sample(left), sparse.get_sample(right)),
+                    (dense.get_sample(left), sparse.get_sample(right)),
+                    (sparse.get_sample(left), dense.get_sample(right)),
+                ] {
+                    let mut zipped = Vec::new();
+                    left_sample.zip_features(&right_sample, |index, left, right| {
+                        if left != 0.0 || right != 0.0 {
+                            zipped.push((index, left, right));
+                        }
+                    });
+                    assert_eq!(zipped, expected);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_sparse_matrices() {
+        assert!(CSRMatrix::<f32>::new(vec![1, 2], vec![0], vec![1.0], 2).is_err());
+        assert!(CSRMatrix::<f32>::new(vec![0, 2, 1], vec![0, 1], vec![1.0, 1.0], 2).is_err());
+        assert!(CSRMatrix::<f32>::new(vec![0, 1], vec![2], vec![1.0], 2).is_err());
+        assert!(CSRMatrix::<f32>::new(vec![0, 1], vec![0], vec![1.0, 2.0], 2).is_err());
+        assert!(CSRMatrix::<f32>::new(vec![0, 1], vec![0], vec![1.0], 0).is_err());
+    }
+
+    #[test]
+    fn test_precomputed_matrix() {
+        let affinities = vec![
+            1.0, 0.5, 0.0, //
+            0.3, 1.0, 0.0, //
+            0.0, 0.0, 0.0,
+        ];
+        let dense = PrecomputedMatrix::new(
+            DenseMatrix::new(&affinities, 3).unwrap(),
+            PrecomputedKind::Affinity,
+        )
+        .unwrap();
+        let sparse = PrecomputedMatrix::new(
+            CSRMatrix::new(
+                vec![0, 2, 4, 4],
+                vec![0, 1, 0, 1],
+                vec![1.0, 0.5, 0.3, 1.0],
+                3,
+            )
+            .unwrap(),
+            PrecomputedKind::Affinity,
+        )
+        .unwrap();
+        for left in 0..3 {
+            for right in 0..3 {
+                assert_eq!(
+                    dense.get_similarity::<f64>(left, right),
+                    sparse.get_similarity::<f64>(left, right)
+                );
+            }
+        }
+        assert!((dense.get_similarity::<f64>(0, 1) - logit(0.4)).abs() < 1e-12);
+        assert_eq!(dense.get_similarity::<f64>(2, 2), logit(1.0));
+
+        assert!(PrecomputedMatrix::new(
+            DenseMatrix::new(&affinities[..6], 3).unwrap(),
+            PrecomputedKind::Affinity
+        )
+        .is_err());
+        assert!(PrecomputedMatrix::new(
+            DenseMatrix::new(&[1.0, 2.0, 2.0, 1.0], 2).unwrap(),
+            PrecomputedKind::Affinity
+        )
+        .is_err());
+        assert!(PrecomputedMatrix::new(
+            DenseMatrix::new(&[0.0, -1.0, 1.0, 0.0], 2).unwrap(),
+            PrecomputedKind::Distance { bandwidth: 1.0 }
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,190 @@
+use crate::{
+    basic_iterative_decomposition::BasicIterativeDecomposition,
+    errors::DimensionalReductionError,
+    knn::euclidean_distance,
+    matrices::OriginalMatrix,
+    traits::{DimensionalReduction, GenericFeature, IterativeDecomposition},
+    utils::validate_fit_transform_matrix_arguments,
+};
+use num_traits::{AsPrimitive, Float};
+use rayon::prelude::*;
+
+/// Metric Multidimensional Scaling.
+///
+/// The embedding minimizes the raw stress, that is the sum over all of the pairs of
+/// samples of the squared differences of their Euclidean distances in the original space
+/// and in the embedding, with the SMACOF majorization algorithm. Each epoch moves the
+/// embedding towards its Guttman transform by the learning rate, so that the default
+/// learning rate of 1.0 runs the plain SMACOF iterations, which never increase the stress.
+///
+/// The original distances of all of the pairs of samples are computed once and stored,
+/// taking quadratic memory in the number of samples. Each epoch takes quadratic time in
+/// the number of samples, and as it computes the Guttman transform from the embedding
+/// at its start, the results do not depend on the number of threads.
+#[derive(Clone)]
+pub struct MDSDecomposition {
+    decomposition: BasicIterativeDecomposition,
+}
+
+impl MDSDecomposition {
+    /// Returns a new metric MDS model.
+    ///
+    /// # Arguments
+    /// * `decomposition`: The basic iterative decomposition parameters. The learning rate
+    ///   is the fraction of the step towards the Guttman transform taken in each epoch.
+    ///   The optimizer must be SGD, as the Guttman transform is not a gradient step.
+    pub fn new(
+        decomposition: BasicIterativeDecomposition,
+    ) -> Result<Self, DimensionalReductionError> {
+        if decomposition.optimizer.is_stateful() {
+            return Err(DimensionalReductionError::InvalidParameter {
+                parameter: "optimizer",
+                value: format!("{:?}", decomposition.optimizer),
+                expected: "sgd, as MDS moves the embedding towards its Guttman transform",
+            });
+        }
+
+        Ok(Self { decomposition })
+    }
+}
+
+impl IterativeDecomposition for MDSDecomposition {
+    fn get_iterative_basic_decomposition(&self) -> &BasicIterativeDecomposition {
+        &self.decomposition
+    }
+}
+
+impl DimensionalReduction for MDSDecomposition {
+    fn fit_transform_matrix<Original, Target, M>(
+        &self,
+        target: &mut [Target],
+        target_dimension: usize,
+        original: &M,
+    ) -> Result<Vec<f32>, DimensionalReductionError>
+    where
+        Original: AsPrimitive<Target> + GenericFeature + Float,
+        Target: Float + GenericFeature,
+        M: OriginalMatrix<Original>,
+        usize: AsPrimitive<Original> + AsPrimitive<Target>,
+        f32: AsPrimitive<Target>,
+    {
+        validate_fit_transform_matrix_arguments(target, target_dimension, original)?;
+
+        self.initialize_target(target, target_dimension, original)?;
+
+        let number_of_samples = target.len() / target_dimension;
+        // The original distances do not change across the epochs, so we store the
+        // condensed distances of the pairs of samples, sorted by the smaller sample.
+        let original_distance = &euclidean_distance(original);
+        let condensed_distances = (0..number_of_samples)
+            .into_par_iter()
+            .flat_map_iter(|left| {
+                ((left + 1)..number_of_samples).map(move |right| original_distance(left, right))
+            })
+            .collect::<Vec<f64>>();
+        let original_distance = |left: usize, right: usize| {
+            let (left, right) = (left.min(right), left.max(right));
+            condensed_distances[left * (2 * number_of_samples - left - 1) / 2 + right - left - 1]
+        };
+        let number_of_samples_as_target: Target = number_of_samples.as_();
+        let mut guttman_transform = vec![Target::zero(); target.len()];
+
+        self.start_iterations(|_, learning_rate| {
+            let learning_rate: Target = learning_rate.as_();
+            let current_target: &[Target] = target;
+
+            // The Guttman transform of each sample is the average of its differences
+            // from the other samples, weighted by the ratio of their original and
+            // embedded distances. We also compute the stress of the current embedding,
+            // where each pair is counted twice.
+            let losses = guttman_transform
+                .par_chunks_mut(target_dimension)
+                .zip(current_target.par_chunks(target_dimension))
+                .enumerate()
+                .map(|(sample_number, (transformed, point))| {
+                    transformed.iter_mut().for_each(|value| {
+                        *value = Target::zero();
+                    });
+                    let loss = (0..number_of_samples)
+                        .filter(|&other_sample_number| other_sample_number != sample_number)
+                        .fold(0.0, |loss, other_sample_number| {
+                            let other = &current_target[other_sample_number * target_dimension
+                                ..(other_sample_number + 1) * target_dimension];
+                            let distance = point
+                                .iter()
+                                .zip(other.iter())
+                                .map(|(&left, &right)| (left - right) * (left - right))
+                                .fold(Target::zero(), |total, value| total + value)
+                                .sqrt();
+                            let original_distance =
+                                original_distance(sample_number, other_sample_number);
+                            // Coincident embedded samples do not contribute to the transform.
+                            if distance > Target::zero() {
+                                let weight = Target::from(original_distance).unwrap() / distance;
+                                transformed
+                                    .iter_mut()
+                                    .zip(point.iter().zip(other.iter()))
+                                    .for_each(|(value, (&left, &right))| {
+                                        *value += weight * (left - right);
+                                    });
+                            }
+                            loss + (original_distance - distance.to_f64().unwrap()).powi(2)
+                        });
+                    transformed.iter_mut().for_each(|value| {
+                        *value /= number_of_samples_as_target;
+                    });
+                    loss
+                })
+                .collect::<Vec<f64>>();
+
+            target
+                .par_iter_mut()
+                .zip(guttman_transform.par_iter())
+                .for_each(|(value, &transformed)| {
+                    *value += learning_rate * (transformed - *value);
+                });
+
+            Ok((losses.into_iter().sum::<f64>() / 2.0) as f32)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basic_iterative_decomposition::IterativeParameters;
+    use crate::matrices::DenseMatrix;
+    use crate::metrics::raw_stress;
+    use vec_rand::{random_f32, splitmix64};
+
+    #[test]
+    fn test_mds_decreases_stress() {
+        let number_of_samples = 50;
+        let original = (0..(number_of_samples * 4) as u64)
+            .map(|i| random_f32(splitmix64(i)))
+            .collect::<Vec<f32>>();
+        let model = MDSDecomposition::new(
+            BasicIterativeDecomposition::new(
+                "MDS test",
+                IterativeParameters {
+                    iterations: Some(30),
+                    verbose: Some(false),
+                    ..Default::default()
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let mut target = vec![0.0_f32; number_of_samples * 2];
+        let loss_history = model.fit_transform(&mut target, 2, &original, 4).unwrap();
+
+        assert_eq!(loss_history.len(), 30);
+        loss_history.windows(2).for_each(|losses| {
+            assert!(losses[1] <= losses[0] * (1.0 + 1e-5));
+        });
+        // The loss of each epoch is the stress of the embedding at its start.
+        let stress = raw_stress(&DenseMatrix::new(&original, 4).unwrap(), &target, 2).unwrap();
+        assert!(stress as f32 <= loss_history[29] * (1.0 + 1e-5));
+        assert!(stress < loss_history[0] as f64 / 5.0);
+    }
+}
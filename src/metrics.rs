@@ -0,0 +1,721 @@
+use crate::{
+    errors::DimensionalReductionError,
+    knn::{euclidean_distance, exact_nearest_neighbours},
+    matrices::{DenseMatrix, OriginalMatrix},
+    traits::{GenericFeature, MatrixIsFinite},
+    utils::validate_fit_transform_matrix_arguments,
+};
+use num_traits::{AsPrimitive, Float};
+use rayon::prelude::*;
+
+/// Checks that the provided embedding matches the provided original samples,
+/// returning it as a dense matrix.
+fn validate_embedding<'a, Original, Target, M>(
+    original: &M,
+    target: &'a [Target],
+    target_dimension: usize,
+) -> Result<DenseMatrix<'a, Target>, DimensionalReductionError>
+where
+    Original: GenericFeature + Float,
+    Target: GenericFeature + Float,
+    M: OriginalMatrix<Original>,
+    usize: AsPrimitive<Target>,
+{
+    validate_fit_transform_matrix_arguments(target, target_dimension, original)?;
+    target.matrix_is_finite("target")?;
+    DenseMatrix::new(target, target_dimension)
+}
+
+/// Returns the rank of each sample by increasing distance from the provided sample,
+/// starting from one, while the provided sample has rank zero.
+///
+/// Ties between equidistant samples are broken by the sample number,
+/// as in the nearest neighbours search.
+///
+/// # Arguments
+/// * `sample_number`: The sample whose neighbours are ranked.
+/// * `number_of_samples`: The number of samples.
+/// * `distance`: The distance between the two samples with the provided numbers.
+fn get_ranks<D>(sample_number: usize, number_of_samples: usize, distance: &D) -> Vec<usize>
+where
+    D: Fn(usize, usize) -> f64,
+{
+    let mut others = (0..number_of_samples)
+        .filter(|&other| other != sample_number)
+        .map(|other| (distance(sample_number, other), other))
+        .collect::<Vec<(f64, usize)>>();
+    others.sort_unstable_by(|left, right| left.0.total_cmp(&right.0).then(left.1.cmp(&right.1)));
+    let mut ranks = vec![0; number_of_samples];
+    others
+        .into_iter()
+        .enumerate()
+        .for_each(|(rank, (_, other))| {
+            ranks[other] = rank + 1;
+        });
+    ranks
+}
+
+/// Returns the sums of the rank penalties of trustworthiness and continuity,
+/// normalized so that they are zero for a perfect embedding and one at worst.
+///
+/// # Arguments
+/// * `original`: The original samples.
+/// * `target`: The embedding of the original samples.
+/// * `target_dimension`: The dimensionality of the embedding.
+/// * `number_of_neighbours`: The size of the neighbourhoods that are compared.
+fn get_rank_penalties<Original, Target, M>(
+    original: &M,
+    target: &[Target],
+    target_dimension: usize,
+    number_of_neighbours: usize,
+) -> Result<(f64, f64), DimensionalReductionError>
+where
+    Original: GenericFeature + Float,
+    Target: GenericFeature + Float,
+    M: OriginalMatrix<Original>,
+    usize: AsPrimitive<Target>,
+{
+    let embedding = validate_embedding(original, target, target_dimension)?;
+    let number_of_samples = original.get_number_of_samples();
+
+    if number_of_neighbours == 0 || 3 * number_of_neighbours + 1 >= 2 * number_of_samples {
+        return Err(DimensionalReductionError::InvalidParameter {
+            parameter: "number of neighbours",
+            value: number_of_neighbours.to_string(),
+            expected: "strictly positive and smaller than (2 * number of samples - 1) / 3",
+        });
+    }
+
+    let (original_distance, target_distance) =
+        (euclidean_distance(original), euclidean_distance(&embedding));
+
+    let (trustworthiness_penalty, continuity_penalty) = (0..number_of_samples)
+        .into_par_iter()
+        .map(|sample_number| {
+            let original_ranks = get_ranks(sample_number, number_of_samples, &original_distance);
+            let target_ranks = get_ranks(sample_number, number_of_samples, &target_distance);
+            original_ranks.iter().zip(target_ranks.iter()).fold(
+                (0, 0),
+                |(trustworthiness_penalty, continuity_penalty), (&original_rank, &target_rank)| {
+                    (
+                        // Samples close in the embedding but far in the original space.
+                        trustworthiness_penalty
+                            + if target_rank > 0 && target_rank <= number_of_neighbours {
+                                original_rank.saturating_sub(number_of_neighbours)
+                            } else {
+                                0
+                            },
+                        // Samples close in the original space but far in the embedding.
+                        continuity_penalty
+                            + if original_rank > 0 && original_rank <= number_of_neighbours {
+                                target_rank.saturating_sub(number_of_neighbours)
+                            } else {
+                                0
+                            },
+                    )
+                },
+            )
+        })
+        .reduce(
+            || (0, 0),
+            |(left_trustworthiness, left_continuity), (right_trustworthiness, right_continuity)| {
+                (
+                    left_trustworthiness + right_trustworthiness,
+                    left_continuity + right_continuity,
+                )
+            },
+        );
+
+    let normalization = 2.0
+        / (number_of_samples
+            * number_of_neighbours
+            * (2 * number_of_samples - 3 * number_of_neighbours - 1)) as f64;
+
+    Ok((
+        trustworthiness_penalty as f64 * normalization,
+        continuity_penalty as f64 * normalization,
+    ))
+}
+
+/// Returns the trustworthiness of the embedding, that is how much the nearest
+/// neighbours in the embedding are also near in the original space.
+///
+/// The trustworthiness is one when the neighbourhoods are preserved, and decreases
+/// with the original rank of the samples that intrude the neighbourhoods of the embedding.
+/// The time complexity is quadratic in the number of samples.
+///
+/// # Arguments
+/// * `original`: The original samples.
+/// * `target`: The embedding of the original samples.
+/// * `target_dimension`: The dimensionality of the embedding.
+/// * `number_of_neighbours`: The size of the neighbourhoods that are compared.
+pub fn trustworthiness<Original, Target, M>(
+    original: &M,
+    target: &[Target],
+    target_dimension: usize,
+    number_of_neighbours: usize,
+) -> Result<f64, DimensionalReductionError>
+where
+    Original: GenericFeature + Float,
+    Target: GenericFeature + Float,
+    M: OriginalMatrix<Original>,
+    usize: AsPrimitive<Target>,
+{
+    let (trustworthiness_penalty, _) =
+        get_rank_penalties(original, target, target_dimension, number_of_neighbours)?;
+    Ok(1.0 - trustworthiness_penalty)
+}
+
+/// Returns the continuity of the embedding, that is how much the nearest
+/// neighbours in the original space are also near in the embedding.
+///
+/// The continuity is one when the neighbourhoods are preserved, and decreases
+/// with the rank in the embedding of the samples missing from its neighbourhoods.
+/// The time complexity is quadratic in the number of samples.
+///
+/// # Arguments
+/// * `original`: The original samples.
+/// * `target`: The embedding of the original samples.
+/// * `target_dimension`: The dimensionality of the embedding.
+/// * `number_of_neighbours`: The size of the neighbourhoods that are compared.
+pub fn continuity<Original, Target, M>(
+    original: &M,
+    target: &[Target],
+    target_dimension: usize,
+    number_of_neighbours: usize,
+) -> Result<f64, DimensionalReductionError>
+where
+    Original: GenericFeature + Float,
+    Target: GenericFeature + Float,
+    M: OriginalMatrix<Original>,
+    usize: AsPrimitive<Target>,
+{
+    let (_, continuity_penalty) =
+        get_rank_penalties(original, target, target_dimension, number_of_neighbours)?;
+    Ok(1.0 - continuity_penalty)
+}
+
+/// Returns the mean fraction of the nearest neighbours of each sample
+/// in the original space that are also its nearest neighbours in the embedding.
+///
+/// # Arguments
+/// * `original`: The original samples.
+/// * `target`: The embedding of the original samples.
+/// * `target_dimension`: The dimensionality of the embedding.
+/// * `number_of_neighbours`: The size of the neighbourhoods that are compared.
+pub fn knn_recall<Original, Target, M>(
+    original: &M,
+    target: &[Target],
+    target_dimension: usize,
+    number_of_neighbours: usize,
+) -> Result<f64, DimensionalReductionError>
+where
+    Original: GenericFeature + Float,
+    Target: GenericFeature + Float,
+    M: OriginalMatrix<Original>,
+    usize: AsPrimitive<Target>,
+{
+    let embedding = validate_embedding(original, target, target_dimension)?;
+    if number_of_neighbours == 0 {
+        return Err(DimensionalReductionError::InvalidParameter {
+            parameter: "number of neighbours",
+            value: number_of_neighbours.to_string(),
+            expected: "strictly positive",
+        });
+    }
+
+    let original_neighbours = exact_nearest_neighbours(original, number_of_neighbours)?;
+    let target_neighbours = exact_nearest_neighbours(&embedding, number_of_neighbours)?;
+
+    let number_of_samples = original.get_number_of_samples();
+    let preserved_neighbours = (0..number_of_samples)
+        .into_par_iter()
+        .map(|sample_number| {
+            let target_neighbours = target_neighbours.get_neighbours(sample_number);
+            original_neighbours
+                .get_neighbours(sample_number)
+                .iter()
+                .filter(|neighbour| target_neighbours.contains(neighbour))
+                .count()
+        })
+        .sum::<usize>();
+
+    Ok(preserved_neighbours as f64 / (number_of_samples * number_of_neighbours) as f64)
+}
+
+/// Co-ranking matrix of an embedding, counting the pairs of samples by
+/// their rank in the original space and their rank in the embedding.
+///
+/// The element in the row k and column l, starting from one, is the number of pairs
+/// where the second sample is the k-th nearest neighbour of the first sample in the
+/// original space and its l-th nearest neighbour in the embedding.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CoRankingMatrix {
+    number_of_samples: usize,
+    maximum_rank: usize,
+    counts: Vec<usize>,
+}
+
+impl CoRankingMatrix {
+    /// Returns the co-ranking matrix of the provided embedding.
+    ///
+    /// The time complexity is quadratic in the number of samples, and the memory
+    /// complexity is quadratic in the maximum rank plus linear in the number of samples.
+    ///
+    /// # Arguments
+    /// * `original`: The original samples.
+    /// * `target`: The embedding of the original samples.
+    /// * `target_dimension`: The dimensionality of the embedding.
+    /// * `maximum_rank`: The largest rank counted by the matrix. By default, all of them.
+    pub fn new<Original, Target, M>(
+        original: &M,
+        target: &[Target],
+        target_dimension: usize,
+        maximum_rank: Option<usize>,
+    ) -> Result<Self, DimensionalReductionError>
+    where
+        Original: GenericFeature + Float,
+        Target: GenericFeature + Float,
+        M: OriginalMatrix<Original>,
+        usize: AsPrimitive<Target>,
+    {
+        let embedding = validate_embedding(original, target, target_dimension)?;
+        let number_of_samples = original.get_number_of_samples();
+
+        let maximum_rank = maximum_rank.unwrap_or(number_of_samples - 1);
+        if maximum_rank == 0 || maximum_rank >= number_of_samples {
+            return Err(DimensionalReductionError::InvalidParameter {
+                parameter: "maximum rank",
+                value: maximum_rank.to_string(),
+                expected: "strictly positive and smaller than the number of samples",
+            });
+        }
+
+        let (original_distance, target_distance) =
+            (euclidean_distance(original), euclidean_distance(&embedding));
+
+        // Each chunk of samples is ranked in parallel, and the cells of its pairs are
+        // then counted in the single matrix, so that the memory does not grow with the
+        // number of threads times the square of the maximum rank.
+        let mut counts = vec![0; maximum_rank * maximum_rank];
+        let chunk_size = rayon::current_num_threads() * 4;
+        (0..number_of_samples)
+            .step_by(chunk_size)
+            .for_each(|chunk_start| {
+                (chunk_start..(chunk_start + chunk_size).min(number_of_samples))
+                    .into_par_iter()
+                    .map(|sample_number| {
+                        let original_ranks =
+                            get_ranks(sample_number, number_of_samples, &original_distance);
+                        let target_ranks =
+                            get_ranks(sample_number, number_of_samples, &target_distance);
+                        original_ranks
+                            .into_iter()
+                            .zip(target_ranks)
+                            .filter(|&(original_rank, target_rank)| {
+                                original_rank > 0
+                                    && original_rank <= maximum_rank
+                                    && target_rank <= maximum_rank
+                            })
+                            .map(|(original_rank, target_rank)| {
+                                (original_rank - 1) * maximum_rank + target_rank - 1
+                            })
+                            .collect::<Vec<usize>>()
+                    })
+                    .collect::<Vec<Vec<usize>>>()
+                    .into_iter()
+                    .flatten()
+                    .for_each(|cell| {
+                        counts[cell] += 1;
+                    });
+            });
+
+        Ok(Self {
+            number_of_samples,
+            maximum_rank,
+            counts,
+        })
+    }
+
+    /// Returns the largest rank counted by the matrix.
+    pub fn get_maximum_rank(&self) -> usize {
+        self.maximum_rank
+    }
+
+    /// Returns the counts of the matrix, stored by rows.
+    pub fn get_counts(&self) -> &[usize] {
+        &self.counts
+    }
+
+    /// Returns the number of pairs with the provided ranks, starting from one.
+    ///
+    /// # Arguments
+    /// * `original_rank`: The rank in the original space.
+    /// * `target_rank`: The rank in the embedding.
+    pub fn get_count(&self, original_rank: usize, target_rank: usize) -> usize {
+        self.counts[(original_rank - 1) * self.maximum_rank + target_rank - 1]
+    }
+
+    /// Returns the quality Q_NX of the embedding for each neighbourhood size from one
+    /// to the maximum rank, that is the mean fraction of the neighbours in the original
+    /// space that are also neighbours in the embedding.
+    pub fn get_quality_curve(&self) -> Vec<f64> {
+        let mut preserved_neighbours = 0;
+        (1..=self.maximum_rank)
+            .map(|rank| {
+                // We add the last row and column of the upper left block.
+                preserved_neighbours += (1..=rank)
+                    .map(|other_rank| self.get_count(rank, other_rank))
+                    .sum::<usize>()
+                    + (1..rank)
+                        .map(|other_rank| self.get_count(other_rank, rank))
+                        .sum::<usize>();
+                preserved_neighbours as f64 / (rank * self.number_of_samples) as f64
+            })
+            .collect()
+    }
+
+    /// Returns the local continuity meta-criterion LCMC of the embedding for each
+    /// neighbourhood size from one to the maximum rank, that is the quality Q_NX
+    /// minus its expected value for a random embedding.
+    pub fn get_local_continuity_curve(&self) -> Vec<f64> {
+        self.get_quality_curve()
+            .into_iter()
+            .enumerate()
+            .map(|(rank, quality)| {
+                quality - (rank + 1) as f64 / (self.number_of_samples - 1) as f64
+            })
+            .collect()
+    }
+}
+
+/// Returns the ranks of the provided values, starting from one,
+/// where tied values have the mean of their ranks.
+fn get_fractional_ranks(values: &[f64]) -> Vec<f64> {
+    let mut order = (0..values.len()).collect::<Vec<usize>>();
+    order.par_sort_unstable_by(|&left, &right| values[left].total_cmp(&values[right]));
+    let mut ranks = vec![0.0; values.len()];
+    let mut start = 0;
+    while start < order.len() {
+        let mut end = start + 1;
+        while end < order.len() && values[order[end]] == values[order[start]] {
+            end += 1;
+        }
+        let rank = (start + end + 1) as f64 / 2.0;
+        order[start..end].iter().for_each(|&position| {
+            ranks[position] = rank;
+        });
+        start = end;
+    }
+    ranks
+}
+
+/// Returns the Spearman correlation between the distances of all of the pairs
+/// of samples in the original space and in the embedding.
+///
+/// The time and memory complexities are quadratic in the number of samples.
+/// The correlation is not a number when all of the distances in either space are equal.
+///
+/// # Arguments
+/// * `original`: The original samples.
+/// * `target`: The embedding of the original samples.
+/// * `target_dimension`: The dimensionality of the embedding.
+pub fn spearman_correlation<Original, Target, M>(
+    original: &M,
+    target: &[Target],
+    target_dimension: usize,
+) -> Result<f64, DimensionalReductionError>
+where
+    Original: GenericFeature + Float,
+    Target: GenericFeature + Float,
+    M: OriginalMatrix<Original>,
+    usize: AsPrimitive<Target>,
+{
+    let embedding = validate_embedding(original, target, target_dimension)?;
+    let number_of_samples = original.get_number_of_samples();
+    if number_of_samples < 3 {
+        return Err(DimensionalReductionError::InvalidParameter {
+            parameter: "number of samples",
+            value: number_of_samples.to_string(),
+            expected: "at least three",
+        });
+    }
+
+    let get_distances = |distance: &(dyn Fn(usize, usize) -> f64 + Sync)| {
+        (0..number_of_samples)
+            .into_par_iter()
+            .flat_map_iter(|left| {
+                ((left + 1)..number_of_samples).map(move |right| distance(left, right))
+            })
+            .collect::<Vec<f64>>()
+    };
+    let original_ranks = get_fractional_ranks(&get_distances(&euclidean_distance(original)));
+    let target_ranks = get_fractional_ranks(&get_distances(&euclidean_distance(&embedding)));
+
+    // Both ranks have the same mean, since they are a permutation of the same ranks
+    // when there are no ties, and the mean is preserved by the fractional ranks.
+    let mean = (original_ranks.len() + 1) as f64 / 2.0;
+    let (covariance, original_variance, target_variance) = original_ranks
+        .par_iter()
+        .zip(target_ranks.par_iter())
+        .map(|(original_rank, target_rank)| {
+            let (original_rank, target_rank) = (original_rank - mean, target_rank - mean);
+            (
+                original_rank * target_rank,
+                original_rank * original_rank,
+                target_rank * target_rank,
+            )
+        })
+        .reduce(
+            || (0.0, 0.0, 0.0),
+            |left, right| (left.0 + right.0, left.1 + right.1, left.2 + right.2),
+        );
+
+    Ok(covariance / (original_variance * target_variance).sqrt())
+}
+
+/// Sums over all of the pairs of samples of the terms of the stress measures.
+#[derive(Clone, Copy, Default)]
+struct StressSums {
+    /// Sum of the squared differences of the original and embedded distances.
+    squared_errors: f64,
+    /// Sum of the squared original distances.
+    squared_original_distances: f64,
+    /// Sum of the squared differences divided by the original distances.
+    relative_squared_errors: f64,
+    /// Sum of the original distances.
+    original_distances: f64,
+}
+
+impl StressSums {
+    fn add(self, other: Self) -> Self {
+        Self {
+            squared_errors: self.squared_errors + other.squared_errors,
+            squared_original_distances: self.squared_original_distances
+                + other.squared_original_distances,
+            relative_squared_errors: self.relative_squared_errors + other.relative_squared_errors,
+            original_distances: self.original_distances + other.original_distances,
+        }
+    }
+}
+
+/// Returns the sums of the terms of the stress measures of the embedding.
+///
+/// # Arguments
+/// * `original`: The original samples.
+/// * `target`: The embedding of the original samples.
+/// * `target_dimension`: The dimensionality of the embedding.
+fn get_stress_sums<Original, Target, M>(
+    original: &M,
+    target: &[Target],
+    target_dimension: usize,
+) -> Result<StressSums, DimensionalReductionError>
+where
+    Original: GenericFeature + Float,
+    Target: GenericFeature + Float,
+    M: OriginalMatrix<Original>,
+    usize: AsPrimitive<Target>,
+{
+    let embedding = validate_embedding(original, target, target_dimension)?;
+    let number_of_samples = original.get_number_of_samples();
+    let (original_distance, target_distance) =
+        (euclidean_distance(original), euclidean_distance(&embedding));
+
+    Ok((0..number_of_samples)
+        .into_par_iter()
+        .map(|left| {
+            ((left + 1)..number_of_samples).fold(StressSums::default(), |sums, right| {
+                let original_distance = original_distance(left, right);
+                let squared_error = (original_distance - target_distance(left, right)).powi(2);
+                sums.add(StressSums {
+                    squared_errors: squared_error,
+                    squared_original_distances: original_distance * original_distance,
+                    // Coincident original samples are skipped by the Sammon stress.
+                    relative_squared_errors: if original_distance > 0.0 {
+                        squared_error / original_distance
+                    } else {
+                        0.0
+                    },
+                    original_distances: original_distance,
+                })
+            })
+        })
+        .reduce(StressSums::default, StressSums::add))
+}
+
+/// Returns the raw stress of the embedding, that is the sum over all of the pairs
+/// of samples of the squared differences of their original and embedded distances.
+///
+/// The time complexity is quadratic in the number of samples.
+///
+/// # Arguments
+/// * `original`: The original samples.
+/// * `target`: The embedding of the original samples.
+/// * `target_dimension`: The dimensionality of the embedding.
+pub fn raw_stress<Original, Target, M>(
+    original: &M,
+    target: &[Target],
+    target_dimension: usize,
+) -> Result<f64, DimensionalReductionError>
+where
+    Original: GenericFeature + Float,
+    Target: GenericFeature + Float,
+    M: OriginalMatrix<Original>,
+    usize: AsPrimitive<Target>,
+{
+    Ok(get_stress_sums(original, target, target_dimension)?.squared_errors)
+}
+
+/// Returns the normalized stress of the embedding, that is the square root of
+/// the raw stress divided by the sum of the squared original distances.
+///
+/// The normalized stress is zero when the distances are preserved, and does not
+/// depend on the scale of the samples. It is not a number when all of the original
+/// samples coincide. The time complexity is quadratic in the number of samples.
+///
+/// # Arguments
+/// * `original`: The original samples.
+/// * `target`: The embedding of the original samples.
+/// * `target_dimension`: The dimensionality of the embedding.
+pub fn normalized_stress<Original, Target, M>(
+    original: &M,
+    target: &[Target],
+    target_dimension: usize,
+) -> Result<f64, DimensionalReductionError>
+where
+    Original: GenericFeature + Float,
+    Target: GenericFeature + Float,
+    M: OriginalMatrix<Original>,
+    usize: AsPrimitive<Target>,
+{
+    let sums = get_stress_sums(original, target, target_dimension)?;
+    Ok((sums.squared_errors / sums.squared_original_distances).sqrt())
+}
+
+/// Returns the Sammon stress of the embedding, where the squared difference of the
+/// distances of each pair of samples is divided by their original distance, so that
+/// the small distances weigh more than in the raw stress.
+///
+/// The pairs of coincident original samples are skipped, and the stress is not a number
+/// when all of the original samples coincide. The time complexity is quadratic in the
+/// number of samples.
+///
+/// # Arguments
+/// * `original`: The original samples.
+/// * `target`: The embedding of the original samples.
+/// * `target_dimension`: The dimensionality of the embedding.
+pub fn sammon_stress<Original, Target, M>(
+    original: &M,
+    target: &[Target],
+    target_dimension: usize,
+) -> Result<f64, DimensionalReductionError>
+where
+    Original: GenericFeature + Float,
+    Target: GenericFeature + Float,
+    M: OriginalMatrix<Original>,
+    usize: AsPrimitive<Target>,
+{
+    let sums = get_stress_sums(original, target, target_dimension)?;
+    Ok(sums.relative_squared_errors / sums.original_distances)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vec_rand::{random_f32, splitmix64};
+
+    const NUMBER_OF_SAMPLES: usize = 100;
+    const DIMENSION: usize = 3;
+
+    fn get_samples() -> Vec<f32> {
+        (0..(NUMBER_OF_SAMPLES * DIMENSION) as u64)
+            .map(|i| random_f32(splitmix64(i)))
+            .collect()
+    }
+
+    #[test]
+    fn test_identical_embedding() {
+        let samples = get_samples();
+        let original = DenseMatrix::new(&samples, DIMENSION).unwrap();
+        assert!((trustworthiness(&original, &samples, DIMENSION, 5).unwrap() - 1.0).abs() < 1e-12);
+        assert!((continuity(&original, &samples, DIMENSION, 5).unwrap() - 1.0).abs() < 1e-12);
+        assert_eq!(knn_recall(&original, &samples, DIMENSION, 5).unwrap(), 1.0);
+        assert!(
+            (spearman_correlation(&original, &samples, DIMENSION).unwrap() - 1.0).abs() < 1e-12
+        );
+
+        let co_ranking = CoRankingMatrix::new(&original, &samples, DIMENSION, Some(10)).unwrap();
+        (1..=10).for_each(|rank| {
+            assert_eq!(co_ranking.get_count(rank, rank), NUMBER_OF_SAMPLES);
+        });
+        co_ranking
+            .get_quality_curve()
+            .into_iter()
+            .for_each(|quality| assert!((quality - 1.0).abs() < 1e-12));
+    }
+
+    #[test]
+    fn test_random_embedding() {
+        let samples = get_samples();
+        let original = DenseMatrix::new(&samples, DIMENSION).unwrap();
+        let target = (0..(NUMBER_OF_SAMPLES * 2) as u64)
+            .map(|i| random_f32(splitmix64(i + 1_000_000)))
+            .collect::<Vec<f32>>();
+        assert!(trustworthiness(&original, &target, 2, 5).unwrap() < 0.8);
+        assert!(continuity(&original, &target, 2, 5).unwrap() < 0.8);
+        assert!(knn_recall(&original, &target, 2, 5).unwrap() < 0.3);
+        assert!(spearman_correlation(&original, &target, 2).unwrap().abs() < 0.2);
+
+        let co_ranking = CoRankingMatrix::new(&original, &target, 2, None).unwrap();
+        assert_eq!(
+            co_ranking.get_counts().iter().sum::<usize>(),
+            NUMBER_OF_SAMPLES * (NUMBER_OF_SAMPLES - 1)
+        );
+        // The chunks of samples are counted in the same matrix with any number of threads.
+        let single_threaded_co_ranking = rayon::ThreadPoolBuilder::new()
+            .num_threads(1)
+            .build()
+            .unwrap()
+            .install(|| CoRankingMatrix::new(&original, &target, 2, None).unwrap());
+        assert_eq!(
+            single_threaded_co_ranking.get_counts(),
+            co_ranking.get_counts()
+        );
+        let quality_curve = co_ranking.get_quality_curve();
+        assert!((quality_curve[NUMBER_OF_SAMPLES - 2] - 1.0).abs() < 1e-12);
+        co_ranking
+            .get_local_continuity_curve()
+            .into_iter()
+            .for_each(|local_continuity| assert!(local_continuity.abs() < 0.2));
+
+        assert!(trustworthiness(&original, &target, 2, 0).is_err());
+        assert!(trustworthiness(&original, &target, 2, NUMBER_OF_SAMPLES).is_err());
+        assert!(CoRankingMatrix::new(&original, &target, 2, Some(NUMBER_OF_SAMPLES)).is_err());
+        assert!(knn_recall(&original, &target[..10], 2, 5).is_err());
+    }
+
+    #[test]
+    fn test_stress() {
+        let samples = get_samples();
+        let original = DenseMatrix::new(&samples, DIMENSION).unwrap();
+        assert!(raw_stress(&original, &samples, DIMENSION).unwrap() < 1e-8);
+        assert!(normalized_stress(&original, &samples, DIMENSION).unwrap() < 1e-5);
+        assert!(sammon_stress(&original, &samples, DIMENSION).unwrap() < 1e-5);
+
+        // Doubling the distances makes each error equal to the original distance.
+        let doubled = samples
+            .iter()
+            .map(|value| value * 2.0)
+            .collect::<Vec<f32>>();
+        let sums = get_stress_sums(&original, &samples, DIMENSION).unwrap();
+        assert!(
+            (raw_stress(&original, &doubled, DIMENSION).unwrap() - sums.squared_original_distances)
+                .abs()
+                < 1e-6 * sums.squared_original_distances
+        );
+        assert!((normalized_stress(&original, &doubled, DIMENSION).unwrap() - 1.0).abs() < 1e-6);
+        assert!((sammon_stress(&original, &doubled, DIMENSION).unwrap() - 1.0).abs() < 1e-6);
+
+        assert!(raw_stress(&original, &samples[..10], DIMENSION).is_err());
+    }
+}
@@ -0,0 +1,264 @@
+use crate::{errors::DimensionalReductionError, traits::GenericFeature};
+use num_traits::Float;
+use rayon::prelude::*;
+
+/// Rule used to update the embedding of the iterative decompositions
+/// from the descent direction computed in each epoch.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Optimizer {
+    /// Steps of the learning rate along the descent direction, applied
+    /// as soon as each pair of samples is processed unless deterministic.
+    #[default]
+    SGD,
+    /// Steps accumulating the previous ones, decayed by the provided momentum.
+    Momentum { momentum: f32 },
+    /// Steps normalized by the moving averages of the first and second moments
+    /// of the descent direction of each coordinate.
+    Adam {
+        beta_1: f32,
+        beta_2: f32,
+        epsilon: f32,
+    },
+    /// Steps normalized by the root of the sum of the squared descent
+    /// directions of each coordinate seen so far.
+    AdaGrad { epsilon: f32 },
+}
+
+/// Checks that the provided decay rate is in the range [0, 1).
+fn validate_decay(
+    parameter: &'static str,
+    value: Option<f32>,
+    default: f32,
+) -> Result<f32, DimensionalReductionError> {
+    let value = value.unwrap_or(default);
+    if !(0.0..1.0).contains(&value) {
+        return Err(DimensionalReductionError::InvalidParameter {
+            parameter,
+            value: value.to_string(),
+            expected: "in the range [0, 1)",
+        });
+    }
+    Ok(value)
+}
+
+impl Optimizer {
+    /// Returns the optimizer with the provided name.
+    ///
+    /// # Arguments
+    /// * `name`: The name of the optimizer, one of `sgd`, `momentum`, `adam` and `adagrad`.
+    /// * `momentum`: The momentum of the `momentum` optimizer. By default, 0.9.
+    /// * `beta_1`: The decay of the first moment of the `adam` optimizer. By default, 0.9.
+    /// * `beta_2`: The decay of the second moment of the `adam` optimizer. By default, 0.999.
+    /// * `epsilon`: The value added to the denominators of the `adam` and `adagrad`
+    ///   optimizers for numerical stability. By default, 1e-8.
+    pub fn from_name(
+        name: &str,
+        momentum: Option<f32>,
+        beta_1: Option<f32>,
+        beta_2: Option<f32>,
+        epsilon: Option<f32>,
+    ) -> Result<Self, DimensionalReductionError> {
+        let epsilon = epsilon.unwrap_or(1e-8);
+        if !epsilon.is_finite() || epsilon <= 0.0 {
+            return Err(DimensionalReductionError::InvalidParameter {
+                parameter: "epsilon",
+                value: epsilon.to_string(),
+                expected: "a finite strictly positive value",
+            });
+        }
+
+        Ok(match name {
+            "sgd" => Self::SGD,
+            "momentum" => Self::Momentum {
+                momentum: validate_decay("momentum", momentum, 0.9)?,
+            },
+            "adam" => Self::Adam {
+                beta_1: validate_decay("beta 1", beta_1, 0.9)?,
+                beta_2: validate_decay("beta 2", beta_2, 0.999)?,
+                epsilon,
+            },
+            "adagrad" => Self::AdaGrad { epsilon },
+            name => {
+                return Err(DimensionalReductionError::InvalidParameter {
+                    parameter: "optimizer",
+                    value: name.to_string(),
+                    expected: "one of sgd, momentum, adam and adagrad",
+                })
+            }
+        })
+    }
+
+    /// Returns whether the optimizer keeps a state for each coordinate, and therefore
+    /// requires the descent direction of each epoch to be accumulated before the update.
+    pub fn is_stateful(&self) -> bool {
+        !matches!(self, Self::SGD)
+    }
+}
+
+/// State of an optimizer for each coordinate of the embedding.
+pub struct OptimizerState<F> {
+    optimizer: Optimizer,
+    first_moments: Vec<F>,
+    second_moments: Vec<F>,
+    step: i32,
+}
+
+impl<F> OptimizerState<F>
+where
+    F: GenericFeature + Float,
+{
+    /// Returns the initial state of the provided optimizer.
+    ///
+    /// # Arguments
+    /// * `optimizer`: The optimizer.
+    /// * `length`: The number of coordinates of the embedding.
+    pub fn new(optimizer: Optimizer, length: usize) -> Self {
+        let (first_moments, second_moments) = match optimizer {
+            Optimizer::SGD => (Vec::new(), Vec::new()),
+            Optimizer::Momentum { .. } => (vec![F::zero(); length], Vec::new()),
+            Optimizer::Adam { .. } => (vec![F::zero(); length], vec![F::zero(); length]),
+            Optimizer::AdaGrad { .. } => (Vec::new(), vec![F::zero(); length]),
+        };
+        Self {
+            optimizer,
+            first_moments,
+            second_moments,
+            step: 0,
+        }
+    }
+
+    /// Updates the embedding with the provided descent directions, that is
+    /// the opposite of the gradient of the loss of the epoch.
+    ///
+    /// # Arguments
+    /// * `target`: The embedding to update.
+    /// * `descent_directions`: The descent direction of each coordinate of the embedding.
+    /// * `learning_rate`: The learning rate of the epoch.
+    pub fn update(&mut self, target: &mut [F], descent_directions: &[F], learning_rate: f32) {
+        self.step += 1;
+        let learning_rate = F::from(learning_rate).unwrap();
+        match self.optimizer {
+            Optimizer::SGD => {
+                target
+                    .par_iter_mut()
+                    .zip(descent_directions.par_iter())
+                    .for_each(|(value, &direction)| {
+                        *value += learning_rate * direction;
+                    });
+            }
+            Optimizer::Momentum { momentum } => {
+                let momentum = F::from(momentum).unwrap();
+                target
+                    .par_iter_mut()
+                    .zip(self.first_moments.par_iter_mut())
+                    .zip(descent_directions.par_iter())
+                    .for_each(|((value, velocity), &direction)| {
+                        *velocity = momentum * *velocity + learning_rate * direction;
+                        *value += *velocity;
+                    });
+            }
+            Optimizer::Adam {
+                beta_1,
+                beta_2,
+                epsilon,
+            } => {
+                let (beta_1, beta_2, epsilon) = (
+                    F::from(beta_1).unwrap(),
+                    F::from(beta_2).unwrap(),
+                    F::from(epsilon).unwrap(),
+                );
+                // The moments start from zero, so they are corrected for their bias.
+                let first_correction = F::one() - beta_1.powi(self.step);
+                let second_correction = F::one() - beta_2.powi(self.step);
+                target
+                    .par_iter_mut()
+                    .zip(
+                        self.first_moments
+                            .par_iter_mut()
+                            .zip(self.second_moments.par_iter_mut()),
+                    )
+                    .zip(descent_directions.par_iter())
+                    .for_each(|((value, (first_moment, second_moment)), &direction)| {
+                        *first_moment = beta_1 * *first_moment + (F::one() - beta_1) * direction;
+                        *second_moment =
+                            beta_2 * *second_moment + (F::one() - beta_2) * direction * direction;
+                        *value += learning_rate * (*first_moment / first_correction)
+                            / ((*second_moment / second_correction).sqrt() + epsilon);
+                    });
+            }
+            Optimizer::AdaGrad { epsilon } => {
+                let epsilon = F::from(epsilon).unwrap();
+                target
+                    .par_iter_mut()
+                    .zip(self.second_moments.par_iter_mut())
+                    .zip(descent_directions.par_iter())
+                    .for_each(|((value, accumulator), &direction)| {
+                        *accumulator += direction * direction;
+                        *value += learning_rate * direction / (accumulator.sqrt() + epsilon);
+                    });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_optimizers_minimize_badly_scaled_quadratic() {
+        // The gradient of the sum of `scale * x^2` is `2 * scale * x`.
+        let scales = [1e-3_f64, 1.0, 1e3];
+        for (optimizer, learning_rate) in [
+            (
+                Optimizer::from_name("momentum", None, None, None, None).unwrap(),
+                1e-4,
+            ),
+            (
+                Optimizer::from_name("adam", None, None, None, None).unwrap(),
+                0.1,
+            ),
+            (
+                Optimizer::from_name("adagrad", None, None, None, None).unwrap(),
+                0.5,
+            ),
+        ] {
+            let mut state = OptimizerState::new(optimizer, scales.len());
+            let mut target = vec![1.0_f64; scales.len()];
+            for _ in 0..2000 {
+                let descent_directions = target
+                    .iter()
+                    .zip(scales.iter())
+                    .map(|(&value, &scale)| -2.0 * scale * value)
+                    .collect::<Vec<f64>>();
+                state.update(&mut target, &descent_directions, learning_rate);
+            }
+            assert!(
+                target.iter().all(|value| value.is_finite()),
+                "{:?}",
+                optimizer
+            );
+            // Only the adaptive optimizers make progress on the flattest coordinate.
+            if optimizer.is_stateful() && !matches!(optimizer, Optimizer::Momentum { .. }) {
+                assert!(
+                    target.iter().all(|value| value.abs() < 0.5),
+                    "{:?}",
+                    optimizer
+                );
+            }
+            assert!(
+                target[1].abs() < 0.9 && target[2].abs() < 1e-2,
+                "{:?}",
+                optimizer
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_optimizer_parameters() {
+        assert!(Optimizer::from_name("rmsprop", None, None, None, None).is_err());
+        assert!(Optimizer::from_name("momentum", Some(1.0), None, None, None).is_err());
+        assert!(Optimizer::from_name("adam", None, Some(-0.1), None, None).is_err());
+        assert!(Optimizer::from_name("adagrad", None, None, None, Some(0.0)).is_err());
+    }
+}
@@ -0,0 +1,472 @@
+use crate::{
+    basic_decomposition::BasicDecomposition,
+    errors::DimensionalReductionError,
+    matrices::{DenseMatrix, OriginalMatrix, Sample},
+    traits::{Decomposition, DimensionalReduction, GenericFeature},
+    utils::{deterministic_partitioned_sum, dot, validate_fit_transform_matrix_arguments},
+};
+use num_traits::{AsPrimitive, Float};
+use rayon::prelude::*;
+use vec_rand::{random_f32, splitmix64};
+
+/// The principal components of a set of original samples.
+#[derive(Clone)]
+pub struct PrincipalComponents<Original, Target> {
+    mean: Vec<Original>,
+    components: Vec<Target>,
+    explained_variance: Vec<Target>,
+    explained_variance_ratio: Vec<Target>,
+    original_dimension: usize,
+}
+
+impl<Original, Target> PrincipalComponents<Original, Target>
+where
+    Original: AsPrimitive<Target> + GenericFeature,
+    Target: Float + GenericFeature,
+{
+    /// Returns the mean of the original samples, used to center them.
+    pub fn get_mean(&self) -> &[Original] {
+        &self.mean
+    }
+
+    /// Returns the row-major matrix of the principal components,
+    /// with a row for each component sorted by decreasing variance.
+    pub fn get_components(&self) -> &[Target] {
+        &self.components
+    }
+
+    /// Returns the variance explained by each of the principal components.
+    pub fn get_explained_variance(&self) -> &[Target] {
+        &self.explained_variance
+    }
+
+    /// Returns the ratio of the total variance explained by each of the principal components.
+    pub fn get_explained_variance_ratio(&self) -> &[Target] {
+        &self.explained_variance_ratio
+    }
+
+    /// Returns the number of principal components.
+    pub fn get_number_of_components(&self) -> usize {
+        self.explained_variance.len()
+    }
+
+    /// Returns the dimensionality of the original samples.
+    pub fn get_original_dimension(&self) -> usize {
+        self.original_dimension
+    }
+
+    /// Projects the provided original samples on the principal components.
+    ///
+    /// # Arguments
+    /// * `target`: The slice where to write the projection of the samples.
+    /// * `original`: The samples to project.
+    pub fn transform(
+        &self,
+        target: &mut [Target],
+        original: &[Original],
+    ) -> Result<(), DimensionalReductionError>
+    where
+        Original: Float,
+        usize: AsPrimitive<Original>,
+    {
+        self.transform_matrix(
+            target,
+            &DenseMatrix::new(original, self.original_dimension)?,
+        )
+    }
+
+    /// Projects the samples of the provided original matrix on the principal components.
+    ///
+    /// # Arguments
+    /// * `target`: The slice where to write the projection of the samples.
+    /// * `original`: The matrix of the samples to project.
+    pub fn transform_matrix<M>(
+        &self,
+        target: &mut [Target],
+        original: &M,
+    ) -> Result<(), DimensionalReductionError>
+    where
+        Original: Float,
+        M: OriginalMatrix<Original>,
+    {
+        if original.get_dimension() != self.original_dimension {
+            return Err(DimensionalReductionError::IncompatibleDimension {
+                name: "original",
+                length: original.get_dimension(),
+                dimension: self.original_dimension,
+            });
+        }
+
+        validate_fit_transform_matrix_arguments(target, self.get_number_of_components(), original)?;
+
+        target
+            .par_chunks_mut(self.get_number_of_components())
+            .enumerate()
+            .for_each(|(sample_number, target_sample)| {
+                let centered_sample = center(original.get_sample(sample_number), &self.mean);
+                target_sample
+                    .iter_mut()
+                    .zip(self.components.chunks(self.original_dimension))
+                    .for_each(|(projection, component)| {
+                        *projection =
+                            dot(centered_sample.iter().copied(), component.iter().copied());
+                    });
+            });
+
+        Ok(())
+    }
+}
+
+/// Returns the provided sample minus the provided mean, with all of its features.
+fn center<Original, Target>(sample: Sample<'_, Original>, mean: &[Original]) -> Vec<Target>
+where
+    Original: AsPrimitive<Target> + GenericFeature + Float,
+    Target: Float + GenericFeature,
+{
+    let mut centered_sample = mean
+        .iter()
+        .map(|&mean| (-mean).as_())
+        .collect::<Vec<Target>>();
+    sample.for_each_feature(|index, value| {
+        centered_sample[index] = (value - mean[index]).as_();
+    });
+    centered_sample
+}
+
+/// Principal Component Analysis.
+///
+/// The principal components are computed with a randomized subspace iteration
+/// on the covariance matrix of the original samples. The covariance matrix is
+/// never materialized, as only its products with the current components are
+/// needed, so that both tall and wide matrices are supported.
+#[derive(Clone)]
+pub struct PCADecomposition {
+    decomposition: BasicDecomposition,
+    iterations: usize,
+}
+
+impl PCADecomposition {
+    /// Returns a new Principal Component Analysis model.
+    ///
+    /// # Arguments
+    /// * `decomposition`: The basic decomposition parameters.
+    /// * `iterations`: The number of subspace iterations to run. By default, 30.
+    pub fn new(decomposition: BasicDecomposition, iterations: Option<usize>) -> Self {
+        Self {
+            decomposition,
+            iterations: iterations.unwrap_or(30),
+        }
+    }
+
+    /// Returns the principal components of the provided original samples.
+    ///
+    /// # Arguments
+    /// * `number_of_components`: The number of principal components to compute.
+    /// * `original`: The original samples.
+    /// * `original_dimension`: The dimensionality of the original samples.
+    pub fn fit_components<Original, Target>(
+        &self,
+        number_of_components: usize,
+        original: &[Original],
+        original_dimension: usize,
+    ) -> Result<PrincipalComponents<Original, Target>, DimensionalReductionError>
+    where
+        Original: AsPrimitive<Target> + GenericFeature + Float,
+        Target: Float + GenericFeature,
+        usize: AsPrimitive<Original> + AsPrimitive<Target>,
+        f32: AsPrimitive<Target>,
+    {
+        self.fit_components_matrix(
+            number_of_components,
+            &DenseMatrix::new(original, original_dimension)?,
+        )
+    }
+
+    /// Returns the principal components of the samples of the provided original matrix.
+    ///
+    /// # Arguments
+    /// * `number_of_components`: The number of principal components to compute.
+    /// * `original`: The matrix of the original samples.
+    pub fn fit_components_matrix<Original, Target, M>(
+        &self,
+        number_of_components: usize,
+        original: &M,
+    ) -> Result<PrincipalComponents<Original, Target>, DimensionalReductionError>
+    where
+        Original: AsPrimitive<Target> + GenericFeature + Float,
+        Target: Float + GenericFeature,
+        M: OriginalMatrix<Original>,
+        usize: AsPrimitive<Original> + AsPrimitive<Target>,
+        f32: AsPrimitive<Target>,
+    {
+        let original_dimension = original.get_dimension();
+
+        if number_of_components == 0 {
+            return Err(DimensionalReductionError::ZeroDimension { name: "target" });
+        }
+
+        if number_of_components > original_dimension {
+            return Err(DimensionalReductionError::UnsupportedTargetDimension {
+                target_dimension: number_of_components,
+                supported_target_dimensions: "at most the original dimension",
+            });
+        }
+
+        original.matrix_is_finite("original")?;
+        let statistics = original.get_statistics()?;
+        let (mean, variance) = (statistics.get_mean(), statistics.get_variance());
+        let number_of_samples: Target = original.get_number_of_samples().as_();
+
+        // We start from a random orthonormal basis.
+        let random_state = splitmix64(self.get_random_state());
+        let mut components = (0..number_of_components * original_dimension)
+            .map(|i| {
+                (2.0_f32 * random_f32(splitmix64(random_state.wrapping_add(i as u64))) - 1.0_f32)
+                    .as_()
+            })
+            .collect::<Vec<Target>>();
+        orthonormalize(&mut components, original_dimension);
+
+        // At each iteration we multiply the current basis by the covariance matrix
+        // and orthonormalize the result, converging to the top eigenvectors.
+        for _ in 0..self.iterations {
+            components = covariance_product(&components, original, mean);
+            components.iter_mut().for_each(|value| {
+                *value /= number_of_samples;
+            });
+            orthonormalize(&mut components, original_dimension);
+        }
+
+        // The variance explained by each component is its Rayleigh quotient.
+        let explained_variance = covariance_product(&components, original, mean)
+            .chunks(original_dimension)
+            .zip(components.chunks(original_dimension))
+            .map(|(product, component)| {
+                dot(product.iter().copied(), component.iter().copied()) / number_of_samples
+            })
+            .collect::<Vec<Target>>();
+
+        // We sort the components by decreasing explained variance and
+        // flip their sign so that their largest coordinate is positive,
+        // making the output deterministic. The total order keeps the sort
+        // from panicking on the not a number variances of degenerate inputs.
+        let mut order = (0..number_of_components).collect::<Vec<usize>>();
+        order.sort_by(|&left, &right| {
+            explained_variance[right]
+                .to_f64()
+                .unwrap()
+                .total_cmp(&explained_variance[left].to_f64().unwrap())
+        });
+        let components = order
+            .iter()
+            .flat_map(|&component_number| {
+                let component = &components[component_number * original_dimension
+                    ..(component_number + 1) * original_dimension];
+                let largest = component
+                    .iter()
+                    .copied()
+                    .fold(Target::zero(), |largest, value| {
+                        if value.abs() > largest.abs() {
+                            value
+                        } else {
+                            largest
+                        }
+                    });
+                let sign = if largest < Target::zero() {
+                    -Target::one()
+                } else {
+                    Target::one()
+                };
+                component.iter().map(move |&value| value * sign)
+            })
+            .collect::<Vec<Target>>();
+        let explained_variance = order
+            .into_iter()
+            .map(|component_number| explained_variance[component_number])
+            .collect::<Vec<Target>>();
+
+        let total_variance = variance
+            .iter()
+            .map(|&value| value.as_())
+            .sum::<Target>()
+            .max(Target::epsilon());
+        let explained_variance_ratio = explained_variance
+            .iter()
+            .map(|&value| value / total_variance)
+            .collect::<Vec<Target>>();
+
+        Ok(PrincipalComponents {
+            mean: mean.to_vec(),
+            components,
+            explained_variance,
+            explained_variance_ratio,
+            original_dimension,
+        })
+    }
+}
+
+/// Returns the product of the unnormalized covariance matrix of the
+/// original samples with each of the provided row-major vectors.
+fn covariance_product<Original, Target, M>(
+    vectors: &[Target],
+    original: &M,
+    mean: &[Original],
+) -> Vec<Target>
+where
+    Original: AsPrimitive<Target> + GenericFeature + Float,
+    Target: Float + GenericFeature,
+    M: OriginalMatrix<Original>,
+{
+    let original_dimension = original.get_dimension();
+    deterministic_partitioned_sum(
+        original.get_number_of_samples(),
+        vectors.len(),
+        |samples, partial_product: &mut [Target]| {
+            samples.for_each(|sample_number| {
+                let centered_sample = center(original.get_sample(sample_number), mean);
+                partial_product
+                    .chunks_mut(original_dimension)
+                    .zip(vectors.chunks(original_dimension))
+                    .for_each(|(product, vector)| {
+                        let projection =
+                            dot(centered_sample.iter().copied(), vector.iter().copied());
+                        product
+                            .iter_mut()
+                            .zip(centered_sample.iter().copied())
+                            .for_each(|(p, value)| {
+                                *p += value * projection;
+                            });
+                    });
+            });
+        },
+    )
+    .0
+}
+
+/// Orthonormalizes in place the provided row-major vectors with the
+/// modified Gram-Schmidt process.
+pub(crate) fn orthonormalize<F>(vectors: &mut [F], dimension: usize)
+where
+    F: Float + GenericFeature,
+{
+    for vector_number in 0..vectors.len() / dimension {
+        let (previous_vectors, following_vectors) = vectors.split_at_mut(vector_number * dimension);
+        let vector = &mut following_vectors[..dimension];
+        previous_vectors
+            .chunks(dimension)
+            .for_each(|previous_vector| {
+                let projection = dot(vector.iter().copied(), previous_vector.iter().copied());
+                vector
+                    .iter_mut()
+                    .zip(previous_vector.iter().copied())
+                    .for_each(|(v, p)| {
+                        *v -= projection * p;
+                    });
+            });
+        let norm = dot(vector.iter().copied(), vector.iter().copied())
+            .sqrt()
+            .max(F::epsilon());
+        vector.iter_mut().for_each(|v| {
+            *v /= norm;
+        });
+    }
+}
+
+impl Decomposition for PCADecomposition {
+    fn get_basic_decomposition(&self) -> &BasicDecomposition {
+        &self.decomposition
+    }
+}
+
+impl DimensionalReduction for PCADecomposition {
+    fn fit_transform_matrix<Original, Target, M>(
+        &self,
+        target: &mut [Target],
+        target_dimension: usize,
+        original: &M,
+    ) -> Result<Vec<f32>, DimensionalReductionError>
+    where
+        Original: AsPrimitive<Target> + GenericFeature + Float,
+        Target: Float + GenericFeature,
+        M: OriginalMatrix<Original>,
+        usize: AsPrimitive<Original> + AsPrimitive<Target>,
+        f32: AsPrimitive<Target>,
+    {
+        validate_fit_transform_matrix_arguments(target, target_dimension, original)?;
+
+        self.fit_components_matrix(target_dimension, original)?
+            .transform_matrix(target, original)?;
+
+        Ok(Vec::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overflowing_variances_do_not_panic() {
+        // The variances of samples this large overflow, so that the explained
+        // variances are not a number and must still be sorted.
+        let model = PCADecomposition::new(
+            BasicDecomposition::new("PCA test", None, None).unwrap(),
+            None,
+        );
+        let original = (0..40 * 4)
+            .map(|i| if i % 3 == 0 { 3e38_f32 } else { -3e38 })
+            .collect::<Vec<f32>>();
+        let _ = model.fit_components::<f32, f32>(3, &original, 4);
+    }
+
+    #[test]
+    fn test_components_match_known_axes() {
+        // The samples are the corners of a box centered in the mean, with
+        // sides of half-length 3, 2 and 1 along the orthonormal axes, so that
+        // the covariance has eigenvalues 9, 4 and 1 along these axes.
+        let axes = [
+            [1.0 / 3.0, 2.0 / 3.0, 2.0 / 3.0],
+            [2.0 / 3.0, 1.0 / 3.0, -2.0 / 3.0],
+            [2.0 / 3.0, -2.0 / 3.0, 1.0 / 3.0],
+        ];
+        let (mean, half_lengths) = ([5.0, -3.0, 1.0], [3.0, 2.0, 1.0]);
+        let original = (0..8)
+            .flat_map(|corner: usize| {
+                (0..3).map(move |dimension| {
+                    mean[dimension]
+                        + (0..3)
+                            .map(|axis| {
+                                let sign = if corner >> axis & 1 == 0 { -1.0 } else { 1.0 };
+                                sign * half_lengths[axis] * axes[axis][dimension]
+                            })
+                            .sum::<f64>()
+                })
+            })
+            .collect::<Vec<f64>>();
+
+        let components = PCADecomposition::new(
+            BasicDecomposition::new("PCA test", None, None).unwrap(),
+            None,
+        )
+        .fit_components::<f64, f64>(2, &original, 3)
+        .unwrap();
+
+        let first_component = &components.get_components()[..3];
+        let alignment = dot(first_component.iter().copied(), axes[0].iter().copied());
+        assert!((alignment.abs() - 1.0).abs() < 1e-9);
+        components
+            .get_explained_variance()
+            .iter()
+            .zip([9.0, 4.0])
+            .for_each(|(&explained_variance, eigenvalue)| {
+                assert!((explained_variance - eigenvalue).abs() < 1e-9);
+            });
+        assert!(
+            components
+                .get_explained_variance_ratio()
+                .iter()
+                .sum::<f64>()
+                <= 1.0 + 1e-12
+        );
+    }
+}
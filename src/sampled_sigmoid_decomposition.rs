@@ -1,138 +1,519 @@
 use crate::traits::*;
 use crate::{
     basic_iterative_decomposition::BasicIterativeDecomposition,
+    errors::DimensionalReductionError,
+    graph::Graph,
+    knn::{approximate_nearest_neighbours_by, NNDescent},
+    matrices::{OriginalMatrix, PrecomputedMatrix},
+    optimizers::OptimizerState,
+    similarities::{Similarity, StandardizedDot},
+    target_similarities::{SigmoidDot, TargetSimilarity},
     traits::{Decomposition, DimensionalReduction, GenericFeature, IterativeDecomposition},
-    utils::{dot, normal_dot, sigmoid, DataRaceAware},
+    utils::{
+        binary_cross_entropy, deterministic_partitioned_sum, sigmoid,
+        validate_fit_transform_matrix_arguments, DataRaceAware,
+    },
 };
 use num_traits::{AsPrimitive, Float};
 use rayon::prelude::*;
 use vec_rand::splitmix64;
 
+/// Sigmoid decomposition trained on sampled pairs of samples.
+///
+/// In each epoch, every sample is paired with the provided number of its most similar
+/// samples and with the provided number of negative samples, drawn with probability
+/// proportional to a power of their degree in the graph of the most similar samples.
 #[derive(Clone)]
-pub struct SampledSigmoidDecomposition {
+pub struct SampledSigmoidDecomposition<S = StandardizedDot, K = SigmoidDot> {
     decomposition: BasicIterativeDecomposition,
+    similarity: S,
+    target_similarity: K,
+    number_of_samples_per_point: usize,
+    negative_samples: usize,
+    number_of_neighbours: usize,
+    negative_sampling_exponent: f32,
+}
+
+impl<S: Similarity, K: TargetSimilarity> SampledSigmoidDecomposition<S, K> {
+    /// Returns a new model comparing the original samples with the provided similarity.
+    ///
+    /// # Arguments
+    /// * `decomposition`: The basic iterative decomposition parameters.
+    /// * `similarity`: The similarity between the original samples.
+    /// * `target_similarity`: The similarity between the embedded samples.
+    /// * `number_of_samples_per_point`: The number of positive pairs of each sample in each
+    ///   epoch, drawn among its most similar samples. By default, 0.
+    /// * `negative_samples`: The number of negative pairs of each sample in each epoch.
+    ///   By default, 1.
+    /// * `number_of_neighbours`: The number of most similar samples of each sample
+    ///   among which the positive pairs are drawn. By default, 10.
+    /// * `negative_sampling_exponent`: The exponent of the degree of the samples in the
+    ///   graph of the most similar samples, plus one, to which the probability of drawing
+    ///   them as negative samples is proportional. By default, 0.0, that is uniform.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        decomposition: BasicIterativeDecomposition,
+        similarity: S,
+        target_similarity: K,
+        number_of_samples_per_point: Option<usize>,
+        negative_samples: Option<usize>,
+        number_of_neighbours: Option<usize>,
+        negative_sampling_exponent: Option<f32>,
+    ) -> Result<Self, DimensionalReductionError> {
+        let number_of_samples_per_point = number_of_samples_per_point.unwrap_or(0);
+        let negative_samples = negative_samples.unwrap_or(1);
+        if number_of_samples_per_point + negative_samples == 0 {
+            return Err(DimensionalReductionError::InvalidParameter {
+                parameter: "negative samples",
+                value: negative_samples.to_string(),
+                expected: "strictly positive when no positive pairs are sampled",
+            });
+        }
+
+        let number_of_neighbours = number_of_neighbours.unwrap_or(10);
+        if number_of_neighbours == 0 {
+            return Err(DimensionalReductionError::InvalidParameter {
+                parameter: "number of neighbours",
+                value: number_of_neighbours.to_string(),
+                expected: "strictly positive",
+            });
+        }
+
+        let negative_sampling_exponent = negative_sampling_exponent.unwrap_or(0.0);
+        if !negative_sampling_exponent.is_finite() {
+            return Err(DimensionalReductionError::InvalidParameter {
+                parameter: "negative sampling exponent",
+                value: negative_sampling_exponent.to_string(),
+                expected: "a finite value",
+            });
+        }
+
+        Ok(Self {
+            decomposition,
+            similarity,
+            target_similarity,
+            number_of_samples_per_point,
+            negative_samples,
+            number_of_neighbours,
+            negative_sampling_exponent,
+        })
+    }
+
+    /// Returns whether the graph of the most similar samples is needed.
+    fn requires_neighbours(&self) -> bool {
+        self.number_of_samples_per_point > 0
+            || (self.negative_samples > 0 && self.negative_sampling_exponent != 0.0)
+    }
+
+    /// Returns the most similar samples of each sample, if they are needed,
+    /// approximated with NN-descent to avoid comparing all of the pairs of samples.
+    ///
+    /// # Arguments
+    /// * `number_of_samples`: The number of original samples.
+    /// * `original_similarity`: The similarity of the provided original samples.
+    fn get_neighbours<Original, O>(
+        &self,
+        number_of_samples: usize,
+        original_similarity: O,
+    ) -> Result<Neighbours<'static>, DimensionalReductionError>
+    where
+        Original: Float,
+        O: Fn(usize, usize) -> Original + Sync,
+    {
+        if !self.requires_neighbours() {
+            return Ok(Neighbours::Uniform(Vec::new()));
+        }
+        let nearest_neighbours = approximate_nearest_neighbours_by(
+            number_of_samples,
+            self.number_of_neighbours.min(number_of_samples - 1),
+            |left, right| -original_similarity(left, right).to_f64().unwrap(),
+            NNDescent::new(None, None, Some(self.get_random_state()))?,
+        )?;
+        Ok(Neighbours::Uniform(
+            (0..number_of_samples)
+                .map(|sample_number| nearest_neighbours.get_neighbours(sample_number).to_vec())
+                .collect(),
+        ))
+    }
 }
 
 impl From<BasicIterativeDecomposition> for SampledSigmoidDecomposition {
     fn from(decomposition: BasicIterativeDecomposition) -> Self {
-        Self { decomposition }
+        Self {
+            decomposition,
+            similarity: StandardizedDot,
+            target_similarity: SigmoidDot,
+            number_of_samples_per_point: 0,
+            negative_samples: 1,
+            number_of_neighbours: 10,
+            negative_sampling_exponent: 0.0,
+        }
     }
 }
 
-impl IterativeDecomposition for SampledSigmoidDecomposition {
+impl<S, K> IterativeDecomposition for SampledSigmoidDecomposition<S, K> {
     fn get_iterative_basic_decomposition(&self) -> &BasicIterativeDecomposition {
         &self.decomposition
     }
 }
 
-impl DimensionalReduction for SampledSigmoidDecomposition {
-    fn fit_transform<Original, Target>(
+impl<S: Similarity, K: TargetSimilarity> SimilarityDecomposition
+    for SampledSigmoidDecomposition<S, K>
+{
+    type Similarity = S;
+    type TargetSimilarity = K;
+
+    fn get_similarity(&self) -> &S {
+        &self.similarity
+    }
+
+    fn get_target_similarity(&self) -> &K {
+        &self.target_similarity
+    }
+}
+
+/// The samples among which the positive pairs of each sample are drawn.
+enum Neighbours<'a> {
+    /// The most similar samples of each sample, drawn uniformly.
+    Uniform(Vec<Vec<usize>>),
+    /// The neighbours of each node of a graph, drawn with probability
+    /// proportional to the weight of their edge.
+    Weighted {
+        indptr: &'a [usize],
+        destinations: &'a [usize],
+        cumulative_weights: Vec<f64>,
+    },
+}
+
+impl<'a> Neighbours<'a> {
+    /// Returns the neighbours of the nodes of the provided graph.
+    fn from_graph<Weight>(graph: &'a Graph<Weight>) -> Self
+    where
+        Weight: GenericFeature + Float,
+    {
+        let adjacency = graph.get_adjacency();
+        let indptr = adjacency.get_indptr();
+        let weights = adjacency.get_data();
+        // The cumulative weights restart from zero in each row.
+        let cumulative_weights = indptr
+            .par_windows(2)
+            .flat_map_iter(|window| {
+                weights[window[0]..window[1]]
+                    .iter()
+                    .scan(0.0, |total, weight| {
+                        *total += weight.to_f64().unwrap();
+                        Some(*total)
+                    })
+            })
+            .collect::<Vec<f64>>();
+        Self::Weighted {
+            indptr,
+            destinations: adjacency.get_indices(),
+            cumulative_weights,
+        }
+    }
+
+    /// Returns the neighbour of the provided sample drawn with the provided random value,
+    /// if the sample has any.
+    fn sample(&self, sample_number: usize, random_value: u64) -> Option<usize> {
+        match self {
+            Self::Uniform(neighbours) => {
+                let row = &neighbours[sample_number];
+                (!row.is_empty()).then(|| row[random_value as usize % row.len()])
+            }
+            Self::Weighted {
+                indptr,
+                destinations,
+                cumulative_weights,
+            } => {
+                let (start, end) = (indptr[sample_number], indptr[sample_number + 1]);
+                let cumulative_weights = &cumulative_weights[start..end];
+                let total = *cumulative_weights.last()?;
+                if total <= 0.0 {
+                    return None;
+                }
+                let threshold = (random_value >> 11) as f64 / (1_u64 << 53) as f64 * total;
+                let position = cumulative_weights
+                    .partition_point(|&cumulative| cumulative <= threshold)
+                    .min(end - start - 1);
+                Some(destinations[start + position])
+            }
+        }
+    }
+
+    /// Returns the number of times each sample appears among the neighbours.
+    fn get_degrees(&self, number_of_samples: usize) -> Vec<f64> {
+        let mut degrees = vec![0.0_f64; number_of_samples];
+        let mut increase = |neighbour: usize| {
+            degrees[neighbour] += 1.0;
+        };
+        match self {
+            Self::Uniform(neighbours) => {
+                neighbours.iter().flatten().copied().for_each(&mut increase)
+            }
+            Self::Weighted { destinations, .. } => {
+                destinations.iter().copied().for_each(&mut increase)
+            }
+        }
+        degrees
+    }
+}
+
+/// The samples paired with each sample in each epoch.
+struct PairSampler<'a> {
+    /// The samples among which the positive pairs are drawn.
+    neighbours: Neighbours<'a>,
+    /// The cumulative distribution of the negative samples, when it is not uniform.
+    cumulative_distribution: Option<Vec<f64>>,
+    number_of_samples: usize,
+    number_of_samples_per_point: usize,
+    negative_samples: usize,
+}
+
+impl<'a> PairSampler<'a> {
+    /// Returns a new sampler of the pairs of the provided number of samples.
+    ///
+    /// # Arguments
+    /// * `neighbours`: The samples among which the positive pairs are drawn.
+    /// * `number_of_samples`: The number of samples.
+    /// * `number_of_samples_per_point`: The number of positive pairs of each sample.
+    /// * `negative_samples`: The number of negative pairs of each sample.
+    /// * `negative_sampling_exponent`: The exponent of the degree of the samples, plus one,
+    ///   to which the probability of drawing them as negative samples is proportional.
+    fn new(
+        neighbours: Neighbours<'a>,
+        number_of_samples: usize,
+        number_of_samples_per_point: usize,
+        negative_samples: usize,
+        negative_sampling_exponent: f32,
+    ) -> Self {
+        let cumulative_distribution = (negative_sampling_exponent != 0.0).then(|| {
+            neighbours
+                .get_degrees(number_of_samples)
+                .into_iter()
+                .scan(0.0, |total, degree| {
+                    *total += (degree + 1.0).powf(negative_sampling_exponent as f64);
+                    Some(*total)
+                })
+                .collect::<Vec<f64>>()
+        });
+        Self {
+            neighbours,
+            cumulative_distribution,
+            number_of_samples,
+            number_of_samples_per_point,
+            negative_samples,
+        }
+    }
+
+    /// Returns the samples paired with the provided sample in the epoch with the
+    /// provided random state, first the positive pairs and then the negative ones.
+    ///
+    /// # Arguments
+    /// * `sample_number`: The sample whose pairs are drawn.
+    /// * `random_state`: The random state of the epoch.
+    fn get_pairs(
+        &self,
+        sample_number: usize,
+        random_state: u64,
+    ) -> impl Iterator<Item = usize> + '_ {
+        let pairs_per_point = self.number_of_samples_per_point + self.negative_samples;
+        (0..pairs_per_point).filter_map(move |pair_number| {
+            let random_value = splitmix64(random_state.wrapping_add(
+                ((sample_number * pairs_per_point + pair_number) as u64).wrapping_mul(random_state),
+            ));
+            if pair_number < self.number_of_samples_per_point {
+                return self.neighbours.sample(sample_number, random_value);
+            }
+            Some(match &self.cumulative_distribution {
+                Some(cumulative_distribution) => {
+                    let total = cumulative_distribution[self.number_of_samples - 1];
+                    let threshold = (random_value >> 11) as f64 / (1_u64 << 53) as f64 * total;
+                    cumulative_distribution
+                        .partition_point(|&cumulative| cumulative <= threshold)
+                        .min(self.number_of_samples - 1)
+                }
+                None => random_value as usize % self.number_of_samples,
+            })
+        })
+    }
+}
+
+impl<S: Similarity, K: TargetSimilarity> SampledSigmoidDecomposition<S, K> {
+    /// Fits the initialized target embedding on the provided similarities
+    /// between the original samples, returning the loss history.
+    ///
+    /// # Arguments
+    /// * `target`: The initialized embedding to fit.
+    /// * `target_dimension`: The dimensionality of the embedding.
+    /// * `original_similarity`: The similarity of the provided original samples.
+    /// * `neighbours`: The samples among which the positive pairs are drawn.
+    fn fit_transform_with_similarity<Original, Target, O>(
         &self,
-        mut target: &mut [Target],
+        target: &mut [Target],
         target_dimension: usize,
-        original: &[Original],
-        original_dimension: usize,
-    ) -> Result<(), String>
+        original_similarity: O,
+        neighbours: Neighbours<'_>,
+    ) -> Result<Vec<f32>, DimensionalReductionError>
     where
-        Original: AsPrimitive<Target> + GenericFeature,
+        Original: AsPrimitive<Target> + GenericFeature + Float,
         Target: Float + GenericFeature,
+        O: Fn(usize, usize) -> Original + Sync,
         usize: AsPrimitive<Original> + AsPrimitive<Target>,
         f32: AsPrimitive<Target>,
     {
-        if target.len() % target_dimension != 0 {
-            return Err(format!(
-                concat!(
-                    "The provided target slice has length {} ",
-                    "which is not compatible with the provided ",
-                    "target dimension {}."
-                ),
-                target.len(),
-                target_dimension
-            ));
-        }
-
-        target.random_init(self.get_random_state());
-
         let number_of_samples = target.len() / target_dimension;
 
-        let mean = original.matrix_mean(original_dimension)?;
-        let variance = original.matrix_var(original_dimension)?;
-
         // We wrap the features object in an unsafe cell so
         // it may be shared among threads.
+        let mut optimizer = OptimizerState::new(self.get_optimizer(), target.len());
         let wrapped_target = DataRaceAware::from(target);
 
-        if original.len() % original_dimension != 0 {
-            return Err(format!(
-                concat!(
-                    "The provided original slice has length {} ",
-                    "which is not compatible with the provided ",
-                    "original dimension {}."
-                ),
-                original.len(),
-                original_dimension
-            ));
-        }
-
-        let learning_rate: Target = self.get_learning_rate().as_();
         let mut random_state = splitmix64(self.get_random_state());
 
-        self.start_iterations()
-            .map(|_| {
-                random_state = splitmix64(random_state);
-                original
-                    .par_chunks(original_dimension)
-                    .enumerate()
-                    .map(|(sample_number, left_original_sample)| unsafe {
-                        (
-                            sample_number as u64,
-                            (
-                                &mut (*wrapped_target.get())[(sample_number * target_dimension)
-                                    ..((sample_number + 1) * target_dimension)],
-                                left_original_sample,
-                            ),
+        // Returns the variations of the provided pair of samples and its loss.
+        let get_variations_and_loss =
+            |left_target_sample: &[Target],
+             right_target_sample: &[Target],
+             original_similarity: Original,
+             learning_rate: Target| {
+                let original_similarity: Target = original_similarity.as_();
+
+                let (target_probability, own_coefficient, other_coefficient) = self
+                    .target_similarity
+                    .similarity_and_gradient(left_target_sample, right_target_sample);
+                let original_probability = sigmoid(original_similarity);
+
+                let variation = (target_probability - original_probability) * learning_rate;
+
+                (
+                    own_coefficient * variation,
+                    other_coefficient * variation,
+                    binary_cross_entropy(original_probability, target_probability),
+                )
+            };
+
+        let sampler = PairSampler::new(
+            neighbours,
+            number_of_samples,
+            self.number_of_samples_per_point,
+            self.negative_samples,
+            self.negative_sampling_exponent,
+        );
+
+        self.start_iterations(|_, learning_rate| {
+            // When the descent directions are batched, the optimizer applies the learning rate.
+            let variations_scale: Target = if self.is_batched() {
+                Target::one()
+            } else {
+                learning_rate.as_()
+            };
+
+            random_state = splitmix64(random_state);
+
+            if self.is_batched() {
+                let current_target: &[Target] = unsafe { *wrapped_target.get() };
+                let (variations, losses) = deterministic_partitioned_sum(
+                    number_of_samples,
+                    current_target.len(),
+                    |samples, variations: &mut [Target]| {
+                        samples.fold(
+                            (Target::zero(), Target::zero()),
+                            |(total_loss, number_of_pairs), sample_number| {
+                                sampler.get_pairs(sample_number, random_state).fold(
+                                    (total_loss, number_of_pairs),
+                                    |(total_loss, number_of_pairs), inner_sample_number| {
+                                        let left_target_sample = &current_target[sample_number
+                                            * target_dimension
+                                            ..(sample_number + 1) * target_dimension];
+                                        let right_target_sample = &current_target
+                                            [inner_sample_number * target_dimension
+                                                ..(inner_sample_number + 1) * target_dimension];
+                                        let (own_variation, other_variation, loss) =
+                                            get_variations_and_loss(
+                                                left_target_sample,
+                                                right_target_sample,
+                                                original_similarity(
+                                                    sample_number,
+                                                    inner_sample_number,
+                                                ),
+                                                variations_scale,
+                                            );
+                                        left_target_sample
+                                            .iter()
+                                            .zip(right_target_sample.iter())
+                                            .enumerate()
+                                            .for_each(|(dimension, (&left, &right))| {
+                                                let left_variation =
+                                                    left * own_variation + right * other_variation;
+                                                let right_variation =
+                                                    right * own_variation + left * other_variation;
+                                                if (left - left_variation).is_finite() {
+                                                    variations[sample_number * target_dimension
+                                                        + dimension] -= left_variation;
+                                                }
+                                                if (right - right_variation).is_finite() {
+                                                    variations[inner_sample_number
+                                                        * target_dimension
+                                                        + dimension] -= right_variation;
+                                                }
+                                            });
+                                        if loss.is_finite() {
+                                            (total_loss + loss, number_of_pairs + Target::one())
+                                        } else {
+                                            (total_loss, number_of_pairs)
+                                        }
+                                    },
+                                )
+                            },
                         )
-                    })
-                    .map(
-                        |(sample_number, (left_target_sample, left_original_sample))| unsafe {
-                            let inner_sample_number = (splitmix64(
-                                random_state.wrapping_add(sample_number.wrapping_mul(random_state)),
-                            ) as usize)
-                                % number_of_samples;
+                    },
+                );
+                optimizer.update(
+                    unsafe { &mut **wrapped_target.get() },
+                    &variations,
+                    learning_rate,
+                );
+                let (total_loss, number_of_pairs) = losses.into_iter().fold(
+                    (Target::zero(), Target::zero()),
+                    |(left_loss, left_pairs), (right_loss, right_pairs)| {
+                        (left_loss + right_loss, left_pairs + right_pairs)
+                    },
+                );
+                return Ok((total_loss / number_of_pairs.max(Target::one()))
+                    .to_f32()
+                    .unwrap());
+            }
+
+            let (total_loss, number_of_pairs) = (0..number_of_samples)
+                .into_par_iter()
+                .map(|sample_number| unsafe {
+                    sampler.get_pairs(sample_number, random_state).fold(
+                        (Target::zero(), Target::zero()),
+                        |(total_loss, number_of_pairs), inner_sample_number| {
+                            let left_target_sample = &mut (*wrapped_target.get())[(sample_number
+                                * target_dimension)
+                                ..((sample_number + 1) * target_dimension)];
 
                             let right_target_sample = &mut (*wrapped_target.get())
                                 [(inner_sample_number * target_dimension)
                                     ..((inner_sample_number + 1) * target_dimension)];
 
-                            let right_original_sample = &original[(inner_sample_number
-                                * original_dimension)
-                                ..((inner_sample_number + 1) * original_dimension)];
-
-                            let target_dot = dot(
-                                left_target_sample.iter().copied(),
-                                right_target_sample.iter().copied(),
+                            let (own_variation, other_variation, loss) = get_variations_and_loss(
+                                left_target_sample,
+                                right_target_sample,
+                                original_similarity(sample_number, inner_sample_number),
+                                variations_scale,
                             );
 
-                            let original_dot: Target = normal_dot(
-                                left_original_sample,
-                                right_original_sample,
-                                &mean,
-                                &variance,
-                            )
-                            .as_();
-
-                            //let left_sigmoid = sigmoid(target_dot);
-                            //let right_sigmoid = sigmoid(original_dot);
-
-                            let mut variation = sigmoid(target_dot) - sigmoid(original_dot.ln());
-                            variation *= learning_rate;
-
                             left_target_sample
                                 .iter_mut()
                                 .zip(right_target_sample.iter_mut())
                                 .for_each(|(left, right)| {
-                                    let new_left = *left - *right * variation;
-                                    let new_right = *right - *left * variation;
+                                    let new_left =
+                                        *left - (*left * own_variation + *right * other_variation);
+                                    let new_right =
+                                        *right - (*right * own_variation + *left * other_variation);
                                     if new_left.is_finite() {
                                         *left = new_left;
                                     }
@@ -140,11 +521,327 @@ impl DimensionalReduction for SampledSigmoidDecomposition {
                                         *right = new_right;
                                     }
                                 });
-                            Ok(())
+
+                            // Pairs whose loss overflows are not counted in the loss.
+                            if loss.is_finite() {
+                                (total_loss + loss, number_of_pairs + Target::one())
+                            } else {
+                                (total_loss, number_of_pairs)
+                            }
                         },
                     )
-                    .collect::<Result<(), String>>()
+                })
+                .reduce(
+                    || (Target::zero(), Target::zero()),
+                    |(left_loss, left_pairs), (right_loss, right_pairs)| {
+                        (left_loss + right_loss, left_pairs + right_pairs)
+                    },
+                );
+            Ok((total_loss / number_of_pairs.max(Target::one()))
+                .to_f32()
+                .unwrap())
+        })
+    }
+}
+
+impl<S: Similarity, K: TargetSimilarity> DimensionalReduction
+    for SampledSigmoidDecomposition<S, K>
+{
+    fn fit_transform_matrix<Original, Target, M>(
+        &self,
+        target: &mut [Target],
+        target_dimension: usize,
+        original: &M,
+    ) -> Result<Vec<f32>, DimensionalReductionError>
+    where
+        Original: AsPrimitive<Target> + GenericFeature + Float,
+        Target: Float + GenericFeature,
+        M: OriginalMatrix<Original>,
+        usize: AsPrimitive<Original> + AsPrimitive<Target>,
+        f32: AsPrimitive<Target>,
+    {
+        validate_fit_transform_matrix_arguments(target, target_dimension, original)?;
+
+        self.initialize_target(target, target_dimension, original)?;
+
+        let statistics = original.get_statistics()?;
+        let original_similarity = |left: usize, right: usize| {
+            self.similarity.similarity(
+                original.get_sample(left),
+                original.get_sample(right),
+                &statistics,
+            )
+        };
+
+        self.fit_transform_with_similarity(
+            target,
+            target_dimension,
+            original_similarity,
+            self.get_neighbours(original.get_number_of_samples(), original_similarity)?,
+        )
+    }
+}
+
+impl<S: Similarity, K: TargetSimilarity> PrecomputedDimensionalReduction
+    for SampledSigmoidDecomposition<S, K>
+{
+    fn fit_transform_precomputed<Original, Target, M>(
+        &self,
+        target: &mut [Target],
+        target_dimension: usize,
+        precomputed: &PrecomputedMatrix<M>,
+    ) -> Result<Vec<f32>, DimensionalReductionError>
+    where
+        Original: AsPrimitive<Target> + GenericFeature + Float,
+        Target: Float + GenericFeature,
+        M: OriginalMatrix<Original>,
+        usize: AsPrimitive<Original> + AsPrimitive<Target>,
+        f32: AsPrimitive<Target>,
+    {
+        validate_fit_transform_matrix_arguments(
+            target,
+            target_dimension,
+            precomputed.get_matrix(),
+        )?;
+
+        self.get_initialization().initialize_precomputed(
+            target,
+            target_dimension,
+            precomputed,
+            self.get_random_state(),
+        )?;
+
+        let original_similarity =
+            |left: usize, right: usize| precomputed.get_similarity::<Original>(left, right);
+
+        // The most similar samples are taken from the entries stored in each row,
+        // so that the sparse matrices are not compared over all of the pairs.
+        let neighbours = if self.requires_neighbours() {
+            precomputed
+                .get_most_similar::<Original>(self.number_of_neighbours)
+                .into_iter()
+                .map(|row| row.into_iter().map(|(neighbour, _)| neighbour).collect())
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        self.fit_transform_with_similarity(
+            target,
+            target_dimension,
+            original_similarity,
+            Neighbours::Uniform(neighbours),
+        )
+    }
+}
+
+impl<S: Similarity, K: TargetSimilarity> GraphDimensionalReduction
+    for SampledSigmoidDecomposition<S, K>
+{
+    /// Fits the model on the nodes of the provided graph, drawing the positive
+    /// pairs of each node among its neighbours with probability proportional to
+    /// the weight of their edge, as in a first-order LINE embedding.
+    fn fit_transform_graph<Weight, Target>(
+        &self,
+        target: &mut [Target],
+        target_dimension: usize,
+        graph: &Graph<Weight>,
+    ) -> Result<Vec<f32>, DimensionalReductionError>
+    where
+        Weight: AsPrimitive<Target> + GenericFeature + Float,
+        Target: Float + GenericFeature,
+        usize: AsPrimitive<Weight> + AsPrimitive<Target>,
+        f32: AsPrimitive<Target>,
+    {
+        if self.number_of_samples_per_point == 0 {
+            return Err(DimensionalReductionError::InvalidParameter {
+                parameter: "number of samples per point",
+                value: self.number_of_samples_per_point.to_string(),
+                expected: "strictly positive when the input is a graph",
+            });
+        }
+
+        validate_fit_transform_matrix_arguments(target, target_dimension, graph.get_adjacency())?;
+
+        self.get_initialization().initialize_graph(
+            target,
+            target_dimension,
+            graph,
+            self.get_random_state(),
+        )?;
+
+        // The connected nodes have unit similarity, the others zero similarity.
+        self.fit_transform_with_similarity(
+            target,
+            target_dimension,
+            |left: usize, right: usize| graph.get_similarity::<Weight>(left, right),
+            Neighbours::from_graph(graph),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basic_iterative_decomposition::IterativeParameters;
+    use crate::initialization::Initialization;
+    use crate::knn::exact_nearest_neighbours_by;
+    use vec_rand::random_f32;
+
+    #[test]
+    fn test_neighbours_match_exact_neighbours() {
+        let number_of_samples = 300;
+        let points = (0..number_of_samples as u64 * 2)
+            .map(|i| random_f32(splitmix64(i)) as f64)
+            .collect::<Vec<f64>>();
+        let similarity = |left: usize, right: usize| {
+            -((points[left * 2] - points[right * 2]).powi(2)
+                + (points[left * 2 + 1] - points[right * 2 + 1]).powi(2))
+        };
+        let model = SampledSigmoidDecomposition::new(
+            BasicIterativeDecomposition::new(
+                "Sampled test",
+                IterativeParameters {
+                    verbose: Some(false),
+                    ..Default::default()
+                },
+            )
+            .unwrap(),
+            StandardizedDot,
+            SigmoidDot,
+            Some(1),
+            None,
+            Some(10),
+            None,
+        )
+        .unwrap();
+        let neighbours = match model.get_neighbours(number_of_samples, similarity).unwrap() {
+            Neighbours::Uniform(neighbours) => neighbours,
+            Neighbours::Weighted { .. } => unreachable!(),
+        };
+        let exact_neighbours = exact_nearest_neighbours_by(number_of_samples, 10, |left, right| {
+            -similarity(left, right)
+        })
+        .unwrap();
+        let recalled = neighbours
+            .iter()
+            .enumerate()
+            .map(|(sample_number, neighbours)| {
+                assert_eq!(neighbours.len(), 10);
+                neighbours
+                    .iter()
+                    .filter(|neighbour| {
+                        exact_neighbours
+                            .get_neighbours(sample_number)
+                            .contains(neighbour)
+                    })
+                    .count()
+            })
+            .sum::<usize>();
+        assert!(recalled as f64 > 0.95 * (number_of_samples * 10) as f64);
+    }
+
+    #[test]
+    fn test_dissimilar_pairs_move_the_embedding() {
+        // The standardized samples are opposite, so that their logit is negative.
+        let original = [1.0_f32, 2.0, -1.0, -2.0];
+        let mut target = vec![1.0_f32, 0.0, 1.0, 0.0];
+        let model = SampledSigmoidDecomposition::new(
+            BasicIterativeDecomposition::new(
+                "Sampled test",
+                IterativeParameters {
+                    iterations: Some(1),
+                    initialization: Some(Initialization::Provided),
+                    verbose: Some(false),
+                    ..Default::default()
+                },
+            )
+            .unwrap(),
+            StandardizedDot,
+            SigmoidDot,
+            Some(1),
+            Some(0),
+            Some(1),
+            None,
+        )
+        .unwrap();
+        let loss_history = model.fit_transform(&mut target, 2, &original, 2).unwrap();
+
+        assert!(loss_history[0].is_finite() && loss_history[0] > 0.0);
+        // The embedded samples are pushed apart, decreasing their dot product.
+        assert!(target[0] * target[2] + target[1] * target[3] < 1.0);
+    }
+
+    #[test]
+    fn test_pairs_per_epoch() {
+        // Each sample has as neighbours the two following samples of a ring.
+        let number_of_samples = 20;
+        let neighbours = (0..number_of_samples)
+            .map(|sample_number| {
+                vec![
+                    (sample_number + 1) % number_of_samples,
+                    (sample_number + 2) % number_of_samples,
+                ]
             })
-            .collect::<Result<(), String>>()
+            .collect::<Vec<Vec<usize>>>();
+        let sampler = PairSampler::new(
+            Neighbours::Uniform(neighbours.clone()),
+            number_of_samples,
+            3,
+            2,
+            0.0,
+        );
+        let mut random_state = 42;
+        for _ in 0..10 {
+            random_state = splitmix64(random_state);
+            for sample_number in 0..number_of_samples {
+                let pairs = sampler
+                    .get_pairs(sample_number, random_state)
+                    .collect::<Vec<usize>>();
+                assert_eq!(pairs.len(), 3 + 2);
+                assert!(pairs[..3]
+                    .iter()
+                    .all(|pair| neighbours[sample_number].contains(pair)));
+                assert!(pairs[3..].iter().all(|&pair| pair < number_of_samples));
+            }
+        }
+    }
+
+    #[test]
+    fn test_negative_sampling_exponent() {
+        // Every sample has the first sample as neighbour, which therefore
+        // has the largest degree, while the first sample has the second.
+        let number_of_samples = 10;
+        let neighbours = (0..number_of_samples)
+            .map(|sample_number| vec![usize::from(sample_number == 0)])
+            .collect::<Vec<Vec<usize>>>();
+        // Returns the frequency of the first sample among the negative samples.
+        let get_frequency = |negative_sampling_exponent: f32| {
+            let sampler = PairSampler::new(
+                Neighbours::Uniform(neighbours.clone()),
+                number_of_samples,
+                0,
+                5,
+                negative_sampling_exponent,
+            );
+            let mut random_state = 42;
+            let (mut hits, mut total) = (0, 0);
+            for _ in 0..200 {
+                random_state = splitmix64(random_state);
+                for sample_number in 0..number_of_samples {
+                    sampler
+                        .get_pairs(sample_number, random_state)
+                        .for_each(|pair| {
+                            hits += usize::from(pair == 0);
+                            total += 1;
+                        });
+                }
+            }
+            hits as f64 / total as f64
+        };
+        // With the exponent one, the first sample has weight 10 out of the
+        // total 10 + 2 + 8 = 20, while it is drawn uniformly without exponent.
+        assert!((get_frequency(1.0) - 0.5).abs() < 0.05);
+        assert!((get_frequency(0.0) - 0.1).abs() < 0.05);
     }
 }
@@ -0,0 +1,186 @@
+use crate::errors::DimensionalReductionError;
+
+/// Decay of the learning rate over the epochs following the warmup.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum LearningRateDecay {
+    /// The learning rate does not change.
+    #[default]
+    Constant,
+    /// The learning rate decays linearly to zero over the epochs.
+    Linear,
+    /// The learning rate is multiplied by the provided rate at each epoch.
+    Exponential { rate: f32 },
+    /// The learning rate is multiplied by the provided rate every `step_size` epochs.
+    Step { rate: f32, step_size: usize },
+    /// The learning rate follows half a cosine period, decaying smoothly to zero.
+    Cosine,
+}
+
+/// Schedule of the learning rate of the iterative decompositions.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LearningRateSchedule {
+    decay: LearningRateDecay,
+    warmup_iterations: usize,
+}
+
+impl LearningRateSchedule {
+    /// Returns a new learning rate schedule.
+    ///
+    /// # Arguments
+    /// * `decay`: The decay of the learning rate after the warmup.
+    /// * `warmup_iterations`: The number of epochs over which the learning rate
+    ///   grows linearly to its full value before decaying. By default, 0.
+    pub fn new(decay: LearningRateDecay, warmup_iterations: Option<usize>) -> Self {
+        Self {
+            decay,
+            warmup_iterations: warmup_iterations.unwrap_or(0),
+        }
+    }
+
+    /// Returns the learning rate schedule with the provided name.
+    ///
+    /// # Arguments
+    /// * `name`: The name of the decay, one of `constant`, `linear`, `exponential`, `step` and `cosine`.
+    /// * `decay_rate`: The factor of the `exponential` and `step` decays. By default, 0.95
+    ///   for the `exponential` decay and 0.5 for the `step` decay.
+    /// * `step_size`: The number of epochs between the decays of the `step` decay. By default, 10.
+    /// * `warmup_iterations`: The number of epochs over which the learning rate
+    ///   grows linearly to its full value before decaying. By default, 0.
+    pub fn from_name(
+        name: &str,
+        decay_rate: Option<f32>,
+        step_size: Option<usize>,
+        warmup_iterations: Option<usize>,
+    ) -> Result<Self, DimensionalReductionError> {
+        let validate_rate = |default: f32| {
+            let rate = decay_rate.unwrap_or(default);
+            if !(rate > 0.0 && rate <= 1.0) {
+                return Err(DimensionalReductionError::InvalidParameter {
+                    parameter: "decay rate",
+                    value: rate.to_string(),
+                    expected: "in the range (0, 1]",
+                });
+            }
+            Ok(rate)
+        };
+
+        let decay = match name {
+            "constant" => LearningRateDecay::Constant,
+            "linear" => LearningRateDecay::Linear,
+            "exponential" => LearningRateDecay::Exponential {
+                rate: validate_rate(0.95)?,
+            },
+            "step" => {
+                let step_size = step_size.unwrap_or(10);
+                if step_size == 0 {
+                    return Err(DimensionalReductionError::InvalidParameter {
+                        parameter: "step size",
+                        value: step_size.to_string(),
+                        expected: "strictly positive",
+                    });
+                }
+                LearningRateDecay::Step {
+                    rate: validate_rate(0.5)?,
+                    step_size,
+                }
+            }
+            "cosine" => LearningRateDecay::Cosine,
+            name => {
+                return Err(DimensionalReductionError::InvalidParameter {
+                    parameter: "learning rate schedule",
+                    value: name.to_string(),
+                    expected: "one of constant, linear, exponential, step and cosine",
+                })
+            }
+        };
+
+        Ok(Self::new(decay, warmup_iterations))
+    }
+
+    /// Returns whether the learning rate is the same in every epoch.
+    pub fn is_constant(&self) -> bool {
+        self.decay == LearningRateDecay::Constant && self.warmup_iterations == 0
+    }
+
+    /// Returns the factor of the learning rate in the provided epoch.
+    ///
+    /// # Arguments
+    /// * `iteration`: The epoch, starting from zero.
+    /// * `iterations`: The maximum number of epochs.
+    pub fn get_factor(&self, iteration: usize, iterations: usize) -> f32 {
+        if iteration < self.warmup_iterations {
+            return (iteration + 1) as f32 / (self.warmup_iterations + 1) as f32;
+        }
+        let iteration = iteration - self.warmup_iterations;
+        let progress =
+            iteration as f32 / iterations.saturating_sub(self.warmup_iterations).max(1) as f32;
+        match self.decay {
+            LearningRateDecay::Constant => 1.0,
+            LearningRateDecay::Linear => 1.0 - progress,
+            LearningRateDecay::Exponential { rate } => rate.powi(iteration as i32),
+            LearningRateDecay::Step { rate, step_size } => {
+                rate.powi((iteration / step_size) as i32)
+            }
+            LearningRateDecay::Cosine => 0.5 * (1.0 + (std::f32::consts::PI * progress).cos()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_learning_rate_schedules() {
+        let factors = |name: &str, warmup_iterations: Option<usize>| {
+            let schedule =
+                LearningRateSchedule::from_name(name, None, Some(3), warmup_iterations).unwrap();
+            (0..10)
+                .map(|iteration| schedule.get_factor(iteration, 10))
+                .collect::<Vec<f32>>()
+        };
+
+        assert!(factors("constant", None)
+            .iter()
+            .all(|&factor| factor == 1.0));
+        for name in ["linear", "exponential", "step", "cosine"] {
+            let factors = factors(name, None);
+            assert_eq!(factors[0], 1.0, "{}", name);
+            assert!(
+                factors.windows(2).all(|window| window[1] <= window[0]),
+                "{}: {:?}",
+                name,
+                factors
+            );
+            assert!(
+                factors[9] > 0.0 && factors[9] < 1.0,
+                "{}: {:?}",
+                name,
+                factors
+            );
+        }
+        assert_eq!(factors("step", None)[2], 1.0);
+        assert_eq!(factors("step", None)[3], 0.5);
+        assert!((factors("linear", None)[5] - 0.5).abs() < 1e-6);
+        assert!((factors("cosine", None)[5] - 0.5).abs() < 1e-6);
+
+        // The warmup grows linearly to the full learning rate, which then decays.
+        let factors = factors("linear", Some(3));
+        assert_eq!(&factors[..4], &[0.25, 0.5, 0.75, 1.0]);
+        assert!(factors[4] < 1.0);
+    }
+
+    #[test]
+    fn test_invalid_learning_rate_schedules() {
+        assert!(LearningRateSchedule::from_name("polynomial", None, None, None).is_err());
+        assert!(LearningRateSchedule::from_name("exponential", Some(0.0), None, None).is_err());
+        assert!(LearningRateSchedule::from_name("step", Some(1.5), None, None).is_err());
+        assert!(LearningRateSchedule::from_name("step", None, Some(0), None).is_err());
+        assert!(LearningRateSchedule::default().is_constant());
+        assert!(
+            !LearningRateSchedule::from_name("constant", None, None, Some(5))
+                .unwrap()
+                .is_constant()
+        );
+    }
+}
@@ -1,136 +1,285 @@
 use crate::traits::*;
 use crate::{
     basic_iterative_decomposition::BasicIterativeDecomposition,
-    traits::{Decomposition, DimensionalReduction, GenericFeature, IterativeDecomposition},
-    utils::{dot, normal_dot, sigmoid, DataRaceAware},
+    errors::DimensionalReductionError,
+    matrices::{OriginalMatrix, PrecomputedMatrix},
+    optimizers::OptimizerState,
+    similarities::{Similarity, StandardizedDot},
+    target_similarities::{SigmoidDot, TargetSimilarity},
+    traits::{DimensionalReduction, GenericFeature, IterativeDecomposition},
+    utils::{
+        binary_cross_entropy, deterministic_partitioned_sum, sigmoid,
+        validate_fit_transform_matrix_arguments, DataRaceAware,
+    },
 };
 use num_traits::{AsPrimitive, Float};
 use rayon::prelude::*;
 
 #[derive(Clone)]
-pub struct SigmoidDecomposition {
+pub struct SigmoidDecomposition<S = StandardizedDot, K = SigmoidDot> {
     decomposition: BasicIterativeDecomposition,
+    similarity: S,
+    target_similarity: K,
+}
+
+impl<S: Similarity, K: TargetSimilarity> SigmoidDecomposition<S, K> {
+    /// Returns a new model comparing the original samples with the provided similarity.
+    ///
+    /// # Arguments
+    /// * `decomposition`: The basic iterative decomposition parameters.
+    /// * `similarity`: The similarity between the original samples.
+    /// * `target_similarity`: The similarity between the embedded samples.
+    pub fn new(
+        decomposition: BasicIterativeDecomposition,
+        similarity: S,
+        target_similarity: K,
+    ) -> Self {
+        Self {
+            decomposition,
+            similarity,
+            target_similarity,
+        }
+    }
 }
 
 impl From<BasicIterativeDecomposition> for SigmoidDecomposition {
     fn from(decomposition: BasicIterativeDecomposition) -> Self {
-        Self { decomposition }
+        Self::new(decomposition, StandardizedDot, SigmoidDot)
     }
 }
 
-impl IterativeDecomposition for SigmoidDecomposition {
+impl<S, K> IterativeDecomposition for SigmoidDecomposition<S, K> {
     fn get_iterative_basic_decomposition(&self) -> &BasicIterativeDecomposition {
         &self.decomposition
     }
 }
 
-impl DimensionalReduction for SigmoidDecomposition {
-    fn fit_transform<Original, Target>(
+impl<S: Similarity, K: TargetSimilarity> SimilarityDecomposition for SigmoidDecomposition<S, K> {
+    type Similarity = S;
+    type TargetSimilarity = K;
+
+    fn get_similarity(&self) -> &S {
+        &self.similarity
+    }
+
+    fn get_target_similarity(&self) -> &K {
+        &self.target_similarity
+    }
+}
+
+impl<S: Similarity, K: TargetSimilarity> SigmoidDecomposition<S, K> {
+    /// Fits the initialized target embedding on the provided similarities
+    /// between the original samples, returning the loss history.
+    ///
+    /// # Arguments
+    /// * `target`: The initialized embedding to fit.
+    /// * `target_dimension`: The dimensionality of the embedding.
+    /// * `original_similarity`: The similarity of the provided original samples as a logit.
+    fn fit_transform_with_similarity<Original, Target, O>(
         &self,
-        mut target: &mut [Target],
+        target: &mut [Target],
         target_dimension: usize,
-        original: &[Original],
-        original_dimension: usize,
-    ) -> Result<(), String>
+        original_similarity: O,
+    ) -> Result<Vec<f32>, DimensionalReductionError>
     where
-        Original: AsPrimitive<Target> + GenericFeature,
+        Original: AsPrimitive<Target> + GenericFeature + Float,
         Target: Float + GenericFeature,
+        O: Fn(usize, usize) -> Original + Sync,
         usize: AsPrimitive<Original> + AsPrimitive<Target>,
         f32: AsPrimitive<Target>,
     {
-        if target.len() % target_dimension != 0 {
-            return Err(format!(
-                concat!(
-                    "The provided target slice has length {} ",
-                    "which is not compatible with the provided ",
-                    "target dimension {}."
-                ),
-                target.len(),
-                target_dimension
-            ));
-        }
-
-        target.random_init(self.get_random_state());
-
-        let mean = original.matrix_mean(original_dimension)?;
-        let variance = original.matrix_var(original_dimension)?;
+        let number_of_samples = target.len() / target_dimension;
 
         // We wrap the features object in an unsafe cell so
         // it may be shared among threads.
+        let mut optimizer = OptimizerState::new(self.get_optimizer(), target.len());
         let wrapped_target = DataRaceAware::from(target);
 
-        if original.len() % original_dimension != 0 {
-            return Err(format!(
-                concat!(
-                    "The provided original slice has length {} ",
-                    "which is not compatible with the provided ",
-                    "original dimension {}."
-                ),
-                original.len(),
-                original_dimension
-            ));
-        }
+        // Every sample is paired with itself and with all the following samples.
+        let number_of_pairs: Target = (number_of_samples * (number_of_samples + 1) / 2).as_();
 
-        let learning_rate: Target = self.get_learning_rate().as_();
+        // Returns the variations of the provided pair of samples and its loss.
+        let get_variations_and_loss =
+            |left_target_sample: &[Target],
+             right_target_sample: &[Target],
+             original_similarity: Original,
+             learning_rate: Target| {
+                let (target_probability, own_coefficient, other_coefficient) = self
+                    .target_similarity
+                    .similarity_and_gradient(left_target_sample, right_target_sample);
+                let original_probability = sigmoid(original_similarity.as_());
+                let variation = (target_probability - original_probability) * learning_rate;
+                (
+                    own_coefficient * variation,
+                    other_coefficient * variation,
+                    binary_cross_entropy(original_probability, target_probability),
+                )
+            };
 
-        self.start_iterations()
-            .map(|_| {
-                original
-                    .par_chunks(original_dimension)
-                    .enumerate()
-                    .map(|(sample_number, left_original_sample)| unsafe {
-                        (
-                            sample_number,
-                            (
-                                &mut (*wrapped_target.get())[(sample_number * target_dimension)
-                                    ..((sample_number + 1) * target_dimension)],
-                                left_original_sample,
-                            ),
-                        )
-                    })
-                    .map(
-                        |(sample_number, (left_target_sample, left_original_sample))| {
-                            original[sample_number * original_dimension..]
-                                .chunks(original_dimension)
-                                .enumerate()
-                                .map(|(mut inner_sample_number, right_original_sample)| unsafe {
-                                    inner_sample_number += sample_number;
-                                    (
-                                        &mut (*wrapped_target.get())[(inner_sample_number
-                                            * target_dimension)
-                                            ..((inner_sample_number + 1) * target_dimension)],
-                                        right_original_sample,
-                                    )
-                                })
-                                .map(|(right_target_sample, right_original_sample)| {
-                                    let target_dot = dot(
-                                        left_target_sample.iter().copied(),
-                                        right_target_sample.iter().copied(),
-                                    );
-                                    let original_dot: Target = normal_dot(
-                                        left_original_sample,
-                                        right_original_sample,
-                                        &mean,
-                                        &variance,
-                                    )
-                                    .as_();
-                                    let mut variation = sigmoid(target_dot) - sigmoid(original_dot);
-                                    variation *= learning_rate;
-                                    left_target_sample
-                                        .iter_mut()
-                                        .zip(right_target_sample.iter_mut())
-                                        .for_each(|(left, right)| {
-                                            let left_tmp = *left;
-                                            *left -= *right * variation;
-                                            *right -= left_tmp * variation;
-                                        });
-                                    Ok(())
-                                })
-                                .collect::<Result<(), String>>()
-                        },
+        self.start_iterations(|_, learning_rate| {
+            // When the descent directions are batched, the optimizer applies the learning rate.
+            let variations_scale: Target = if self.is_batched() {
+                Target::one()
+            } else {
+                learning_rate.as_()
+            };
+
+            if self.is_batched() {
+                let current_target: &[Target] = unsafe { *wrapped_target.get() };
+                let (variations, losses) = deterministic_partitioned_sum(
+                    number_of_samples,
+                    current_target.len(),
+                    |samples, variations: &mut [Target]| {
+                        samples.fold(Target::zero(), |loss, sample_number| {
+                            let left_target_sample = &current_target[sample_number
+                                * target_dimension
+                                ..(sample_number + 1) * target_dimension];
+                            (sample_number..number_of_samples).fold(
+                                loss,
+                                |loss, inner_sample_number| {
+                                    let right_target_sample = &current_target[inner_sample_number
+                                        * target_dimension
+                                        ..(inner_sample_number + 1) * target_dimension];
+                                    let (own_variation, other_variation, pair_loss) =
+                                        get_variations_and_loss(
+                                            left_target_sample,
+                                            right_target_sample,
+                                            original_similarity(sample_number, inner_sample_number),
+                                            variations_scale,
+                                        );
+                                    (0..target_dimension).for_each(|dimension| {
+                                        let (left, right) = (
+                                            left_target_sample[dimension],
+                                            right_target_sample[dimension],
+                                        );
+                                        variations[sample_number * target_dimension + dimension] -=
+                                            left * own_variation + right * other_variation;
+                                        variations
+                                            [inner_sample_number * target_dimension + dimension] -=
+                                            right * own_variation + left * other_variation;
+                                    });
+                                    loss + pair_loss
+                                },
+                            )
+                        })
+                    },
+                );
+                optimizer.update(
+                    unsafe { &mut **wrapped_target.get() },
+                    &variations,
+                    learning_rate,
+                );
+                let total_loss = losses
+                    .into_iter()
+                    .fold(Target::zero(), |total_loss, loss| total_loss + loss);
+                return Ok((total_loss / number_of_pairs).to_f32().unwrap());
+            }
+
+            let total_loss = (0..number_of_samples)
+                .into_par_iter()
+                .map(|sample_number| unsafe {
+                    (
+                        sample_number,
+                        &mut (*wrapped_target.get())[(sample_number * target_dimension)
+                            ..((sample_number + 1) * target_dimension)],
                     )
-                    .collect::<Result<(), String>>()
-            })
-            .collect::<Result<(), String>>()
+                })
+                .map(|(sample_number, left_target_sample)| {
+                    (sample_number..number_of_samples)
+                        .map(|inner_sample_number| unsafe {
+                            (
+                                &mut (*wrapped_target.get())[(inner_sample_number
+                                    * target_dimension)
+                                    ..((inner_sample_number + 1) * target_dimension)],
+                                original_similarity(sample_number, inner_sample_number),
+                            )
+                        })
+                        .map(|(right_target_sample, original_similarity)| {
+                            let (own_variation, other_variation, loss) = get_variations_and_loss(
+                                left_target_sample,
+                                right_target_sample,
+                                original_similarity,
+                                variations_scale,
+                            );
+                            left_target_sample
+                                .iter_mut()
+                                .zip(right_target_sample.iter_mut())
+                                .for_each(|(left, right)| {
+                                    let left_tmp = *left;
+                                    *left -= left_tmp * own_variation + *right * other_variation;
+                                    *right -= *right * own_variation + left_tmp * other_variation;
+                                });
+                            loss
+                        })
+                        .sum::<Target>()
+                })
+                .sum::<Target>();
+            Ok((total_loss / number_of_pairs).to_f32().unwrap())
+        })
+    }
+}
+
+impl<S: Similarity, K: TargetSimilarity> DimensionalReduction for SigmoidDecomposition<S, K> {
+    fn fit_transform_matrix<Original, Target, M>(
+        &self,
+        target: &mut [Target],
+        target_dimension: usize,
+        original: &M,
+    ) -> Result<Vec<f32>, DimensionalReductionError>
+    where
+        Original: AsPrimitive<Target> + GenericFeature + Float,
+        Target: Float + GenericFeature,
+        M: OriginalMatrix<Original>,
+        usize: AsPrimitive<Original> + AsPrimitive<Target>,
+        f32: AsPrimitive<Target>,
+    {
+        validate_fit_transform_matrix_arguments(target, target_dimension, original)?;
+
+        self.initialize_target(target, target_dimension, original)?;
+
+        let statistics = original.get_statistics()?;
+
+        self.fit_transform_with_similarity(target, target_dimension, |left, right| {
+            self.similarity.similarity(
+                original.get_sample(left),
+                original.get_sample(right),
+                &statistics,
+            )
+        })
+    }
+}
+
+impl<S: Similarity, K: TargetSimilarity> PrecomputedDimensionalReduction
+    for SigmoidDecomposition<S, K>
+{
+    fn fit_transform_precomputed<Original, Target, M>(
+        &self,
+        target: &mut [Target],
+        target_dimension: usize,
+        precomputed: &PrecomputedMatrix<M>,
+    ) -> Result<Vec<f32>, DimensionalReductionError>
+    where
+        Original: AsPrimitive<Target> + GenericFeature + Float,
+        Target: Float + GenericFeature,
+        M: OriginalMatrix<Original>,
+        usize: AsPrimitive<Original> + AsPrimitive<Target>,
+        f32: AsPrimitive<Target>,
+    {
+        validate_fit_transform_matrix_arguments(
+            target,
+            target_dimension,
+            precomputed.get_matrix(),
+        )?;
+
+        self.get_initialization().initialize_precomputed(
+            target,
+            target_dimension,
+            precomputed,
+            self.get_random_state(),
+        )?;
+
+        self.fit_transform_with_similarity(target, target_dimension, |left, right| {
+            precomputed.get_similarity::<Original>(left, right)
+        })
     }
 }
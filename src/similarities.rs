@@ -0,0 +1,459 @@
+use crate::{
+    errors::DimensionalReductionError,
+    matrices::{FeatureStatistics, Sample},
+    traits::GenericFeature,
+    utils::sparse_normal_dot,
+};
+use num_traits::Float;
+use std::fmt::Debug;
+
+/// Similarity between the original samples, whose embeddings are trained
+/// so that the sigmoid of their dot product matches it.
+pub trait Similarity: Clone + Debug + Send + Sync {
+    /// Returns the similarity of the provided samples as a logit,
+    /// that is the value whose sigmoid is the probability of the samples being similar.
+    ///
+    /// # Arguments
+    /// * `left`: The first original sample.
+    /// * `right`: The second original sample.
+    /// * `statistics`: The mean and variance of the features of the original samples.
+    fn similarity<F>(
+        &self,
+        left: Sample<'_, F>,
+        right: Sample<'_, F>,
+        statistics: &FeatureStatistics<F>,
+    ) -> F
+    where
+        F: GenericFeature + Float;
+}
+
+/// Returns the logit of the provided probability, clamped away from zero and one
+/// so that identical and completely dissimilar samples have a finite logit.
+pub(crate) fn logit<F>(probability: F) -> F
+where
+    F: Float,
+{
+    let probability = probability.max(F::epsilon()).min(F::one() - F::epsilon());
+    (probability / (F::one() - probability)).ln()
+}
+
+/// Checks that the provided bandwidth is finite and strictly positive.
+fn validate_bandwidth(bandwidth: Option<f32>) -> Result<f32, DimensionalReductionError> {
+    let bandwidth = bandwidth.unwrap_or(1.0);
+    if !bandwidth.is_finite() || bandwidth <= 0.0 {
+        return Err(DimensionalReductionError::InvalidParameter {
+            parameter: "bandwidth",
+            value: bandwidth.to_string(),
+            expected: "a finite strictly positive value",
+        });
+    }
+    Ok(bandwidth)
+}
+
+/// Dot product of the standardized samples, used directly as logit.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct StandardizedDot;
+
+impl Similarity for StandardizedDot {
+    fn similarity<F>(
+        &self,
+        left: Sample<'_, F>,
+        right: Sample<'_, F>,
+        statistics: &FeatureStatistics<F>,
+    ) -> F
+    where
+        F: GenericFeature + Float,
+    {
+        sparse_normal_dot(&left, &right, statistics)
+    }
+}
+
+/// Cosine similarity of the centered samples, mapped from [-1, 1] to a probability.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Cosine;
+
+impl Similarity for Cosine {
+    fn similarity<F>(
+        &self,
+        left: Sample<'_, F>,
+        right: Sample<'_, F>,
+        statistics: &FeatureStatistics<F>,
+    ) -> F
+    where
+        F: GenericFeature + Float,
+    {
+        let mean = statistics.get_mean();
+        let (mut product, mut left_norm, mut right_norm) = (F::zero(), F::zero(), F::zero());
+        let mut visited_squared_mean = F::zero();
+        let number_of_features = left.zip_features(&right, |index, left, right| {
+            let (left, right) = (left - mean[index], right - mean[index]);
+            product += left * right;
+            left_norm += left * left;
+            right_norm += right * right;
+            visited_squared_mean += mean[index] * mean[index];
+        });
+        // Each feature missing from both samples is centered to minus its mean.
+        if number_of_features < left.get_dimension() {
+            let missing_squared_mean = statistics.get_squared_mean_sum() - visited_squared_mean;
+            product += missing_squared_mean;
+            left_norm += missing_squared_mean;
+            right_norm += missing_squared_mean;
+        }
+        let cosine = product / (left_norm * right_norm).sqrt().max(F::epsilon());
+        logit((F::one() + cosine) / (F::one() + F::one()))
+    }
+}
+
+/// Gaussian radial basis function of the Euclidean distance of the samples.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GaussianRBF {
+    bandwidth: f32,
+}
+
+impl GaussianRBF {
+    /// Returns a new Gaussian kernel.
+    ///
+    /// # Arguments
+    /// * `bandwidth`: The standard deviation of the kernel. By default, 1.0.
+    pub fn new(bandwidth: Option<f32>) -> Result<Self, DimensionalReductionError> {
+        Ok(Self {
+            bandwidth: validate_bandwidth(bandwidth)?,
+        })
+    }
+
+    /// Returns the standard deviation of the kernel.
+    pub fn get_bandwidth(&self) -> f32 {
+        self.bandwidth
+    }
+}
+
+impl Similarity for GaussianRBF {
+    fn similarity<F>(
+        &self,
+        left: Sample<'_, F>,
+        right: Sample<'_, F>,
+        _statistics: &FeatureStatistics<F>,
+    ) -> F
+    where
+        F: GenericFeature + Float,
+    {
+        let bandwidth = F::from(self.bandwidth).unwrap();
+        let mut squared_distance = F::zero();
+        left.zip_features(&right, |_, left, right| {
+            squared_distance += (left - right) * (left - right);
+        });
+        logit((-squared_distance / (bandwidth * bandwidth + bandwidth * bandwidth)).exp())
+    }
+}
+
+/// Laplacian kernel of the Manhattan distance of the samples.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Manhattan {
+    bandwidth: f32,
+}
+
+impl Manhattan {
+    /// Returns a new Manhattan kernel.
+    ///
+    /// # Arguments
+    /// * `bandwidth`: The distance at which the similarity decays by a factor e. By default, 1.0.
+    pub fn new(bandwidth: Option<f32>) -> Result<Self, DimensionalReductionError> {
+        Ok(Self {
+            bandwidth: validate_bandwidth(bandwidth)?,
+        })
+    }
+
+    /// Returns the bandwidth of the kernel.
+    pub fn get_bandwidth(&self) -> f32 {
+        self.bandwidth
+    }
+}
+
+impl Similarity for Manhattan {
+    fn similarity<F>(
+        &self,
+        left: Sample<'_, F>,
+        right: Sample<'_, F>,
+        _statistics: &FeatureStatistics<F>,
+    ) -> F
+    where
+        F: GenericFeature + Float,
+    {
+        let mut distance = F::zero();
+        left.zip_features(&right, |_, left, right| {
+            distance += (left - right).abs();
+        });
+        logit((-distance / F::from(self.bandwidth).unwrap()).exp())
+    }
+}
+
+/// Tanimoto, or Jaccard, similarity of binary fingerprints.
+///
+/// On non-binary samples it is the continuous Tanimoto coefficient.
+/// Two empty fingerprints are considered identical.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Tanimoto;
+
+impl Similarity for Tanimoto {
+    fn similarity<F>(
+        &self,
+        left: Sample<'_, F>,
+        right: Sample<'_, F>,
+        _statistics: &FeatureStatistics<F>,
+    ) -> F
+    where
+        F: GenericFeature + Float,
+    {
+        let (mut intersection, mut left_norm, mut right_norm) = (F::zero(), F::zero(), F::zero());
+        left.zip_features(&right, |_, left, right| {
+            intersection += left * right;
+            left_norm += left * left;
+            right_norm += right * right;
+        });
+        let union = left_norm + right_norm - intersection;
+        logit(if union > F::zero() {
+            intersection / union
+        } else {
+            F::one()
+        })
+    }
+}
+
+/// Fraction of the features on which the samples agree.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Hamming;
+
+impl Similarity for Hamming {
+    fn similarity<F>(
+        &self,
+        left: Sample<'_, F>,
+        right: Sample<'_, F>,
+        _statistics: &FeatureStatistics<F>,
+    ) -> F
+    where
+        F: GenericFeature + Float,
+    {
+        let mut differences = 0_usize;
+        left.zip_features(&right, |_, left, right| {
+            if left != right {
+                differences += 1;
+            }
+        });
+        logit(F::one() - F::from(differences).unwrap() / F::from(left.get_dimension()).unwrap())
+    }
+}
+
+/// Similarity chosen at runtime among the ones available.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum SimilarityKernel {
+    #[default]
+    StandardizedDot,
+    Cosine,
+    GaussianRBF(GaussianRBF),
+    Manhattan(Manhattan),
+    Tanimoto,
+    Hamming,
+}
+
+impl SimilarityKernel {
+    /// Returns the similarity with the provided name.
+    ///
+    /// # Arguments
+    /// * `name`: The name of the similarity, one of `standardized_dot`,
+    ///   `cosine`, `gaussian`, `manhattan`, `tanimoto` and `hamming`.
+    /// * `bandwidth`: The bandwidth of the `gaussian` and `manhattan` kernels. By default, 1.0.
+    pub fn from_name(
+        name: &str,
+        bandwidth: Option<f32>,
+    ) -> Result<Self, DimensionalReductionError> {
+        Ok(match name {
+            "standardized_dot" => Self::StandardizedDot,
+            "cosine" => Self::Cosine,
+            "gaussian" => Self::GaussianRBF(GaussianRBF::new(bandwidth)?),
+            "manhattan" => Self::Manhattan(Manhattan::new(bandwidth)?),
+            "tanimoto" => Self::Tanimoto,
+            "hamming" => Self::Hamming,
+            name => {
+                return Err(DimensionalReductionError::InvalidParameter {
+                    parameter: "similarity",
+                    value: name.to_string(),
+                    expected: concat!(
+                        "one of standardized_dot, cosine, gaussian, ",
+                        "manhattan, tanimoto and hamming"
+                    ),
+                })
+            }
+        })
+    }
+}
+
+impl Similarity for SimilarityKernel {
+    fn similarity<F>(
+        &self,
+        left: Sample<'_, F>,
+        right: Sample<'_, F>,
+        statistics: &FeatureStatistics<F>,
+    ) -> F
+    where
+        F: GenericFeature + Float,
+    {
+        match self {
+            Self::StandardizedDot => StandardizedDot.similarity(left, right, statistics),
+            Self::Cosine => Cosine.similarity(left, right, statistics),
+            Self::GaussianRBF(kernel) => kernel.similarity(left, right, statistics),
+            Self::Manhattan(kernel) => kernel.similarity(left, right, statistics),
+            Self::Tanimoto => Tanimoto.similarity(left, right, statistics),
+            Self::Hamming => Hamming.similarity(left, right, statistics),
+        }
+    }
+}
+
+/// Kind of the values of a matrix of precomputed comparisons between the samples.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum PrecomputedKind {
+    /// Affinities in the range [0, 1], that is the probability of the samples being similar.
+    #[default]
+    Affinity,
+    /// Non-negative distances, mapped to the affinities `exp(-distance / bandwidth)`.
+    Distance { bandwidth: f32 },
+}
+
+impl PrecomputedKind {
+    /// Returns the kind of precomputed values with the provided name.
+    ///
+    /// # Arguments
+    /// * `name`: The name of the kind, one of `affinity` and `distance`.
+    /// * `bandwidth`: The distance at which the affinity decays by a factor e. By default, 1.0.
+    pub fn from_name(
+        name: &str,
+        bandwidth: Option<f32>,
+    ) -> Result<Self, DimensionalReductionError> {
+        Ok(match name {
+            "affinity" => Self::Affinity,
+            "distance" => Self::Distance {
+                bandwidth: validate_bandwidth(bandwidth)?,
+            },
+            name => {
+                return Err(DimensionalReductionError::InvalidParameter {
+                    parameter: "precomputed kind",
+                    value: name.to_string(),
+                    expected: "one of affinity and distance",
+                })
+            }
+        })
+    }
+
+    /// Returns whether the provided precomputed value is in the domain of this kind.
+    pub fn is_valid<F>(&self, value: F) -> bool
+    where
+        F: Float,
+    {
+        match self {
+            Self::Affinity => value >= F::zero() && value <= F::one(),
+            Self::Distance { .. } => value >= F::zero(),
+        }
+    }
+
+    /// Returns the affinity corresponding to the provided precomputed value,
+    /// which is zero for the values missing from a sparse matrix.
+    pub fn get_affinity<F>(&self, value: Option<F>) -> F
+    where
+        F: Float,
+    {
+        match (self, value) {
+            (_, None) => F::zero(),
+            (Self::Affinity, Some(affinity)) => affinity,
+            (Self::Distance { bandwidth }, Some(distance)) => {
+                (-distance / F::from(*bandwidth).unwrap()).exp()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matrices::{CSRMatrix, DenseMatrix, OriginalMatrix};
+
+    #[test]
+    fn test_identical_samples_are_the_most_similar() {
+        let (left, right, far) = ([1.0_f64, 0.0, 1.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]);
+        let statistics = FeatureStatistics::new(vec![0.5, 0.5, 0.5], vec![1.0, 1.0, 1.0]);
+        for kernel in [
+            SimilarityKernel::Cosine,
+            SimilarityKernel::from_name("gaussian", Some(2.0)).unwrap(),
+            SimilarityKernel::from_name("manhattan", None).unwrap(),
+            SimilarityKernel::Tanimoto,
+            SimilarityKernel::Hamming,
+        ] {
+            let identical = kernel.similarity(left[..].into(), left[..].into(), &statistics);
+            let close = kernel.similarity(left[..].into(), right[..].into(), &statistics);
+            let distant = kernel.similarity(left[..].into(), far[..].into(), &statistics);
+            assert!(identical.is_finite() && distant.is_finite(), "{:?}", kernel);
+            assert!(identical > close && close > distant, "{:?}", kernel);
+        }
+    }
+
+    #[test]
+    fn test_sparse_samples_match_dense_ones() {
+        let dense = [
+            0.0_f64, 2.0, 0.0, 0.0, 1.0, //
+            1.0, 0.0, 0.0, 3.0, 0.0, //
+            0.0, 1.0, 0.0, 0.0, 4.0,
+        ];
+        let sparse = CSRMatrix::new(
+            vec![0, 2, 4, 6],
+            vec![1, 4, 0, 3, 1, 4],
+            vec![2.0, 1.0, 1.0, 3.0, 1.0, 4.0],
+            5,
+        )
+        .unwrap();
+        let dense = DenseMatrix::new(&dense, 5).unwrap();
+        let statistics = sparse.get_statistics().unwrap();
+        for kernel in [
+            SimilarityKernel::StandardizedDot,
+            SimilarityKernel::Cosine,
+            SimilarityKernel::from_name("gaussian", Some(2.0)).unwrap(),
+            SimilarityKernel::from_name("manhattan", None).unwrap(),
+            SimilarityKernel::Tanimoto,
+            SimilarityKernel::Hamming,
+        ] {
+            for left in 0..3 {
+                for right in 0..3 {
+                    let expected = kernel.similarity(
+                        dense.get_sample(left),
+                        dense.get_sample(right),
+                        &statistics,
+                    );
+                    for similarity in [
+                        kernel.similarity(
+                            sparse.get_sample(left),
+                            sparse.get_sample(right),
+                            &statistics,
+                        ),
+                        kernel.similarity(
+                            dense.get_sample(left),
+                            sparse.get_sample(right),
+                            &statistics,
+                        ),
+                    ] {
+                        assert!(
+                            (similarity - expected).abs() < 1e-9,
+                            "{:?}: {} {}",
+                            kernel,
+                            similarity,
+                            expected
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_similarity_parameters() {
+        assert!(SimilarityKernel::from_name("euclidean", None).is_err());
+        assert!(SimilarityKernel::from_name("gaussian", Some(0.0)).is_err());
+        assert!(SimilarityKernel::from_name("manhattan", Some(f32::NAN)).is_err());
+    }
+}
@@ -0,0 +1,365 @@
+use crate::{errors::DimensionalReductionError, traits::GenericFeature, utils::sigmoid};
+use num_traits::Float;
+use std::fmt::Debug;
+
+/// Similarity between the embedded samples, trained to match
+/// the similarity between the corresponding original samples.
+pub trait TargetSimilarity: Clone + Debug + Send + Sync {
+    /// Returns the probability of the provided embedded samples being similar,
+    /// followed by the coefficients of the left and right samples in the gradient
+    /// of the binary cross-entropy with respect to the left sample, divided by the
+    /// difference between the predicted and the expected probabilities.
+    ///
+    /// As the similarities are symmetric, the gradient with respect to
+    /// the right sample has the same coefficients with the samples swapped.
+    ///
+    /// # Arguments
+    /// * `left`: The first embedded sample.
+    /// * `right`: The second embedded sample.
+    fn similarity_and_gradient<F>(&self, left: &[F], right: &[F]) -> (F, F, F)
+    where
+        F: GenericFeature + Float;
+}
+
+/// Value added to the squared distances in the gradients of the distance-based
+/// similarities, so that they remain finite for overlapping samples.
+const DISTANCE_EPSILON: f64 = 1e-3;
+
+/// Returns the squared Euclidean distance of the provided samples.
+fn squared_distance<F>(left: &[F], right: &[F]) -> F
+where
+    F: GenericFeature + Float,
+{
+    left.iter()
+        .zip(right.iter())
+        .map(|(&left, &right)| (left - right) * (left - right))
+        .fold(F::zero(), |total, value| total + value)
+}
+
+/// Sigmoid of the dot product of the embedded samples.
+///
+/// As it only depends on the angle and norms of the samples,
+/// the resulting layouts tend to be radial.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SigmoidDot;
+
+impl TargetSimilarity for SigmoidDot {
+    fn similarity_and_gradient<F>(&self, left: &[F], right: &[F]) -> (F, F, F)
+    where
+        F: GenericFeature + Float,
+    {
+        let dot = left
+            .iter()
+            .zip(right.iter())
+            .map(|(&left, &right)| left * right)
+            .fold(F::zero(), |total, value| total + value);
+        (sigmoid(dot), F::zero(), F::one())
+    }
+}
+
+/// Student-t kernel `1 / (1 + d^2)` of the distance of the embedded samples, as in t-SNE.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct StudentT;
+
+impl TargetSimilarity for StudentT {
+    fn similarity_and_gradient<F>(&self, left: &[F], right: &[F]) -> (F, F, F)
+    where
+        F: GenericFeature + Float,
+    {
+        let squared_distance = squared_distance(left, right);
+        let coefficient =
+            (F::one() + F::one()) / (squared_distance + F::from(DISTANCE_EPSILON).unwrap());
+        (
+            (F::one() + squared_distance).recip(),
+            -coefficient,
+            coefficient,
+        )
+    }
+}
+
+/// Kernel `1 / (1 + a d^(2b))` of the distance of the embedded samples, as in UMAP.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct UMAPCurve {
+    a: f32,
+    b: f32,
+}
+
+impl UMAPCurve {
+    /// Returns a new UMAP kernel with the provided parameters.
+    ///
+    /// # Arguments
+    /// * `a`: The scale of the distances.
+    /// * `b`: The exponent of the distances.
+    pub fn new(a: f32, b: f32) -> Result<Self, DimensionalReductionError> {
+        for (parameter, value) in [("a", a), ("b", b)] {
+            if !value.is_finite() || value <= 0.0 {
+                return Err(DimensionalReductionError::InvalidParameter {
+                    parameter,
+                    value: value.to_string(),
+                    expected: "a finite strictly positive value",
+                });
+            }
+        }
+        Ok(Self { a, b })
+    }
+
+    /// Returns the UMAP kernel whose parameters best fit a similarity that is one
+    /// up to the minimum distance and then decays exponentially with the provided spread.
+    ///
+    /// # Arguments
+    /// * `minimum_distance`: The distance under which the samples are identical. By default, 0.1.
+    /// * `spread`: The scale of the exponential decay of the similarity. By default, 1.0.
+    pub fn from_distances(
+        minimum_distance: Option<f32>,
+        spread: Option<f32>,
+    ) -> Result<Self, DimensionalReductionError> {
+        let spread = spread.unwrap_or(1.0);
+        if !spread.is_finite() || spread <= 0.0 {
+            return Err(DimensionalReductionError::InvalidParameter {
+                parameter: "spread",
+                value: spread.to_string(),
+                expected: "a finite strictly positive value",
+            });
+        }
+        let minimum_distance = minimum_distance.unwrap_or(0.1);
+        if !minimum_distance.is_finite() || minimum_distance < 0.0 || minimum_distance > spread {
+            return Err(DimensionalReductionError::InvalidParameter {
+                parameter: "minimum distance",
+                value: minimum_distance.to_string(),
+                expected: "a finite non-negative value not greater than the spread",
+            });
+        }
+        let (a, b) = fit_umap_curve(minimum_distance as f64, spread as f64);
+        Self::new(a as f32, b as f32)
+    }
+
+    /// Returns the scale of the distances.
+    pub fn get_a(&self) -> f32 {
+        self.a
+    }
+
+    /// Returns the exponent of the distances.
+    pub fn get_b(&self) -> f32 {
+        self.b
+    }
+}
+
+impl Default for UMAPCurve {
+    fn default() -> Self {
+        Self::from_distances(None, None).unwrap()
+    }
+}
+
+/// Number of distances used to fit the parameters of the UMAP kernel.
+const UMAP_CURVE_SAMPLES: usize = 300;
+/// Number of Levenberg-Marquardt iterations used to fit the parameters of the UMAP kernel.
+const UMAP_CURVE_ITERATIONS: usize = 200;
+
+/// Returns the parameters a and b of the UMAP kernel fitted with the Levenberg-Marquardt
+/// method, in the least squares sense, to the similarity with the provided minimum
+/// distance and spread, on the distances up to three times the spread.
+fn fit_umap_curve(minimum_distance: f64, spread: f64) -> (f64, f64) {
+    let points = (0..UMAP_CURVE_SAMPLES)
+        .map(|i| {
+            let distance = 3.0 * spread * i as f64 / (UMAP_CURVE_SAMPLES - 1) as f64;
+            let similarity = if distance < minimum_distance {
+                1.0
+            } else {
+                (-(distance - minimum_distance) / spread).exp()
+            };
+            (distance, similarity)
+        })
+        .collect::<Vec<(f64, f64)>>();
+
+    // Returns the squared error of the provided parameters.
+    let get_error = |a: f64, b: f64| {
+        points
+            .iter()
+            .map(|&(distance, similarity)| {
+                let residual = 1.0 / (1.0 + a * distance.powf(2.0 * b)) - similarity;
+                residual * residual
+            })
+            .sum::<f64>()
+    };
+
+    let (mut a, mut b) = (1.0, 1.0);
+    let mut error = get_error(a, b);
+    let mut damping = 1e-3;
+
+    for _ in 0..UMAP_CURVE_ITERATIONS {
+        // We accumulate the normal equations J^T J and J^T r.
+        let (mut jaa, mut jab, mut jbb, mut ja, mut jb) = (0.0, 0.0, 0.0, 0.0, 0.0);
+        points
+            .iter()
+            .filter(|&&(distance, _)| distance > 0.0)
+            .for_each(|&(distance, similarity)| {
+                let power = distance.powf(2.0 * b);
+                let denominator = 1.0 + a * power;
+                let residual = 1.0 / denominator - similarity;
+                let derivative_a = -power / (denominator * denominator);
+                let derivative_b = derivative_a * a * 2.0 * distance.ln();
+                jaa += derivative_a * derivative_a;
+                jab += derivative_a * derivative_b;
+                jbb += derivative_b * derivative_b;
+                ja += derivative_a * residual;
+                jb += derivative_b * residual;
+            });
+
+        let (damped_aa, damped_bb) = (jaa * (1.0 + damping), jbb * (1.0 + damping));
+        let determinant = damped_aa * damped_bb - jab * jab;
+        if determinant.abs() < f64::EPSILON {
+            break;
+        }
+        let step_a = (damped_bb * ja - jab * jb) / determinant;
+        let step_b = (damped_aa * jb - jab * ja) / determinant;
+        let (new_a, new_b) = (a - step_a, b - step_b);
+
+        if new_a > 0.0 && new_b > 0.0 {
+            let new_error = get_error(new_a, new_b);
+            if new_error < error {
+                let improvement = error - new_error;
+                (a, b, error) = (new_a, new_b, new_error);
+                damping /= 10.0;
+                if improvement < f64::EPSILON * error.max(1.0) {
+                    break;
+                }
+                continue;
+            }
+        }
+        damping *= 10.0;
+    }
+
+    (a, b)
+}
+
+impl TargetSimilarity for UMAPCurve {
+    fn similarity_and_gradient<F>(&self, left: &[F], right: &[F]) -> (F, F, F)
+    where
+        F: GenericFeature + Float,
+    {
+        let (a, b) = (F::from(self.a).unwrap(), F::from(self.b).unwrap());
+        let squared_distance = squared_distance(left, right);
+        let coefficient = (b + b) / (squared_distance + F::from(DISTANCE_EPSILON).unwrap());
+        (
+            (F::one() + a * squared_distance.powf(b)).recip(),
+            -coefficient,
+            coefficient,
+        )
+    }
+}
+
+/// Gaussian kernel `exp(-d^2)` of the distance of the embedded samples.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Gaussian;
+
+impl TargetSimilarity for Gaussian {
+    fn similarity_and_gradient<F>(&self, left: &[F], right: &[F]) -> (F, F, F)
+    where
+        F: GenericFeature + Float,
+    {
+        let squared_distance = squared_distance(left, right);
+        let coefficient = (F::one() + F::one())
+            / (-(-squared_distance).exp_m1() + F::from(DISTANCE_EPSILON).unwrap());
+        ((-squared_distance).exp(), -coefficient, coefficient)
+    }
+}
+
+/// Target similarity chosen at runtime among the ones available.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum TargetSimilarityKernel {
+    #[default]
+    SigmoidDot,
+    StudentT,
+    UMAPCurve(UMAPCurve),
+    Gaussian,
+}
+
+impl TargetSimilarityKernel {
+    /// Returns the target similarity with the provided name.
+    ///
+    /// # Arguments
+    /// * `name`: The name of the target similarity, one of `sigmoid_dot`,
+    ///   `student_t`, `umap` and `gaussian`.
+    /// * `minimum_distance`: The minimum distance of the `umap` kernel. By default, 0.1.
+    /// * `spread`: The spread of the `umap` kernel. By default, 1.0.
+    pub fn from_name(
+        name: &str,
+        minimum_distance: Option<f32>,
+        spread: Option<f32>,
+    ) -> Result<Self, DimensionalReductionError> {
+        Ok(match name {
+            "sigmoid_dot" => Self::SigmoidDot,
+            "student_t" => Self::StudentT,
+            "umap" => Self::UMAPCurve(UMAPCurve::from_distances(minimum_distance, spread)?),
+            "gaussian" => Self::Gaussian,
+            name => {
+                return Err(DimensionalReductionError::InvalidParameter {
+                    parameter: "target similarity",
+                    value: name.to_string(),
+                    expected: "one of sigmoid_dot, student_t, umap and gaussian",
+                })
+            }
+        })
+    }
+}
+
+impl TargetSimilarity for TargetSimilarityKernel {
+    fn similarity_and_gradient<F>(&self, left: &[F], right: &[F]) -> (F, F, F)
+    where
+        F: GenericFeature + Float,
+    {
+        match self {
+            Self::SigmoidDot => SigmoidDot.similarity_and_gradient(left, right),
+            Self::StudentT => StudentT.similarity_and_gradient(left, right),
+            Self::UMAPCurve(kernel) => kernel.similarity_and_gradient(left, right),
+            Self::Gaussian => Gaussian.similarity_and_gradient(left, right),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::binary_cross_entropy;
+
+    #[test]
+    fn test_umap_curve_fit() {
+        // Parameters found by the reference implementation for the default distances.
+        let curve = UMAPCurve::default();
+        assert!((curve.get_a() - 1.577).abs() < 0.05, "{:?}", curve);
+        assert!((curve.get_b() - 0.895).abs() < 0.02, "{:?}", curve);
+        assert!(UMAPCurve::from_distances(Some(2.0), Some(1.0)).is_err());
+    }
+
+    #[test]
+    fn test_gradients_match_finite_differences() {
+        let (left, right, expected) = ([0.3_f64, -0.7], [-0.2, 0.4], 0.35);
+        for kernel in [
+            TargetSimilarityKernel::SigmoidDot,
+            TargetSimilarityKernel::StudentT,
+            TargetSimilarityKernel::from_name("umap", None, None).unwrap(),
+            TargetSimilarityKernel::Gaussian,
+        ] {
+            let (predicted, own, other) = kernel.similarity_and_gradient(&left, &right);
+            for dimension in 0..left.len() {
+                let step = 1e-6;
+                let mut shifted = left;
+                shifted[dimension] += step;
+                let loss = |sample: &[f64]| {
+                    binary_cross_entropy(expected, kernel.similarity_and_gradient(sample, &right).0)
+                };
+                let numerical = (loss(&shifted) - loss(&left)) / step;
+                let analytical =
+                    (predicted - expected) * (own * left[dimension] + other * right[dimension]);
+                // The distance-based gradients are slightly damped by the distance epsilon.
+                assert!(
+                    (numerical - analytical).abs() < 0.01 * numerical.abs().max(1.0),
+                    "{:?}: {} != {}",
+                    kernel,
+                    numerical,
+                    analytical
+                );
+            }
+        }
+    }
+}
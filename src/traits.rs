@@ -1,9 +1,19 @@
 use crate::{
     basic_decomposition::BasicDecomposition,
     basic_iterative_decomposition::BasicIterativeDecomposition,
+    errors::DimensionalReductionError,
+    fitted_decomposition::FittedDecomposition,
+    graph::Graph,
+    initialization::Initialization,
+    matrices::{DenseMatrix, OriginalMatrix, PrecomputedMatrix},
+    optimizers::Optimizer,
+    schedules::LearningRateSchedule,
+    similarities::Similarity,
+    target_similarities::TargetSimilarity,
+    utils::deterministic_partitioned_sum,
 };
+use indicatif::ProgressIterator;
 use indicatif::{ProgressBar, ProgressStyle};
-use indicatif::{ProgressBarIter, ProgressIterator};
 use num_traits::{AsPrimitive, Bounded, Float, One, Zero};
 use rayon::prelude::*;
 use std::fmt::{Debug, Display};
@@ -62,17 +72,132 @@ impl<T> GenericFeature for T where
 }
 
 pub trait DimensionalReduction {
+    /// Fits the model on the provided original samples, writing their embedding
+    /// in the provided target slice and returning the loss history of the fit.
+    ///
+    /// Models that are not iterative return an empty loss history.
     fn fit_transform<Original, Target>(
         &self,
         target: &mut [Target],
         target_dimension: usize,
         original: &[Original],
         original_dimension: usize,
-    ) -> Result<(), String>
+    ) -> Result<Vec<f32>, DimensionalReductionError>
     where
         Original: num_traits::AsPrimitive<Target> + GenericFeature + Float,
         Target: Float + GenericFeature,
         usize: AsPrimitive<Original> + AsPrimitive<Target>,
+        f32: AsPrimitive<Target>,
+    {
+        self.fit_transform_matrix(
+            target,
+            target_dimension,
+            &DenseMatrix::new(original, original_dimension)?,
+        )
+    }
+
+    /// Fits the model on the samples of the provided original matrix, either dense
+    /// or sparse, writing their embedding in the provided target slice and returning
+    /// the loss history of the fit.
+    ///
+    /// Models that are not iterative return an empty loss history.
+    fn fit_transform_matrix<Original, Target, M>(
+        &self,
+        target: &mut [Target],
+        target_dimension: usize,
+        original: &M,
+    ) -> Result<Vec<f32>, DimensionalReductionError>
+    where
+        Original: num_traits::AsPrimitive<Target> + GenericFeature + Float,
+        Target: Float + GenericFeature,
+        M: OriginalMatrix<Original>,
+        usize: AsPrimitive<Original> + AsPrimitive<Target>,
+        f32: AsPrimitive<Target>;
+
+    /// Returns the decomposition fitted on the provided original samples.
+    ///
+    /// # Arguments
+    /// * `target_dimension`: The dimensionality of the embedding to learn.
+    /// * `original`: The original samples to fit the model on.
+    /// * `original_dimension`: The dimensionality of the original samples.
+    fn fit<Original, Target>(
+        &self,
+        target_dimension: usize,
+        original: &[Original],
+        original_dimension: usize,
+    ) -> Result<FittedDecomposition<Self, Original, Target>, DimensionalReductionError>
+    where
+        Self: Sized + Clone,
+        Original: num_traits::AsPrimitive<Target> + GenericFeature + Float,
+        Target: Float + GenericFeature,
+        usize: AsPrimitive<Original> + AsPrimitive<Target>,
+        f32: AsPrimitive<Target>,
+    {
+        if original_dimension == 0 {
+            return Err(DimensionalReductionError::ZeroDimension { name: "original" });
+        }
+
+        let mut target =
+            vec![Target::zero(); original.len() / original_dimension * target_dimension];
+
+        let loss_history =
+            self.fit_transform(&mut target, target_dimension, original, original_dimension)?;
+
+        FittedDecomposition::new(
+            self.clone(),
+            original.to_vec(),
+            original_dimension,
+            target,
+            target_dimension,
+            loss_history,
+        )
+    }
+}
+
+/// Decompositions that can be fitted on precomputed affinities or distances
+/// between the samples instead of on their features.
+pub trait PrecomputedDimensionalReduction: DimensionalReduction {
+    /// Fits the model on the provided precomputed matrix, either dense or sparse,
+    /// writing the embedding of its samples in the provided target slice and
+    /// returning the loss history of the fit.
+    ///
+    /// # Arguments
+    /// * `target`: The slice where to write the embedding.
+    /// * `target_dimension`: The dimensionality of the embedding.
+    /// * `precomputed`: The square matrix of the affinities or distances between the samples.
+    fn fit_transform_precomputed<Original, Target, M>(
+        &self,
+        target: &mut [Target],
+        target_dimension: usize,
+        precomputed: &PrecomputedMatrix<M>,
+    ) -> Result<Vec<f32>, DimensionalReductionError>
+    where
+        Original: num_traits::AsPrimitive<Target> + GenericFeature + Float,
+        Target: Float + GenericFeature,
+        M: OriginalMatrix<Original>,
+        usize: AsPrimitive<Original> + AsPrimitive<Target>,
+        f32: AsPrimitive<Target>;
+}
+
+/// Decompositions that can embed the nodes of a graph instead of original samples.
+pub trait GraphDimensionalReduction {
+    /// Fits the model on the provided graph, writing the embedding of its nodes
+    /// in the provided target slice and returning the loss history of the fit.
+    ///
+    /// # Arguments
+    /// * `target`: The slice where to write the embedding.
+    /// * `target_dimension`: The dimensionality of the embedding.
+    /// * `graph`: The graph whose nodes are embedded.
+    fn fit_transform_graph<Weight, Target>(
+        &self,
+        target: &mut [Target],
+        target_dimension: usize,
+        graph: &Graph<Weight>,
+    ) -> Result<Vec<f32>, DimensionalReductionError>
+    where
+        Weight: num_traits::AsPrimitive<Target> + GenericFeature + Float,
+        Target: Float + GenericFeature,
+        usize: AsPrimitive<Weight> + AsPrimitive<Target>,
         f32: AsPrimitive<Target>;
 }
 
@@ -129,8 +254,123 @@ pub trait IterativeDecomposition: Decomposition {
         }
     }
 
-    fn start_iterations(&self) -> ProgressBarIter<std::ops::Range<usize>> {
-        (0..self.get_iterations()).progress_with(self.get_loading_bar())
+    fn get_initialization(&self) -> Initialization {
+        self.get_iterative_basic_decomposition().initialization
+    }
+
+    /// Writes the initial embedding in the target slice, according to
+    /// the initialization strategy of the model.
+    ///
+    /// # Arguments
+    /// * `target`: The slice where to write the initial embedding.
+    /// * `target_dimension`: The dimensionality of the embedding.
+    /// * `original`: The original samples.
+    fn initialize_target<Original, Target, M>(
+        &self,
+        target: &mut [Target],
+        target_dimension: usize,
+        original: &M,
+    ) -> Result<(), DimensionalReductionError>
+    where
+        Original: AsPrimitive<Target> + GenericFeature + Float,
+        Target: Float + GenericFeature,
+        M: OriginalMatrix<Original>,
+        usize: AsPrimitive<Original> + AsPrimitive<Target>,
+        f32: AsPrimitive<Target>,
+    {
+        self.get_initialization().initialize(
+            target,
+            target_dimension,
+            original,
+            self.get_random_state(),
+        )
+    }
+
+    /// Returns the learning rate schedule used when none is provided.
+    fn get_default_learning_rate_schedule(&self) -> LearningRateSchedule {
+        LearningRateSchedule::default()
+    }
+
+    fn get_learning_rate_schedule(&self) -> LearningRateSchedule {
+        self.get_iterative_basic_decomposition()
+            .learning_rate_schedule
+            .unwrap_or_else(|| self.get_default_learning_rate_schedule())
+    }
+
+    fn get_tolerance(&self) -> Option<f32> {
+        self.get_iterative_basic_decomposition().tolerance
+    }
+
+    fn get_patience(&self) -> usize {
+        self.get_iterative_basic_decomposition().patience
+    }
+
+    fn is_deterministic(&self) -> bool {
+        self.get_iterative_basic_decomposition().deterministic
+    }
+
+    fn get_optimizer(&self) -> Optimizer {
+        self.get_iterative_basic_decomposition().optimizer
+    }
+
+    /// Returns whether the descent directions of each epoch are computed from the embedding
+    /// at its start and then applied by the optimizer, rather than as soon as each pair of
+    /// samples is processed.
+    fn is_batched(&self) -> bool {
+        self.is_deterministic() || self.get_optimizer().is_stateful()
+    }
+
+    /// Runs the epochs of the model, returning the loss history.
+    ///
+    /// When a tolerance is provided, the iterations are stopped as soon as the
+    /// relative change of the loss stays below the tolerance for `patience`
+    /// consecutive epochs, therefore the length of the loss history is the
+    /// number of epochs that were actually run.
+    ///
+    /// # Arguments
+    /// * `epoch`: Closure running the provided epoch with the learning rate
+    ///   of its schedule, and returning its loss.
+    fn start_iterations<F>(&self, mut epoch: F) -> Result<Vec<f32>, DimensionalReductionError>
+    where
+        F: FnMut(usize, f32) -> Result<f32, DimensionalReductionError>,
+    {
+        let loading_bar = self.get_loading_bar();
+        let mut loss_history: Vec<f32> = Vec::with_capacity(self.get_iterations());
+        let mut epochs_without_improvement = 0;
+
+        for iteration in (0..self.get_iterations()).progress_with(loading_bar.clone()) {
+            let learning_rate = self.get_learning_rate()
+                * self
+                    .get_learning_rate_schedule()
+                    .get_factor(iteration, self.get_iterations());
+            let loss = epoch(iteration, learning_rate)?;
+            loading_bar.set_message(format!(" (loss {:.6})", loss));
+
+            if let (Some(tolerance), Some(&previous_loss)) =
+                (self.get_tolerance(), loss_history.last())
+            {
+                let relative_change =
+                    (previous_loss - loss).abs() / previous_loss.abs().max(f32::EPSILON);
+                if relative_change <= tolerance {
+                    epochs_without_improvement += 1;
+                } else {
+                    epochs_without_improvement = 0;
+                }
+            }
+
+            loss_history.push(loss);
+
+            if epochs_without_improvement >= self.get_patience() {
+                loading_bar.finish_with_message(format!(
+                    " (converged after {} epochs, loss {:.6})",
+                    loss_history.len(),
+                    loss
+                ));
+                break;
+            }
+        }
+
+        Ok(loss_history)
     }
 }
 
@@ -143,47 +383,71 @@ where
     }
 }
 
+/// Decomposition whose embedding follows a similarity between the original samples.
+pub trait SimilarityDecomposition {
+    type Similarity: Similarity;
+    type TargetSimilarity: TargetSimilarity;
+
+    /// Returns the similarity used to compare the original samples.
+    fn get_similarity(&self) -> &Self::Similarity;
+
+    /// Returns the similarity used to compare the embedded samples.
+    fn get_target_similarity(&self) -> &Self::TargetSimilarity;
+}
+
 pub trait RandomUniformInitialization {
     fn random_init(&mut self, random_state: u64);
 }
 
+pub trait RandomGaussianInitialization {
+    fn random_gaussian_init(&mut self, random_state: u64, scale: f32);
+}
+
 pub trait MatrixSum<F> {
-    fn matrix_sum(&self, dimensionality: usize) -> Result<Vec<F>, String>;
+    fn matrix_sum(&self, dimensionality: usize) -> Result<Vec<F>, DimensionalReductionError>;
 }
 
 impl<'a, F> MatrixSum<F> for &[F]
 where
     F: GenericFeature,
 {
-    fn matrix_sum(&self, dimensionality: usize) -> Result<Vec<F>, String> {
+    fn matrix_sum(&self, dimensionality: usize) -> Result<Vec<F>, DimensionalReductionError> {
+        if dimensionality == 0 {
+            return Err(DimensionalReductionError::ZeroDimension { name: "matrix" });
+        }
+
         if self.len() % dimensionality != 0 {
-            return Err(format!(
-                concat!(
-                    "The provided dimensionality {} is not compatible ",
-                    "with the length of this object {}."
-                ),
-                dimensionality,
-                self.len(),
-            ));
+            return Err(DimensionalReductionError::IncompatibleDimension {
+                name: "matrix",
+                length: self.len(),
+                dimension: dimensionality,
+            });
         }
 
-        Ok(self
-            .par_chunks(dimensionality)
-            .map(|slice| slice.to_vec())
-            .reduce(
-                || vec![F::zero(); dimensionality],
-                |mut left, right| {
-                    left.iter_mut().zip(right.into_iter()).for_each(|(l, r)| {
-                        *l += r;
+        // The partial sums are reduced in a fixed order, so that
+        // the result does not depend on the number of threads.
+        Ok(deterministic_partitioned_sum(
+            self.len() / dimensionality,
+            dimensionality,
+            |samples, partial_sum: &mut [F]| {
+                self[samples.start * dimensionality..samples.end * dimensionality]
+                    .chunks(dimensionality)
+                    .for_each(|slice| {
+                        partial_sum
+                            .iter_mut()
+                            .zip(slice.iter().copied())
+                            .for_each(|(l, r)| {
+                                *l += r;
+                            });
                     });
-                    left
-                },
-            ))
+            },
+        )
+        .0)
     }
 }
 
 pub trait MatrixMean<F> {
-    fn matrix_mean(&self, dimensionality: usize) -> Result<Vec<F>, String>;
+    fn matrix_mean(&self, dimensionality: usize) -> Result<Vec<F>, DimensionalReductionError>;
 }
 
 impl<'a, F> MatrixMean<F> for &[F]
@@ -191,9 +455,9 @@ where
     F: GenericFeature,
     usize: AsPrimitive<F>,
 {
-    fn matrix_mean(&self, dimensionality: usize) -> Result<Vec<F>, String> {
+    fn matrix_mean(&self, dimensionality: usize) -> Result<Vec<F>, DimensionalReductionError> {
         if self.is_empty() {
-            return Err("The provided object is empty".to_string());
+            return Err(DimensionalReductionError::EmptyInput);
         }
 
         let mut matrix_sum = self.matrix_sum(dimensionality)?;
@@ -208,27 +472,34 @@ where
 }
 
 pub trait MatrixMinMax<F> {
-    fn matrix_min_max(&self, dimensionality: usize) -> Result<(Vec<F>, Vec<F>), String>;
+    fn matrix_min_max(
+        &self,
+        dimensionality: usize,
+    ) -> Result<(Vec<F>, Vec<F>), DimensionalReductionError>;
 }
 
 impl<'a, F> MatrixMinMax<F> for &[F]
 where
     F: GenericFeature,
 {
-    fn matrix_min_max(&self, dimensionality: usize) -> Result<(Vec<F>, Vec<F>), String> {
+    fn matrix_min_max(
+        &self,
+        dimensionality: usize,
+    ) -> Result<(Vec<F>, Vec<F>), DimensionalReductionError> {
         if self.is_empty() {
-            return Err("The provided object is empty".to_string());
+            return Err(DimensionalReductionError::EmptyInput);
+        }
+
+        if dimensionality == 0 {
+            return Err(DimensionalReductionError::ZeroDimension { name: "matrix" });
         }
 
         if self.len() % dimensionality != 0 {
-            return Err(format!(
-                concat!(
-                    "The provided dimensionality {} is not compatible ",
-                    "with the length of this object {}."
-                ),
-                dimensionality,
-                self.len(),
-            ));
+            return Err(DimensionalReductionError::IncompatibleDimension {
+                name: "matrix",
+                length: self.len(),
+                dimension: dimensionality,
+            });
         }
 
         Ok(self
@@ -291,7 +562,7 @@ where
 }
 
 pub trait MatrixVariance<F> {
-    fn matrix_var(&self, dimensionality: usize) -> Result<Vec<F>, String>;
+    fn matrix_var(&self, dimensionality: usize) -> Result<Vec<F>, DimensionalReductionError>;
 }
 
 impl<'a, F> MatrixVariance<F> for &[F]
@@ -299,30 +570,26 @@ where
     F: GenericFeature,
     usize: AsPrimitive<F>,
 {
-    fn matrix_var(&self, dimensionality: usize) -> Result<Vec<F>, String> {
+    fn matrix_var(&self, dimensionality: usize) -> Result<Vec<F>, DimensionalReductionError> {
         let matrix_mean = self.matrix_mean(dimensionality)?;
 
-        let mut unnormalized_variance = self
-            .par_chunks(dimensionality)
-            .map(|slice| {
-                let mut vec = slice.to_vec();
-                vec.iter_mut()
-                    .zip(matrix_mean.iter().copied())
-                    .for_each(|(v, m)| {
-                        let delta = *v - m;
-                        *v = delta * delta;
-                    });
-                vec
-            })
-            .reduce(
-                || vec![F::zero(); dimensionality],
-                |mut left, right| {
-                    left.iter_mut().zip(right.into_iter()).for_each(|(l, r)| {
-                        *l += r;
+        let (mut unnormalized_variance, _) = deterministic_partitioned_sum(
+            self.len() / dimensionality,
+            dimensionality,
+            |samples, partial_sum: &mut [F]| {
+                self[samples.start * dimensionality..samples.end * dimensionality]
+                    .chunks(dimensionality)
+                    .for_each(|slice| {
+                        partial_sum
+                            .iter_mut()
+                            .zip(slice.iter().copied().zip(matrix_mean.iter().copied()))
+                            .for_each(|(l, (v, m))| {
+                                let delta = v - m;
+                                *l += delta * delta;
+                            });
                     });
-                    left
-                },
-            );
+            },
+        );
 
         let number_of_samples: F = (self.len() / dimensionality).as_();
 
@@ -334,8 +601,28 @@ where
     }
 }
 
+pub trait MatrixIsFinite {
+    fn matrix_is_finite(&self, name: &'static str) -> Result<(), DimensionalReductionError>;
+}
+
+impl<F> MatrixIsFinite for &[F]
+where
+    F: GenericFeature + Float,
+{
+    fn matrix_is_finite(&self, name: &'static str) -> Result<(), DimensionalReductionError> {
+        match self.par_iter().position_first(|value| !value.is_finite()) {
+            Some(index) => Err(DimensionalReductionError::NonFiniteValue {
+                name,
+                index,
+                value: self[index].to_f64().unwrap(),
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
 pub trait MatrixStandardDeviation<F> {
-    fn matrix_std(&self, dimensionality: usize) -> Result<Vec<F>, String>;
+    fn matrix_std(&self, dimensionality: usize) -> Result<Vec<F>, DimensionalReductionError>;
 }
 
 impl<'a, F> MatrixStandardDeviation<F> for &[F]
@@ -343,7 +630,7 @@ where
     F: GenericFeature + Float,
     usize: AsPrimitive<F>,
 {
-    fn matrix_std(&self, dimensionality: usize) -> Result<Vec<F>, String> {
+    fn matrix_std(&self, dimensionality: usize) -> Result<Vec<F>, DimensionalReductionError> {
         let mut variance = self.matrix_var(dimensionality)?;
 
         variance.iter_mut().for_each(|v| {
@@ -361,7 +648,10 @@ where
 {
     fn random_init(&mut self, random_state: u64) {
         self.par_iter_mut().enumerate().for_each(|(i, weight)| {
-            *weight = (2.0_f32 * random_f32(splitmix64(random_state + random_state * i as u64))
+            *weight = (2.0_f32
+                * random_f32(splitmix64(
+                    random_state.wrapping_add(random_state.wrapping_mul(i as u64)),
+                ))
                 - 1.0_f32)
                 .as_();
         });
@@ -379,3 +669,22 @@ where
         self.as_mut().random_init(random_state);
     }
 }
+
+impl<'a, F> RandomGaussianInitialization for &'a mut [F]
+where
+    F: Send + Sync + Copy + 'static,
+    f32: AsPrimitive<F>,
+{
+    fn random_gaussian_init(&mut self, random_state: u64, scale: f32) {
+        self.par_iter_mut().enumerate().for_each(|(i, weight)| {
+            // We use the Box-Muller transform on two uniform samples.
+            let first_seed =
+                splitmix64(random_state.wrapping_add(random_state.wrapping_mul(2 * i as u64)));
+            let second_seed =
+                splitmix64(random_state.wrapping_add(random_state.wrapping_mul(2 * i as u64 + 1)));
+            let radius = (-2.0_f32 * random_f32(first_seed).max(f32::MIN_POSITIVE).ln()).sqrt();
+            let angle = 2.0_f32 * std::f32::consts::PI * random_f32(second_seed);
+            *weight = (scale * radius * angle.cos()).as_();
+        });
+    }
+}
@@ -0,0 +1,558 @@
+use crate::traits::*;
+use crate::{
+    barnes_hut_sigmoid_decomposition::{
+        get_bits_per_layer, BarnesHutGrid, GradientGrid, Pdep, PortablePdep,
+    },
+    basic_iterative_decomposition::BasicIterativeDecomposition,
+    errors::DimensionalReductionError,
+    knn::NeighbourSearch,
+    matrices::{DenseMatrix, OriginalMatrix},
+    traits::{DimensionalReduction, GenericFeature, IterativeDecomposition},
+    utils::{validate_fit_transform_matrix_arguments, SparseRows},
+};
+use num_traits::{AsPrimitive, Float};
+use rayon::prelude::*;
+
+#[cfg(target_arch = "x86_64")]
+use crate::barnes_hut_sigmoid_decomposition::Bmi2Pdep;
+
+/// Momentum used during the early exaggeration phase.
+const INITIAL_MOMENTUM: f32 = 0.5;
+/// Smallest gain of the adaptive per-coordinate learning rates.
+const MINIMUM_GAIN: f32 = 0.01;
+/// Number of steps of the binary search of the bandwidth of each sample.
+const PERPLEXITY_SEARCH_STEPS: usize = 100;
+/// Tolerance on the entropy of the conditional distribution of each sample.
+const PERPLEXITY_TOLERANCE: f64 = 1e-5;
+/// Number of samples per leaf targeted by the default depth of the grid.
+const SAMPLES_PER_LEAF: usize = 8;
+
+/// t-distributed Stochastic Neighbor Embedding.
+///
+/// The affinities of the original samples are computed on their nearest neighbours,
+/// calibrating the bandwidth of the Gaussian kernel of each sample so that the
+/// perplexity of its conditional distribution matches the provided one. The
+/// repulsive forces between the embedded samples are approximated with the averages
+/// of the cells of a Barnes-Hut grid, so that each epoch takes O(n log n) time.
+///
+/// As every epoch computes the gradient from the embedding at its start and reduces
+/// it in a fixed order, the results do not depend on the number of threads.
+#[derive(Clone)]
+pub struct TSNEDecomposition {
+    decomposition: BasicIterativeDecomposition,
+    perplexity: f32,
+    early_exaggeration: f32,
+    early_exaggeration_iterations: Option<usize>,
+    momentum: f32,
+    theta: f32,
+    depth: Option<usize>,
+    neighbour_search: NeighbourSearch,
+}
+
+impl TSNEDecomposition {
+    /// Returns a new t-SNE model.
+    ///
+    /// # Arguments
+    /// * `decomposition`: The basic iterative decomposition parameters. The learning rate
+    ///   is relative to the number of samples divided by four times the early exaggeration,
+    ///   with a minimum of 50, so that the default of 1.0 is generally appropriate.
+    ///   The optimizer must be SGD, as t-SNE uses its own momentum and gains.
+    /// * `perplexity`: The effective number of neighbours of each sample. By default, 30.
+    /// * `early_exaggeration`: The factor multiplying the affinities of the original samples
+    ///   in the first epochs, so that the clusters form before being spread. By default, 12.
+    /// * `early_exaggeration_iterations`: The number of epochs with early exaggeration.
+    ///   By default, a quarter of the epochs.
+    /// * `momentum`: The momentum of the updates after the early exaggeration, which
+    ///   uses a momentum of 0.5. By default, 0.8.
+    /// * `theta`: The Barnes-Hut opening criterion, where smaller values are more accurate
+    ///   and slower. By default, 0.5.
+    /// * `depth`: The depth of the grid. By default, the smallest depth with enough leafs
+    ///   for a handful of samples per leaf.
+    /// * `neighbour_search`: The search of the nearest neighbours of the original samples.
+    ///   By default, the exact search.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        decomposition: BasicIterativeDecomposition,
+        perplexity: Option<f32>,
+        early_exaggeration: Option<f32>,
+        early_exaggeration_iterations: Option<usize>,
+        momentum: Option<f32>,
+        theta: Option<f32>,
+        depth: Option<usize>,
+        neighbour_search: Option<NeighbourSearch>,
+    ) -> Result<Self, DimensionalReductionError> {
+        if decomposition.optimizer.is_stateful() {
+            return Err(DimensionalReductionError::InvalidParameter {
+                parameter: "optimizer",
+                value: format!("{:?}", decomposition.optimizer),
+                expected: "sgd, as t-SNE uses its own momentum and gains",
+            });
+        }
+
+        let perplexity = perplexity.unwrap_or(30.0);
+        if !perplexity.is_finite() || perplexity <= 0.0 {
+            return Err(DimensionalReductionError::InvalidParameter {
+                parameter: "perplexity",
+                value: perplexity.to_string(),
+                expected: "a finite strictly positive value",
+            });
+        }
+
+        let early_exaggeration = early_exaggeration.unwrap_or(12.0);
+        if !early_exaggeration.is_finite() || early_exaggeration < 1.0 {
+            return Err(DimensionalReductionError::InvalidParameter {
+                parameter: "early exaggeration",
+                value: early_exaggeration.to_string(),
+                expected: "a finite value not smaller than one",
+            });
+        }
+
+        let momentum = momentum.unwrap_or(0.8);
+        if !(0.0..1.0).contains(&momentum) {
+            return Err(DimensionalReductionError::InvalidParameter {
+                parameter: "momentum",
+                value: momentum.to_string(),
+                expected: "in the range [0, 1)",
+            });
+        }
+
+        let theta = theta.unwrap_or(0.5);
+        if !theta.is_finite() || theta < 0.0 {
+            return Err(DimensionalReductionError::InvalidParameter {
+                parameter: "theta",
+                value: theta.to_string(),
+                expected: "a finite non-negative value",
+            });
+        }
+
+        Ok(Self {
+            decomposition,
+            perplexity,
+            early_exaggeration,
+            early_exaggeration_iterations,
+            momentum,
+            theta,
+            depth,
+            neighbour_search: neighbour_search.unwrap_or_default(),
+        })
+    }
+}
+
+/// Returns the conditional probabilities of the neighbours of a sample given
+/// their squared distances, with the bandwidth whose perplexity is the provided one.
+fn calibrate_conditional_probabilities(squared_distances: &[f64], perplexity: f64) -> Vec<f64> {
+    // Shifting the distances does not change the normalized probabilities,
+    // but avoids their underflow for far away samples.
+    let minimum_distance = squared_distances
+        .iter()
+        .copied()
+        .fold(f64::INFINITY, f64::min);
+    let target_entropy = perplexity.ln();
+    let (mut beta, mut beta_min, mut beta_max) = (1.0, f64::NEG_INFINITY, f64::INFINITY);
+    let mut probabilities = vec![0.0; squared_distances.len()];
+
+    for _ in 0..PERPLEXITY_SEARCH_STEPS {
+        probabilities
+            .iter_mut()
+            .zip(squared_distances.iter())
+            .for_each(|(probability, &distance)| {
+                *probability = (-(distance - minimum_distance) * beta).exp();
+            });
+        let total = probabilities.iter().sum::<f64>();
+        let weighted_distances = probabilities
+            .iter()
+            .zip(squared_distances.iter())
+            .map(|(&probability, &distance)| probability * (distance - minimum_distance))
+            .sum::<f64>();
+        let entropy = total.ln() + beta * weighted_distances / total;
+        let difference = entropy - target_entropy;
+
+        if difference.abs() <= PERPLEXITY_TOLERANCE {
+            break;
+        }
+        if difference > 0.0 {
+            beta_min = beta;
+            beta = if beta_max.is_infinite() {
+                beta * 2.0
+            } else {
+                (beta + beta_max) / 2.0
+            };
+        } else {
+            beta_max = beta;
+            beta = if beta_min.is_infinite() {
+                beta / 2.0
+            } else {
+                (beta + beta_min) / 2.0
+            };
+        }
+    }
+
+    let total = probabilities.iter().sum::<f64>();
+    probabilities.iter_mut().for_each(|probability| {
+        *probability /= total;
+    });
+    probabilities
+}
+
+/// Returns the symmetric joint affinities of the original samples, computed
+/// on the nearest `3 * perplexity` neighbours of each sample.
+fn compute_affinities<Original, Target, M>(
+    original: &M,
+    perplexity: f32,
+    neighbour_search: NeighbourSearch,
+) -> Result<SparseRows<Target>, DimensionalReductionError>
+where
+    Original: GenericFeature + Float,
+    Target: Float + GenericFeature,
+    M: OriginalMatrix<Original>,
+{
+    let number_of_samples = original.get_number_of_samples();
+    let number_of_neighbours = ((3.0 * perplexity) as usize).clamp(1, number_of_samples - 1);
+
+    let nearest_neighbours = neighbour_search.search(original, number_of_neighbours)?;
+
+    let rows = (0..number_of_samples)
+        .into_par_iter()
+        .map(|sample_number| {
+            let squared_distances = nearest_neighbours
+                .get_distances(sample_number)
+                .iter()
+                .map(|distance| distance.powi(2))
+                .collect::<Vec<f64>>();
+            nearest_neighbours
+                .get_neighbours(sample_number)
+                .iter()
+                .copied()
+                .zip(calibrate_conditional_probabilities(
+                    &squared_distances,
+                    perplexity as f64,
+                ))
+                .collect::<Vec<(usize, f64)>>()
+        })
+        .collect::<Vec<Vec<(usize, f64)>>>();
+
+    // We symmetrize the conditional probabilities, summing the two
+    // directions of each pair and normalizing them to sum to one.
+    let normalization = 2.0 * number_of_samples as f64;
+    let entries = rows
+        .iter()
+        .enumerate()
+        .flat_map(|(sample_number, row)| {
+            row.iter().flat_map(move |&(neighbour, probability)| {
+                [
+                    (sample_number, neighbour, probability / normalization),
+                    (neighbour, sample_number, probability / normalization),
+                ]
+            })
+        })
+        .collect::<Vec<(usize, usize, f64)>>();
+
+    Ok(SparseRows::from_entries(
+        number_of_samples,
+        entries,
+        |left, right| left + right,
+    ))
+}
+
+impl IterativeDecomposition for TSNEDecomposition {
+    fn get_iterative_basic_decomposition(&self) -> &BasicIterativeDecomposition {
+        &self.decomposition
+    }
+}
+
+impl DimensionalReduction for TSNEDecomposition {
+    fn fit_transform_matrix<Original, Target, M>(
+        &self,
+        target: &mut [Target],
+        target_dimension: usize,
+        original: &M,
+    ) -> Result<Vec<f32>, DimensionalReductionError>
+    where
+        Original: AsPrimitive<Target> + GenericFeature + Float,
+        Target: Float + GenericFeature,
+        M: OriginalMatrix<Original>,
+        usize: AsPrimitive<Original> + AsPrimitive<Target>,
+        f32: AsPrimitive<Target>,
+    {
+        validate_fit_transform_matrix_arguments(target, target_dimension, original)?;
+
+        let number_of_samples = target.len() / target_dimension;
+        if number_of_samples < 2 {
+            return Err(DimensionalReductionError::InvalidParameter {
+                parameter: "number of samples",
+                value: number_of_samples.to_string(),
+                expected: "at least two samples",
+            });
+        }
+
+        if self.perplexity >= number_of_samples as f32 {
+            return Err(DimensionalReductionError::InvalidParameter {
+                parameter: "perplexity",
+                value: self.perplexity.to_string(),
+                expected: "smaller than the number of samples",
+            });
+        }
+
+        self.initialize_target(target, target_dimension, original)?;
+
+        let affinities: SparseRows<Target> =
+            compute_affinities(original, self.perplexity, self.neighbour_search)?;
+
+        let branching_factor = 1 << get_bits_per_layer(target_dimension);
+        let depth = self.depth.unwrap_or_else(|| {
+            let mut depth = 1;
+            let mut number_of_leafs = branching_factor;
+            while number_of_leafs * SAMPLES_PER_LEAF < number_of_samples {
+                depth += 1;
+                number_of_leafs *= branching_factor;
+            }
+            depth
+        });
+        #[cfg(target_arch = "x86_64")]
+        if let Some(pdep) = Bmi2Pdep::detect() {
+            return self.fit_transform_with_grid(
+                GradientGrid::new(pdep, depth, target_dimension, 1)?,
+                target,
+                target_dimension,
+                &affinities,
+            );
+        }
+        self.fit_transform_with_grid(
+            GradientGrid::new(PortablePdep, depth, target_dimension, 1)?,
+            target,
+            target_dimension,
+            &affinities,
+        )
+    }
+}
+
+impl TSNEDecomposition {
+    /// Fits the embedding approximating the repulsive forces with the cells of the provided grid.
+    ///
+    /// # Arguments
+    /// * `grid`: The grid of the embedded samples, with a single original feature.
+    /// * `target`: The initialized embedding.
+    /// * `target_dimension`: The dimensionality of the embedding.
+    /// * `affinities`: The symmetric joint probabilities of the nearest neighbours.
+    fn fit_transform_with_grid<Target, P>(
+        &self,
+        mut grid: GradientGrid<Target, Target, P>,
+        target: &mut [Target],
+        target_dimension: usize,
+        affinities: &SparseRows<Target>,
+    ) -> Result<Vec<f32>, DimensionalReductionError>
+    where
+        Target: Float + GenericFeature,
+        usize: AsPrimitive<Target>,
+        f32: AsPrimitive<Target>,
+        P: Pdep,
+    {
+        let number_of_samples = target.len() / target_dimension;
+        // The grid is only used to approximate the embedded samples, so
+        // a single constant original feature is provided for each sample.
+        let placeholder_values = vec![Target::zero(); number_of_samples];
+        let placeholder_original = DenseMatrix::new(&placeholder_values, 1)?;
+
+        let early_exaggeration_iterations = self
+            .early_exaggeration_iterations
+            .unwrap_or(self.get_iterations() / 4);
+        // The learning rate is relative to the number of samples.
+        let learning_rate_scale =
+            (number_of_samples as f32 / self.early_exaggeration / 4.0).max(50.0);
+        let theta: Target = self.theta.as_();
+        let four: Target = 4.0_f32.as_();
+        let minimum_gain: Target = MINIMUM_GAIN.as_();
+        let gain_increase: Target = 0.2_f32.as_();
+        let gain_decay: Target = 0.8_f32.as_();
+
+        let mut updates = vec![Target::zero(); target.len()];
+        let mut gains = vec![Target::one(); target.len()];
+        let mut gradients = vec![Target::zero(); target.len()];
+
+        self.start_iterations(|iteration, learning_rate| {
+            let learning_rate: Target = (learning_rate * learning_rate_scale).as_();
+            let (exaggeration, momentum): (Target, Target) =
+                if iteration < early_exaggeration_iterations {
+                    (self.early_exaggeration.as_(), INITIAL_MOMENTUM.as_())
+                } else {
+                    (Target::one(), self.momentum.as_())
+                };
+
+            grid.prepare(target, &placeholder_original)?;
+            let current_target: &[Target] = target;
+
+            // We compute the unnormalized repulsive forces and the
+            // contribution of each sample to the normalization.
+            let normalizations = gradients
+                .par_chunks_mut(target_dimension)
+                .zip(current_target.par_chunks(target_dimension))
+                .enumerate()
+                .map(|(sample_number, (repulsion, point))| {
+                    repulsion.iter_mut().for_each(|value| {
+                        *value = Target::zero();
+                    });
+                    let mut normalization = Target::zero();
+                    let mut add_repulsion = |other: &[Target], population: Target| {
+                        let squared_distance = point
+                            .iter()
+                            .zip(other.iter())
+                            .map(|(&left, &right)| (left - right) * (left - right))
+                            .fold(Target::zero(), |total, value| total + value);
+                        let kernel = (Target::one() + squared_distance).recip();
+                        normalization += population * kernel;
+                        repulsion
+                            .iter_mut()
+                            .zip(point.iter().zip(other.iter()))
+                            .for_each(|(value, (&left, &right))| {
+                                *value += population * kernel * kernel * (left - right);
+                            });
+                    };
+                    let (far_away_cells, near_leafs) =
+                        grid.get_far_away_cells_and_near_leafs(sample_number, point, Some(theta));
+                    far_away_cells.into_iter().for_each(|cell| {
+                        let (cell_target_average, _, population) = grid.get_cell_properties(cell);
+                        add_repulsion(cell_target_average, population.as_());
+                    });
+                    grid.iter_leafs_samples(&near_leafs)
+                        .filter(|&other_sample_number| other_sample_number != sample_number)
+                        .for_each(|other_sample_number| {
+                            add_repulsion(
+                                &current_target[other_sample_number * target_dimension
+                                    ..(other_sample_number + 1) * target_dimension],
+                                Target::one(),
+                            );
+                        });
+                    normalization
+                })
+                .collect::<Vec<Target>>();
+            let normalization = normalizations
+                .into_iter()
+                .fold(Target::zero(), |total, value| total + value)
+                .max(Target::min_positive_value());
+
+            // We add the attractive forces, and compute the Kullback-Leibler
+            // divergence of the joint distributions without exaggeration.
+            let losses = gradients
+                .par_chunks_mut(target_dimension)
+                .zip(current_target.par_chunks(target_dimension))
+                .enumerate()
+                .map(|(sample_number, (gradient, point))| {
+                    gradient.iter_mut().for_each(|value| {
+                        *value = -*value / normalization;
+                    });
+                    let (neighbours, values) = affinities.get_row(sample_number);
+                    neighbours.iter().zip(values.iter()).fold(
+                        Target::zero(),
+                        |loss, (&neighbour, &affinity)| {
+                            let other = &current_target
+                                [neighbour * target_dimension..(neighbour + 1) * target_dimension];
+                            let squared_distance = point
+                                .iter()
+                                .zip(other.iter())
+                                .map(|(&left, &right)| (left - right) * (left - right))
+                                .fold(Target::zero(), |total, value| total + value);
+                            let kernel = (Target::one() + squared_distance).recip();
+                            gradient
+                                .iter_mut()
+                                .zip(point.iter().zip(other.iter()))
+                                .for_each(|(value, (&left, &right))| {
+                                    *value += exaggeration * affinity * kernel * (left - right);
+                                });
+                            let probability =
+                                (kernel / normalization).max(Target::min_positive_value());
+                            loss + affinity * (affinity / probability).ln()
+                        },
+                    )
+                })
+                .collect::<Vec<Target>>();
+
+            // We update the embedding with momentum and adaptive gains.
+            target
+                .par_iter_mut()
+                .zip(updates.par_iter_mut())
+                .zip(gains.par_iter_mut().zip(gradients.par_iter()))
+                .for_each(|((value, update), (gain, &gradient))| {
+                    let gradient = four * gradient;
+                    *gain = if (gradient > Target::zero()) != (*update > Target::zero()) {
+                        *gain + gain_increase
+                    } else {
+                        *gain * gain_decay
+                    }
+                    .max(minimum_gain);
+                    *update = momentum * *update - learning_rate * *gain * gradient;
+                    *value += *update;
+                });
+
+            // We keep the embedding centered in the origin.
+            let mean = (&*target).matrix_mean(target_dimension)?;
+            target.par_chunks_mut(target_dimension).for_each(|sample| {
+                sample
+                    .iter_mut()
+                    .zip(mean.iter())
+                    .for_each(|(value, &mean)| {
+                        *value -= mean;
+                    });
+            });
+
+            Ok(losses
+                .into_iter()
+                .fold(Target::zero(), |total, loss| total + loss)
+                .to_f32()
+                .unwrap())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basic_iterative_decomposition::IterativeParameters;
+
+    #[test]
+    fn test_calibrated_perplexity() {
+        let squared_distances = (0..90)
+            .map(|i| (i as f64 * 0.37).sin().abs() * 10.0 + i as f64 * 0.1)
+            .collect::<Vec<f64>>();
+        for perplexity in [2.0, 10.0, 30.0] {
+            let probabilities = calibrate_conditional_probabilities(&squared_distances, perplexity);
+            let entropy = -probabilities
+                .iter()
+                .filter(|&&probability| probability > 0.0)
+                .map(|&probability| probability * probability.ln())
+                .sum::<f64>();
+            assert!((probabilities.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+            assert!((entropy.exp() - perplexity).abs() < 1e-3 * perplexity);
+        }
+    }
+
+    #[test]
+    fn test_single_sample_is_rejected() {
+        let model = TSNEDecomposition::new(
+            BasicIterativeDecomposition::new(
+                "t-SNE test",
+                IterativeParameters {
+                    iterations: Some(1),
+                    verbose: Some(false),
+                    ..Default::default()
+                },
+            )
+            .unwrap(),
+            Some(0.5),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let mut target = vec![0.0_f32; 2];
+        assert!(matches!(
+            model.fit_transform(&mut target, 2, &[1.0_f32, 2.0, 3.0], 3),
+            Err(DimensionalReductionError::InvalidParameter {
+                parameter: "number of samples",
+                ..
+            })
+        ));
+    }
+}
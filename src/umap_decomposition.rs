@@ -0,0 +1,482 @@
+use crate::traits::*;
+use crate::{
+    basic_iterative_decomposition::BasicIterativeDecomposition,
+    errors::DimensionalReductionError,
+    knn::NeighbourSearch,
+    matrices::OriginalMatrix,
+    optimizers::OptimizerState,
+    schedules::{LearningRateDecay, LearningRateSchedule},
+    target_similarities::{TargetSimilarity, UMAPCurve},
+    traits::{DimensionalReduction, GenericFeature, IterativeDecomposition},
+    utils::{
+        binary_cross_entropy, deterministic_partitioned_sum,
+        validate_fit_transform_matrix_arguments, DataRaceAware, SparseRows,
+    },
+};
+use num_traits::{AsPrimitive, Float};
+use rayon::prelude::*;
+use vec_rand::splitmix64;
+
+/// Number of steps of the binary search of the bandwidth of each sample.
+const BANDWIDTH_SEARCH_STEPS: usize = 64;
+/// Tolerance on the sum of the membership strengths of each sample.
+const BANDWIDTH_TOLERANCE: f64 = 1e-5;
+/// Smallest bandwidth of a sample, relative to the mean distance of its neighbours.
+const MINIMUM_BANDWIDTH_SCALE: f64 = 1e-3;
+/// Largest absolute value of each coordinate of the gradient of a pair of samples.
+const GRADIENT_CLIP: f32 = 4.0;
+
+/// Uniform Manifold Approximation and Projection.
+///
+/// The original samples are connected to their nearest neighbours with membership
+/// strengths decaying from the distance of their nearest neighbour, calibrating the
+/// bandwidth of each sample so that its strengths sum to the logarithm in base two of
+/// the number of neighbours. The resulting fuzzy simplicial set is symmetrized with
+/// the fuzzy union, and its edges are sampled in each epoch proportionally to
+/// their strength, each followed by the provided number of negative samples.
+///
+/// Unless another schedule is provided, the learning rate decays linearly to zero over the epochs.
+#[derive(Clone)]
+pub struct UMAPDecomposition<K = UMAPCurve> {
+    decomposition: BasicIterativeDecomposition,
+    target_similarity: K,
+    number_of_neighbours: usize,
+    negative_samples: usize,
+    repulsion_strength: f32,
+    neighbour_search: NeighbourSearch,
+}
+
+impl<K: TargetSimilarity> UMAPDecomposition<K> {
+    /// Returns a new UMAP model.
+    ///
+    /// # Arguments
+    /// * `decomposition`: The basic iterative decomposition parameters. By default,
+    ///   the learning rate decays linearly to zero over the epochs.
+    /// * `target_similarity`: The similarity between the embedded samples,
+    ///   usually the UMAP curve fitted on the desired minimum distance and spread.
+    /// * `number_of_neighbours`: The number of neighbours of each sample in the
+    ///   fuzzy simplicial set. By default, 15.
+    /// * `negative_samples`: The number of negative samples for each sampled edge. By default, 5.
+    /// * `repulsion_strength`: The weight of the negative samples in the loss. By default, 1.0.
+    /// * `neighbour_search`: The search of the nearest neighbours of the original samples.
+    ///   By default, the exact search.
+    pub fn new(
+        decomposition: BasicIterativeDecomposition,
+        target_similarity: K,
+        number_of_neighbours: Option<usize>,
+        negative_samples: Option<usize>,
+        repulsion_strength: Option<f32>,
+        neighbour_search: Option<NeighbourSearch>,
+    ) -> Result<Self, DimensionalReductionError> {
+        let number_of_neighbours = number_of_neighbours.unwrap_or(15);
+        if number_of_neighbours == 0 {
+            return Err(DimensionalReductionError::InvalidParameter {
+                parameter: "number of neighbours",
+                value: number_of_neighbours.to_string(),
+                expected: "strictly positive",
+            });
+        }
+
+        let repulsion_strength = repulsion_strength.unwrap_or(1.0);
+        if !repulsion_strength.is_finite() || repulsion_strength < 0.0 {
+            return Err(DimensionalReductionError::InvalidParameter {
+                parameter: "repulsion strength",
+                value: repulsion_strength.to_string(),
+                expected: "a finite non-negative value",
+            });
+        }
+
+        Ok(Self {
+            decomposition,
+            target_similarity,
+            number_of_neighbours,
+            negative_samples: negative_samples.unwrap_or(5),
+            repulsion_strength,
+            neighbour_search: neighbour_search.unwrap_or_default(),
+        })
+    }
+}
+
+/// Returns the membership strengths of the neighbours of a sample given their
+/// distances, sorted by increasing distance, and the number of neighbours.
+fn calibrate_membership_strengths(distances: &[f64], number_of_neighbours: usize) -> Vec<f64> {
+    // The nearest neighbour at a strictly positive distance is always fully connected,
+    // so that each sample is connected to at least one other sample.
+    let rho = distances
+        .iter()
+        .copied()
+        .find(|&distance| distance > 0.0)
+        .unwrap_or(0.0);
+    let target = (number_of_neighbours as f64).log2();
+    let (mut sigma, mut sigma_min, mut sigma_max) = (1.0, 0.0, f64::INFINITY);
+
+    for _ in 0..BANDWIDTH_SEARCH_STEPS {
+        let total = distances
+            .iter()
+            .map(|&distance| (-(distance - rho).max(0.0) / sigma).exp())
+            .sum::<f64>();
+        if (total - target).abs() <= BANDWIDTH_TOLERANCE {
+            break;
+        }
+        if total > target {
+            sigma_max = sigma;
+            sigma = (sigma_min + sigma_max) / 2.0;
+        } else {
+            sigma_min = sigma;
+            sigma = if sigma_max.is_infinite() {
+                sigma * 2.0
+            } else {
+                (sigma_min + sigma_max) / 2.0
+            };
+        }
+    }
+
+    if !distances.is_empty() {
+        let mean_distance = distances.iter().sum::<f64>() / distances.len() as f64;
+        sigma = sigma.max(MINIMUM_BANDWIDTH_SCALE * mean_distance);
+    }
+
+    distances
+        .iter()
+        .map(|&distance| (-(distance - rho).max(0.0) / sigma).exp())
+        .collect()
+}
+
+/// Returns the fuzzy simplicial set of the original samples, computed on
+/// the provided number of nearest neighbours of each sample.
+fn compute_fuzzy_simplicial_set<Original, Target, M>(
+    original: &M,
+    number_of_neighbours: usize,
+    neighbour_search: NeighbourSearch,
+) -> Result<SparseRows<Target>, DimensionalReductionError>
+where
+    Original: GenericFeature + Float,
+    Target: Float + GenericFeature,
+    M: OriginalMatrix<Original>,
+{
+    let number_of_samples = original.get_number_of_samples();
+    let number_of_neighbours = number_of_neighbours.min(number_of_samples - 1);
+
+    let nearest_neighbours = neighbour_search.search(original, number_of_neighbours)?;
+
+    let rows = (0..number_of_samples)
+        .into_par_iter()
+        .map(|sample_number| {
+            nearest_neighbours
+                .get_neighbours(sample_number)
+                .iter()
+                .copied()
+                .zip(calibrate_membership_strengths(
+                    nearest_neighbours.get_distances(sample_number),
+                    number_of_neighbours,
+                ))
+                .collect::<Vec<(usize, f64)>>()
+        })
+        .collect::<Vec<Vec<(usize, f64)>>>();
+
+    // We symmetrize the membership strengths with the fuzzy union, that is
+    // the probability of at least one of the two directed edges existing.
+    let entries = rows
+        .iter()
+        .enumerate()
+        .flat_map(|(sample_number, row)| {
+            row.iter().flat_map(move |&(neighbour, strength)| {
+                [
+                    (sample_number, neighbour, strength),
+                    (neighbour, sample_number, strength),
+                ]
+            })
+        })
+        .collect::<Vec<(usize, usize, f64)>>();
+
+    Ok(SparseRows::from_entries(
+        number_of_samples,
+        entries,
+        |left, right| left + right - left * right,
+    ))
+}
+
+impl<K> IterativeDecomposition for UMAPDecomposition<K> {
+    fn get_iterative_basic_decomposition(&self) -> &BasicIterativeDecomposition {
+        &self.decomposition
+    }
+
+    fn get_default_learning_rate_schedule(&self) -> LearningRateSchedule {
+        LearningRateSchedule::new(LearningRateDecay::Linear, None)
+    }
+}
+
+impl<K: TargetSimilarity> DimensionalReduction for UMAPDecomposition<K> {
+    fn fit_transform_matrix<Original, Target, M>(
+        &self,
+        target: &mut [Target],
+        target_dimension: usize,
+        original: &M,
+    ) -> Result<Vec<f32>, DimensionalReductionError>
+    where
+        Original: AsPrimitive<Target> + GenericFeature + Float,
+        Target: Float + GenericFeature,
+        M: OriginalMatrix<Original>,
+        usize: AsPrimitive<Original> + AsPrimitive<Target>,
+        f32: AsPrimitive<Target>,
+    {
+        validate_fit_transform_matrix_arguments(target, target_dimension, original)?;
+
+        let number_of_samples = target.len() / target_dimension;
+        if number_of_samples < 2 {
+            return Err(DimensionalReductionError::InvalidParameter {
+                parameter: "number of samples",
+                value: number_of_samples.to_string(),
+                expected: "at least two samples",
+            });
+        }
+
+        self.initialize_target(target, target_dimension, original)?;
+
+        let graph: SparseRows<Target> = compute_fuzzy_simplicial_set(
+            original,
+            self.number_of_neighbours,
+            self.neighbour_search,
+        )?;
+        let maximal_strength = graph
+            .get_values()
+            .iter()
+            .copied()
+            .fold(Target::zero(), Target::max)
+            .to_f64()
+            .unwrap();
+
+        // We wrap the features object in an unsafe cell so
+        // it may be shared among threads.
+        let mut optimizer = OptimizerState::new(self.get_optimizer(), target.len());
+        let wrapped_target = DataRaceAware::from(target);
+
+        let repulsion_strength: Target = self.repulsion_strength.as_();
+        let mut random_state = splitmix64(self.get_random_state());
+
+        // Returns the variations of the provided pair of samples and its loss,
+        // given whether they are connected in the fuzzy simplicial set.
+        let get_variations_and_loss =
+            |left_sample: &[Target], right_sample: &[Target], connected: bool| {
+                let (target_probability, own_coefficient, other_coefficient) = self
+                    .target_similarity
+                    .similarity_and_gradient(left_sample, right_sample);
+                let (expected_probability, weight) = if connected {
+                    (Target::one(), Target::one())
+                } else {
+                    (Target::zero(), repulsion_strength)
+                };
+                let variation = (target_probability - expected_probability) * weight;
+                (
+                    own_coefficient * variation,
+                    other_coefficient * variation,
+                    weight * binary_cross_entropy(expected_probability, target_probability),
+                )
+            };
+        let clip: Target = GRADIENT_CLIP.as_();
+
+        self.start_iterations(|iteration, learning_rate| {
+            random_state = splitmix64(random_state);
+            let epoch = (iteration + 1) as f64;
+            // When the descent directions are batched, the optimizer applies the learning rate.
+            let variations_scale: Target = if self.is_batched() {
+                Target::one()
+            } else {
+                learning_rate.as_()
+            };
+            // Returns the clipped gradient of a coordinate, scaled by the learning rate.
+            let clip_gradient = |gradient: Target| gradient.max(-clip).min(clip) * variations_scale;
+
+            // Returns the samples paired in this epoch with the provided sample, and
+            // whether they are its neighbours or negative samples. Each edge is sampled
+            // once every `maximal_strength / strength` epochs.
+            let get_pairs = |sample_number: usize| {
+                let offset = graph.get_row_offset(sample_number);
+                let (neighbours, strengths) = graph.get_row(sample_number);
+                neighbours
+                    .iter()
+                    .zip(strengths.iter())
+                    .enumerate()
+                    .filter(move |&(_, (_, &strength))| {
+                        let frequency = strength.to_f64().unwrap() / maximal_strength;
+                        (epoch * frequency).floor() > ((epoch - 1.0) * frequency).floor()
+                    })
+                    .flat_map(move |(edge_number, (&neighbour, _))| {
+                        let edge_number = (offset + edge_number) as u64;
+                        std::iter::once((neighbour, true)).chain(
+                            (0..self.negative_samples as u64)
+                                .map(move |negative_sample_number| {
+                                    let index = edge_number
+                                        .wrapping_mul(self.negative_samples as u64)
+                                        .wrapping_add(negative_sample_number);
+                                    (
+                                        (splitmix64(
+                                            random_state
+                                                .wrapping_add(index.wrapping_mul(random_state)),
+                                        ) as usize)
+                                            % number_of_samples,
+                                        false,
+                                    )
+                                })
+                                .filter(move |&(other, _)| other != sample_number),
+                        )
+                    })
+            };
+
+            if self.is_batched() {
+                let current_target: &[Target] = unsafe { *wrapped_target.get() };
+                let (variations, losses) = deterministic_partitioned_sum(
+                    number_of_samples,
+                    current_target.len(),
+                    |samples, variations: &mut [Target]| {
+                        samples.fold(
+                            (Target::zero(), Target::zero()),
+                            |(total_loss, number_of_pairs), sample_number| {
+                                get_pairs(sample_number).fold(
+                                    (total_loss, number_of_pairs),
+                                    |(total_loss, number_of_pairs), (other, connected)| {
+                                        let left_sample = &current_target[sample_number
+                                            * target_dimension
+                                            ..(sample_number + 1) * target_dimension];
+                                        let right_sample = &current_target[other * target_dimension
+                                            ..(other + 1) * target_dimension];
+                                        let (own_variation, other_variation, loss) =
+                                            get_variations_and_loss(
+                                                left_sample,
+                                                right_sample,
+                                                connected,
+                                            );
+                                        left_sample
+                                            .iter()
+                                            .zip(right_sample.iter())
+                                            .enumerate()
+                                            .for_each(|(dimension, (&left, &right))| {
+                                                variations[sample_number * target_dimension
+                                                    + dimension] -= clip_gradient(
+                                                    left * own_variation + right * other_variation,
+                                                );
+                                                // Negative samples only repel the sample they are drawn for.
+                                                if connected {
+                                                    variations
+                                                        [other * target_dimension + dimension] -=
+                                                        clip_gradient(
+                                                            right * own_variation
+                                                                + left * other_variation,
+                                                        );
+                                                }
+                                            });
+                                        (total_loss + loss, number_of_pairs + Target::one())
+                                    },
+                                )
+                            },
+                        )
+                    },
+                );
+                optimizer.update(
+                    unsafe { &mut **wrapped_target.get() },
+                    &variations,
+                    learning_rate,
+                );
+                let (total_loss, number_of_pairs) = losses.into_iter().fold(
+                    (Target::zero(), Target::zero()),
+                    |(left_loss, left_pairs), (right_loss, right_pairs)| {
+                        (left_loss + right_loss, left_pairs + right_pairs)
+                    },
+                );
+                return Ok((total_loss / number_of_pairs.max(Target::one()))
+                    .to_f32()
+                    .unwrap());
+            }
+
+            let (total_loss, number_of_pairs) = (0..number_of_samples)
+                .into_par_iter()
+                .map(|sample_number| unsafe {
+                    get_pairs(sample_number).fold(
+                        (Target::zero(), Target::zero()),
+                        |(total_loss, number_of_pairs), (other, connected)| {
+                            let left_sample = &mut (*wrapped_target.get())[sample_number
+                                * target_dimension
+                                ..(sample_number + 1) * target_dimension];
+                            let right_sample = &mut (*wrapped_target.get())
+                                [other * target_dimension..(other + 1) * target_dimension];
+                            let (own_variation, other_variation, loss) =
+                                get_variations_and_loss(left_sample, right_sample, connected);
+                            left_sample
+                                .iter_mut()
+                                .zip(right_sample.iter_mut())
+                                .for_each(|(left, right)| {
+                                    let new_left = *left
+                                        - clip_gradient(
+                                            *left * own_variation + *right * other_variation,
+                                        );
+                                    if connected {
+                                        *right -= clip_gradient(
+                                            *right * own_variation + *left * other_variation,
+                                        );
+                                    }
+                                    *left = new_left;
+                                });
+                            (total_loss + loss, number_of_pairs + Target::one())
+                        },
+                    )
+                })
+                .reduce(
+                    || (Target::zero(), Target::zero()),
+                    |(left_loss, left_pairs), (right_loss, right_pairs)| {
+                        (left_loss + right_loss, left_pairs + right_pairs)
+                    },
+                );
+            Ok((total_loss / number_of_pairs.max(Target::one()))
+                .to_f32()
+                .unwrap())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basic_iterative_decomposition::IterativeParameters;
+
+    #[test]
+    fn test_calibrated_membership_strengths() {
+        let distances = (0..15)
+            .map(|i| 0.5 + i as f64 * 0.2 + (i as f64 * 0.37).sin().abs())
+            .collect::<Vec<f64>>();
+        let mut sorted_distances = distances.clone();
+        sorted_distances.sort_by(f64::total_cmp);
+        let strengths = calibrate_membership_strengths(&sorted_distances, 15);
+        assert!((strengths[0] - 1.0).abs() < 1e-12);
+        assert!((strengths.iter().sum::<f64>() - 15.0_f64.log2()).abs() < 1e-4);
+        assert!(strengths.windows(2).all(|pair| pair[0] >= pair[1]));
+    }
+
+    #[test]
+    fn test_learning_rate_schedule() {
+        let get_model = |learning_rate_schedule| {
+            UMAPDecomposition::new(
+                BasicIterativeDecomposition::new(
+                    "UMAP test",
+                    IterativeParameters {
+                        learning_rate_schedule,
+                        verbose: Some(false),
+                        ..Default::default()
+                    },
+                )
+                .unwrap(),
+                UMAPCurve::default(),
+                None,
+                None,
+                None,
+                None,
+            )
+            .unwrap()
+        };
+        // By default, the learning rate decays linearly to zero.
+        let schedule = get_model(None).get_learning_rate_schedule();
+        assert_eq!(schedule.get_factor(0, 10), 1.0);
+        assert!((schedule.get_factor(5, 10) - 0.5).abs() < 1e-6);
+        let cosine = LearningRateSchedule::new(LearningRateDecay::Cosine, Some(2));
+        assert_eq!(get_model(Some(cosine)).get_learning_rate_schedule(), cosine);
+    }
+}
@@ -1,10 +1,17 @@
 use std::{
     cell::UnsafeCell,
     iter::Sum,
-    ops::{Add, Div, Mul, Sub},
+    ops::{Add, Div, Mul, Range, Sub},
 };
 
 use num_traits::Float;
+use rayon::prelude::*;
+
+use crate::{
+    errors::DimensionalReductionError,
+    matrices::{FeatureStatistics, OriginalMatrix, Sample},
+    traits::{GenericFeature, MatrixIsFinite},
+};
 
 pub fn dot<I1, I2, E>(left: I1, right: I2) -> E
 where
@@ -37,6 +44,35 @@ where
     )
 }
 
+/// Returns the dot product of the provided samples standardized with the provided
+/// statistics, visiting only the features stored in either of the samples.
+///
+/// The constant features, whose variance is zero, are ignored.
+pub fn sparse_normal_dot<F>(
+    left: &Sample<'_, F>,
+    right: &Sample<'_, F>,
+    statistics: &FeatureStatistics<F>,
+) -> F
+where
+    F: GenericFeature + Float,
+{
+    let (mean, variance) = (statistics.get_mean(), statistics.get_variance());
+    let (mut product, mut visited_standardized_squared_mean) = (F::zero(), F::zero());
+    let number_of_features = left.zip_features(right, |index, left, right| {
+        let (mean, variance) = (mean[index], variance[index]);
+        if variance > F::zero() {
+            product += (left - mean) / variance * (right - mean);
+            visited_standardized_squared_mean += mean * mean / variance;
+        }
+    });
+    // Each feature missing from both samples contributes its squared standardized mean.
+    if number_of_features < left.get_dimension() {
+        product +=
+            statistics.get_standardized_squared_mean_sum() - visited_standardized_squared_mean;
+    }
+    product
+}
+
 pub fn sigmoid<F>(x: F) -> F
 where
     F: Float,
@@ -44,6 +80,219 @@ where
     F::one() / (F::one() + (-x).exp())
 }
 
+/// Returns the binary cross-entropy of the predicted probability from the expected one.
+///
+/// The predicted probability is clamped away from zero and one, so that
+/// the loss remains finite even for saturated sigmoids.
+pub fn binary_cross_entropy<F>(expected: F, predicted: F) -> F
+where
+    F: Float,
+{
+    let predicted = predicted.max(F::epsilon()).min(F::one() - F::epsilon());
+    -(expected * predicted.ln() + (F::one() - expected) * (F::one() - predicted).ln())
+}
+
+/// Checks that the provided target and original matrices are compatible.
+///
+/// # Arguments
+/// * `target`: The slice where the embedding is to be written.
+/// * `target_dimension`: The dimensionality of the embedding.
+/// * `original`: The original samples to embed.
+/// * `original_dimension`: The dimensionality of the original samples.
+pub fn validate_fit_transform_arguments<Original, Target>(
+    target: &[Target],
+    target_dimension: usize,
+    original: &[Original],
+    original_dimension: usize,
+) -> Result<(), DimensionalReductionError>
+where
+    Original: GenericFeature + Float,
+{
+    if original_dimension == 0 {
+        return Err(DimensionalReductionError::ZeroDimension { name: "original" });
+    }
+
+    if original.len() % original_dimension != 0 {
+        return Err(DimensionalReductionError::IncompatibleDimension {
+            name: "original",
+            length: original.len(),
+            dimension: original_dimension,
+        });
+    }
+
+    validate_fit_transform_matrix(
+        target,
+        target_dimension,
+        original.len() / original_dimension,
+    )?;
+
+    original.matrix_is_finite("original")
+}
+
+/// Checks that the provided target matrix is compatible with the provided original matrix.
+///
+/// # Arguments
+/// * `target`: The slice where the embedding is to be written.
+/// * `target_dimension`: The dimensionality of the embedding.
+/// * `original`: The original samples to embed.
+pub fn validate_fit_transform_matrix_arguments<Original, Target, M>(
+    target: &[Target],
+    target_dimension: usize,
+    original: &M,
+) -> Result<(), DimensionalReductionError>
+where
+    Original: GenericFeature + Float,
+    M: OriginalMatrix<Original>,
+{
+    validate_fit_transform_matrix(target, target_dimension, original.get_number_of_samples())?;
+    original.matrix_is_finite("original")
+}
+
+/// Checks that the provided target matrix has the provided number of samples.
+fn validate_fit_transform_matrix<Target>(
+    target: &[Target],
+    target_dimension: usize,
+    number_of_samples: usize,
+) -> Result<(), DimensionalReductionError> {
+    if target_dimension == 0 {
+        return Err(DimensionalReductionError::ZeroDimension { name: "target" });
+    }
+
+    if target.len() % target_dimension != 0 {
+        return Err(DimensionalReductionError::IncompatibleDimension {
+            name: "target",
+            length: target.len(),
+            dimension: target_dimension,
+        });
+    }
+
+    if number_of_samples != target.len() / target_dimension {
+        return Err(DimensionalReductionError::SamplesNumberMismatch {
+            original_samples: number_of_samples,
+            target_samples: target.len() / target_dimension,
+        });
+    }
+
+    Ok(())
+}
+
+/// Number of partitions of the samples whose partial results are reduced in a
+/// fixed order, so that the results do not depend on the number of threads.
+pub(crate) const NUMBER_OF_DETERMINISTIC_PARTITIONS: usize = 16;
+
+/// Returns the sum of the buffers computed in parallel on a fixed number of
+/// partitions of the samples, reduced in the order of the partitions,
+/// and the result computed on each of the partitions.
+///
+/// # Arguments
+/// * `number_of_samples`: The number of samples to partition.
+/// * `buffer_length`: The length of the buffer of each partition.
+/// * `partition_operation`: The operation computing on the provided range of samples
+///   the values to sum in the provided buffer.
+pub(crate) fn deterministic_partitioned_sum<T, R, F>(
+    number_of_samples: usize,
+    buffer_length: usize,
+    partition_operation: F,
+) -> (Vec<T>, Vec<R>)
+where
+    T: GenericFeature,
+    R: Send,
+    F: Fn(Range<usize>, &mut [T]) -> R + Sync,
+{
+    let partition_size = number_of_samples.div_ceil(NUMBER_OF_DETERMINISTIC_PARTITIONS);
+    let (buffers, results): (Vec<Vec<T>>, Vec<R>) = (0..NUMBER_OF_DETERMINISTIC_PARTITIONS)
+        .into_par_iter()
+        .map(|partition_number| {
+            let mut buffer = vec![T::zero(); buffer_length];
+            let result = partition_operation(
+                (partition_number * partition_size).min(number_of_samples)
+                    ..((partition_number + 1) * partition_size).min(number_of_samples),
+                &mut buffer,
+            );
+            (buffer, result)
+        })
+        .unzip();
+    let sum = (0..buffer_length)
+        .into_par_iter()
+        .map(|i| {
+            buffers
+                .iter()
+                .fold(T::zero(), |partial_sum, buffer| partial_sum + buffer[i])
+        })
+        .collect::<Vec<T>>();
+    (sum, results)
+}
+
+/// Sparse square matrix stored in compressed rows.
+pub(crate) struct SparseRows<F> {
+    offsets: Vec<usize>,
+    neighbours: Vec<usize>,
+    values: Vec<F>,
+}
+
+impl<F: Float> SparseRows<F> {
+    /// Returns the matrix with the provided entries, where the values
+    /// of the entries with the same row and column are merged.
+    ///
+    /// # Arguments
+    /// * `number_of_rows`: The number of rows of the matrix.
+    /// * `entries`: The row, column and value of the entries.
+    /// * `merge`: The operation merging two values of the same entry.
+    pub(crate) fn from_entries<M>(
+        number_of_rows: usize,
+        mut entries: Vec<(usize, usize, f64)>,
+        merge: M,
+    ) -> Self
+    where
+        M: Fn(f64, f64) -> f64,
+    {
+        entries.par_sort_unstable_by_key(|&(row, column, _)| (row, column));
+        let mut offsets = vec![0; number_of_rows + 1];
+        let mut neighbours = Vec::with_capacity(entries.len());
+        let mut merged_values: Vec<f64> = Vec::with_capacity(entries.len());
+        let mut previous_entry = None;
+        for (row, column, value) in entries {
+            if previous_entry == Some((row, column)) {
+                let last = merged_values.len() - 1;
+                merged_values[last] = merge(merged_values[last], value);
+            } else {
+                offsets[row + 1] += 1;
+                neighbours.push(column);
+                merged_values.push(value);
+                previous_entry = Some((row, column));
+            }
+        }
+        for row in 0..number_of_rows {
+            offsets[row + 1] += offsets[row];
+        }
+
+        Self {
+            offsets,
+            neighbours,
+            values: merged_values
+                .into_iter()
+                .map(|value| F::from(value).unwrap())
+                .collect(),
+        }
+    }
+
+    /// Returns the offset of the provided row among the entries of the matrix.
+    pub(crate) fn get_row_offset(&self, row: usize) -> usize {
+        self.offsets[row]
+    }
+
+    /// Returns the columns and values of the entries of the provided row.
+    pub(crate) fn get_row(&self, row: usize) -> (&[usize], &[F]) {
+        let row = self.offsets[row]..self.offsets[row + 1];
+        (&self.neighbours[row.clone()], &self.values[row])
+    }
+
+    /// Returns the values of the entries of the matrix.
+    pub(crate) fn get_values(&self) -> &[F] {
+        &self.values
+    }
+}
+
 pub struct DataRaceAware<T>
 where
     T: ?Sized,
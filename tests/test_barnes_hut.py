@@ -12,3 +12,43 @@ def test_barnes_hut():
         depth=4
     )
     result = model.fit_transform(X)
+
+
+def test_barnes_hut_theta():
+    iris = load_iris()
+    X = iris.data
+    model = BarnesHutSigmoidDecomposition(
+        iterations=2,
+        learning_rate=1,
+        depth=4,
+        theta=0.5
+    )
+    result = model.fit_transform(X)
+    assert result.shape == (X.shape[0], 2)
+
+
+def test_barnes_hut_leaf_capacity():
+    iris = load_iris()
+    X = iris.data
+    model = BarnesHutSigmoidDecomposition(
+        iterations=2,
+        learning_rate=1,
+        leaf_capacity=8
+    )
+    result = model.fit_transform(X)
+    assert result.shape == (X.shape[0], 2)
+
+
+def test_barnes_hut_target_similarity():
+    iris = load_iris()
+    X = iris.data
+    for target_similarity in ("sigmoid_dot", "student_t", "umap", "gaussian"):
+        model = BarnesHutSigmoidDecomposition(
+            iterations=2,
+            learning_rate=0.01,
+            depth=4,
+            similarity="cosine",
+            target_similarity=target_similarity
+        )
+        result = model.fit_transform(X)
+        assert result.shape == (X.shape[0], 2)
@@ -0,0 +1,210 @@
+use dimensional_reduction::basic_iterative_decomposition::{
+    BasicIterativeDecomposition, IterativeParameters,
+};
+use dimensional_reduction::traits::DimensionalReduction;
+use dimensional_reduction::*;
+use vec_rand::{random_f32, splitmix64};
+
+const NUMBER_OF_SAMPLES: usize = 200;
+const ORIGINAL_DIMENSION: usize = 8;
+const TARGET_DIMENSION: usize = 2;
+
+/// Returns a deterministic set of original samples.
+fn get_original() -> Vec<f32> {
+    (0..(NUMBER_OF_SAMPLES * ORIGINAL_DIMENSION) as u64)
+        .map(|i| random_f32(splitmix64(i)))
+        .collect()
+}
+
+fn get_decomposition() -> BasicIterativeDecomposition {
+    get_decomposition_with_optimizer(None)
+}
+
+fn get_decomposition_with_optimizer(optimizer: Option<Optimizer>) -> BasicIterativeDecomposition {
+    BasicIterativeDecomposition::new(
+        "Deterministic test",
+        IterativeParameters {
+            iterations: Some(10),
+            learning_rate: Some(0.001),
+            deterministic: Some(true),
+            optimizer,
+            random_state: Some(42),
+            verbose: Some(false),
+            ..Default::default()
+        },
+    )
+    .unwrap()
+}
+
+/// Asserts that the provided model produces the same embedding and loss history
+/// regardless of the number of threads.
+fn assert_identical_across_thread_pools<M>(model: &M)
+where
+    M: DimensionalReduction + Sync,
+{
+    let original = get_original();
+    let results = [1, 2, 3, 8]
+        .into_iter()
+        .map(|number_of_threads| {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(number_of_threads)
+                .build()
+                .unwrap()
+                .install(|| {
+                    let mut target = vec![0.0_f32; NUMBER_OF_SAMPLES * TARGET_DIMENSION];
+                    let loss_history = model
+                        .fit_transform(&mut target, TARGET_DIMENSION, &original, ORIGINAL_DIMENSION)
+                        .unwrap();
+                    (target, loss_history)
+                })
+        })
+        .collect::<Vec<(Vec<f32>, Vec<f32>)>>();
+
+    for (target, loss_history) in results.iter() {
+        assert!(target.iter().all(|value| value.is_finite()));
+        assert_eq!(target, &results[0].0);
+        assert_eq!(loss_history, &results[0].1);
+    }
+}
+
+#[test]
+fn test_deterministic_sigmoid_decomposition() {
+    assert_identical_across_thread_pools(&SigmoidDecomposition::from(get_decomposition()));
+    assert_identical_across_thread_pools(&SigmoidDecomposition::new(
+        get_decomposition(),
+        StandardizedDot,
+        StudentT,
+    ));
+}
+
+#[test]
+fn test_deterministic_sampled_sigmoid_decomposition() {
+    assert_identical_across_thread_pools(&SampledSigmoidDecomposition::from(get_decomposition()));
+    assert_identical_across_thread_pools(
+        &SampledSigmoidDecomposition::new(
+            get_decomposition(),
+            Cosine,
+            SigmoidDot,
+            Some(3),
+            Some(2),
+            Some(5),
+            Some(0.75),
+        )
+        .unwrap(),
+    );
+}
+
+#[test]
+fn test_deterministic_barnes_hut_sigmoid_decomposition() {
+    assert_identical_across_thread_pools(
+        &BarnesHutSigmoidDecomposition::new(
+            get_decomposition(),
+            StandardizedDot,
+            SigmoidDot,
+            None,
+            None,
+            None,
+        )
+        .unwrap(),
+    );
+    assert_identical_across_thread_pools(
+        &BarnesHutSigmoidDecomposition::new(
+            get_decomposition(),
+            StandardizedDot,
+            SigmoidDot,
+            None,
+            Some(0.5),
+            Some(8),
+        )
+        .unwrap(),
+    );
+}
+
+#[test]
+fn test_deterministic_tsne_decomposition() {
+    for neighbour_search in [
+        NeighbourSearch::Exact,
+        NeighbourSearch::Approximate(NNDescent::default()),
+    ] {
+        assert_identical_across_thread_pools(
+            &TSNEDecomposition::new(
+                get_decomposition(),
+                Some(10.0),
+                None,
+                Some(5),
+                None,
+                None,
+                None,
+                Some(neighbour_search),
+            )
+            .unwrap(),
+        );
+    }
+}
+
+#[test]
+fn test_deterministic_umap_decomposition() {
+    for neighbour_search in [
+        NeighbourSearch::Exact,
+        NeighbourSearch::Approximate(NNDescent::default()),
+    ] {
+        assert_identical_across_thread_pools(
+            &UMAPDecomposition::new(
+                get_decomposition(),
+                UMAPCurve::default(),
+                None,
+                None,
+                None,
+                Some(neighbour_search),
+            )
+            .unwrap(),
+        );
+    }
+}
+
+#[test]
+fn test_deterministic_stateful_optimizers() {
+    for name in ["momentum", "adam", "adagrad"] {
+        let optimizer = Some(Optimizer::from_name(name, None, None, None, None).unwrap());
+        assert_identical_across_thread_pools(&SigmoidDecomposition::from(
+            get_decomposition_with_optimizer(optimizer),
+        ));
+        assert_identical_across_thread_pools(&SampledSigmoidDecomposition::from(
+            get_decomposition_with_optimizer(optimizer),
+        ));
+        assert_identical_across_thread_pools(
+            &BarnesHutSigmoidDecomposition::new(
+                get_decomposition_with_optimizer(optimizer),
+                StandardizedDot,
+                SigmoidDot,
+                None,
+                Some(0.5),
+                Some(8),
+            )
+            .unwrap(),
+        );
+        assert_identical_across_thread_pools(
+            &UMAPDecomposition::new(
+                get_decomposition_with_optimizer(optimizer),
+                UMAPCurve::default(),
+                None,
+                None,
+                None,
+                None,
+            )
+            .unwrap(),
+        );
+        assert!(TSNEDecomposition::new(
+            get_decomposition_with_optimizer(optimizer),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None
+        )
+        .is_err());
+        assert!(MDSDecomposition::new(get_decomposition_with_optimizer(optimizer)).is_err());
+    }
+}
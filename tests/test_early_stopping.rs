@@ -0,0 +1,70 @@
+use dimensional_reduction::basic_iterative_decomposition::{
+    BasicIterativeDecomposition, IterativeParameters,
+};
+use dimensional_reduction::traits::DimensionalReduction;
+use dimensional_reduction::*;
+use vec_rand::{random_f32, splitmix64};
+
+const ITERATIONS: usize = 1000;
+const NUMBER_OF_SAMPLES: usize = 100;
+const ORIGINAL_DIMENSION: usize = 8;
+const TARGET_DIMENSION: usize = 2;
+const TOLERANCE: f32 = 1e-4;
+const PATIENCE: usize = 3;
+
+fn get_original() -> Vec<f32> {
+    (0..NUMBER_OF_SAMPLES * ORIGINAL_DIMENSION)
+        .map(|i| random_f32(splitmix64(i as u64)))
+        .collect()
+}
+
+fn get_model(tolerance: Option<f32>) -> SigmoidDecomposition {
+    SigmoidDecomposition::from(
+        BasicIterativeDecomposition::new(
+            "Early stopping test",
+            IterativeParameters {
+                iterations: Some(ITERATIONS),
+                learning_rate: Some(0.01),
+                tolerance,
+                patience: Some(PATIENCE),
+                deterministic: Some(true),
+                random_state: Some(42),
+                verbose: Some(false),
+                ..Default::default()
+            },
+        )
+        .unwrap(),
+    )
+}
+
+#[test]
+fn test_converged_model_stops_early() {
+    let fitted = get_model(Some(TOLERANCE))
+        .fit::<f32, f32>(TARGET_DIMENSION, &get_original(), ORIGINAL_DIMENSION)
+        .unwrap();
+    let loss_history = fitted.get_loss_history();
+
+    assert!(loss_history.len() < ITERATIONS);
+    assert_eq!(fitted.get_number_of_epochs(), loss_history.len());
+    // The training stops as soon as the last `PATIENCE` epochs
+    // changed the loss by less than the tolerance.
+    let relative_changes = loss_history
+        .windows(2)
+        .map(|pair| (pair[0] - pair[1]).abs() / pair[0].abs().max(f32::EPSILON))
+        .collect::<Vec<f32>>();
+    let (previous, last) = relative_changes.split_at(relative_changes.len() - PATIENCE);
+    assert!(last.iter().all(|&change| change <= TOLERANCE));
+    assert!(previous
+        .windows(PATIENCE)
+        .all(|changes| changes.iter().any(|&change| change > TOLERANCE)));
+}
+
+#[test]
+fn test_model_without_tolerance_runs_every_epoch() {
+    let fitted = get_model(None)
+        .fit::<f32, f32>(TARGET_DIMENSION, &get_original(), ORIGINAL_DIMENSION)
+        .unwrap();
+
+    assert_eq!(fitted.get_loss_history().len(), ITERATIONS);
+    assert_eq!(fitted.get_number_of_epochs(), ITERATIONS);
+}
@@ -0,0 +1,95 @@
+use dimensional_reduction::basic_decomposition::BasicDecomposition;
+use dimensional_reduction::traits::DimensionalReduction;
+use dimensional_reduction::*;
+
+const NUMBER_OF_SAMPLES: usize = 10;
+const ORIGINAL_DIMENSION: usize = 4;
+const TARGET_DIMENSION: usize = 2;
+
+fn get_original() -> Vec<f32> {
+    (0..NUMBER_OF_SAMPLES * ORIGINAL_DIMENSION)
+        .map(|i| (i as f32 * 0.37).sin())
+        .collect()
+}
+
+fn get_model() -> PCADecomposition {
+    PCADecomposition::new(
+        BasicDecomposition::new("Errors test", None, Some(false)).unwrap(),
+        None,
+    )
+}
+
+#[test]
+fn test_dimension_errors() {
+    let model = get_model();
+    let original = get_original();
+
+    let mut target = vec![0.0_f32; (NUMBER_OF_SAMPLES - 1) * TARGET_DIMENSION];
+    let error = model
+        .fit_transform(&mut target, TARGET_DIMENSION, &original, ORIGINAL_DIMENSION)
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        DimensionalReductionError::SamplesNumberMismatch {
+            original_samples: NUMBER_OF_SAMPLES,
+            target_samples,
+        } if target_samples == NUMBER_OF_SAMPLES - 1
+    ));
+    assert_eq!(
+        error.to_string(),
+        "The provided original slice has 10 samples while the provided target slice has 9 samples."
+    );
+
+    let mut target = vec![0.0_f32; NUMBER_OF_SAMPLES * TARGET_DIMENSION];
+    assert_eq!(
+        model.fit_transform(
+            &mut target,
+            TARGET_DIMENSION,
+            &original[1..],
+            ORIGINAL_DIMENSION
+        ),
+        Err(DimensionalReductionError::IncompatibleDimension {
+            name: "original",
+            length: NUMBER_OF_SAMPLES * ORIGINAL_DIMENSION - 1,
+            dimension: ORIGINAL_DIMENSION,
+        })
+    );
+    assert_eq!(
+        model.fit_transform(&mut target, 0, &original, ORIGINAL_DIMENSION),
+        Err(DimensionalReductionError::ZeroDimension { name: "target" })
+    );
+}
+
+#[test]
+fn test_non_finite_value_error() {
+    let mut original = get_original();
+    original[7] = f32::INFINITY;
+    let mut target = vec![0.0_f32; NUMBER_OF_SAMPLES * TARGET_DIMENSION];
+    let error = get_model()
+        .fit_transform(&mut target, TARGET_DIMENSION, &original, ORIGINAL_DIMENSION)
+        .unwrap_err();
+    match error {
+        DimensionalReductionError::NonFiniteValue { name, index, value } => {
+            assert_eq!(name, "original");
+            assert_eq!(index, 7);
+            assert_eq!(value, f64::INFINITY);
+        }
+        error => panic!("Unexpected error {:?}", error),
+    }
+}
+
+#[test]
+fn test_invalid_parameter_error() {
+    assert!(matches!(
+        BasicDecomposition::new("", None, None),
+        Err(DimensionalReductionError::EmptyModelName)
+    ));
+    assert_eq!(
+        GaussianRBF::new(Some(-1.0)),
+        Err(DimensionalReductionError::InvalidParameter {
+            parameter: "bandwidth",
+            value: "-1".to_string(),
+            expected: "a finite strictly positive value",
+        })
+    );
+}
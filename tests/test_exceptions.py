@@ -0,0 +1,42 @@
+import numpy as np
+import pytest
+from dimensional_reduction import (
+    SigmoidDecomposition,
+    PCADecomposition,
+    DimensionMismatchError,
+    NonFiniteValueError,
+    UnsupportedTargetDimensionError,
+    InvalidParameterError,
+)
+
+
+def get_samples():
+    return np.random.uniform(size=(50, 4)).astype(np.float32)
+
+
+def test_dimension_mismatch():
+    with pytest.raises(DimensionMismatchError):
+        SigmoidDecomposition(iterations=1, verbose=False).fit_transform(
+            get_samples(), number_of_dimensions=0
+        )
+    with pytest.raises(UnsupportedTargetDimensionError):
+        PCADecomposition().fit_transform(get_samples(), number_of_dimensions=5)
+
+
+def test_non_finite_values():
+    X = get_samples()
+    X[3, 2] = np.nan
+    with pytest.raises(NonFiniteValueError):
+        SigmoidDecomposition(iterations=1, verbose=False).fit_transform(X)
+    X[3, 2] = np.inf
+    with pytest.raises(NonFiniteValueError):
+        PCADecomposition().fit_transform(X)
+
+
+def test_exceptions_are_value_errors():
+    # The exceptions derive from the builtin ones, so that the
+    # code catching the builtin exceptions keeps working.
+    assert issubclass(DimensionMismatchError, ValueError)
+    assert issubclass(NonFiniteValueError, ValueError)
+    assert issubclass(InvalidParameterError, ValueError)
+    assert issubclass(UnsupportedTargetDimensionError, NotImplementedError)
@@ -0,0 +1,74 @@
+import numpy as np
+import pytest
+from dimensional_reduction import SampledSigmoidDecomposition, InvalidParameterError
+from scipy.sparse import csr_matrix
+
+
+def get_ring_edges(number_of_nodes=100):
+    sources = np.arange(number_of_nodes)
+    destinations = (sources + 1) % number_of_nodes
+    weights = np.linspace(1.0, 2.0, number_of_nodes).astype(np.float32)
+    return sources, destinations, weights
+
+
+def test_edge_list_matches_adjacency():
+    sources, destinations, weights = get_ring_edges()
+    model = SampledSigmoidDecomposition(
+        iterations=5,
+        deterministic=True,
+        number_of_samples_per_point=3,
+    )
+    from_edges = model.fit_transform_graph((sources, destinations, weights))
+    adjacency = csr_matrix(
+        (
+            np.concatenate([weights, weights]),
+            (
+                np.concatenate([sources, destinations]),
+                np.concatenate([destinations, sources]),
+            ),
+        ),
+        shape=(100, 100),
+    )
+    from_adjacency = model.fit_transform_graph(adjacency)
+    assert from_edges.shape == (100, 2)
+    assert np.allclose(from_edges, from_adjacency)
+
+
+def test_graph_embedding_preserves_edges():
+    sources, destinations, weights = get_ring_edges()
+    model = SampledSigmoidDecomposition(
+        iterations=200,
+        learning_rate=0.1,
+        number_of_samples_per_point=3,
+        negative_samples=2,
+        negative_sampling_exponent=0.75,
+    )
+    result = model.fit_transform_graph((sources, destinations, weights))
+    # The linked nodes are more similar in the embedding than the other pairs of nodes.
+    similarities = 1.0 / (1.0 + np.exp(-result @ result.T))
+    linked = np.zeros((100, 100), dtype=bool)
+    linked[sources, destinations] = True
+    linked[destinations, sources] = True
+    unlinked = ~linked
+    np.fill_diagonal(unlinked, False)
+    assert similarities[linked].mean() > similarities[unlinked].mean() + 0.2
+
+
+def test_unweighted_edge_list():
+    sources, destinations, _ = get_ring_edges()
+    model = SampledSigmoidDecomposition(
+        iterations=5,
+        number_of_samples_per_point=2,
+        initialization="spectral",
+    )
+    result = model.fit_transform_graph((sources, destinations), number_of_nodes=120)
+    assert result.shape == (120, 2)
+    assert np.isfinite(result).all()
+
+
+def test_graph_requires_positive_pairs():
+    sources, destinations, _ = get_ring_edges()
+    with pytest.raises(InvalidParameterError):
+        SampledSigmoidDecomposition(iterations=5).fit_transform_graph(
+            (sources, destinations)
+        )
@@ -0,0 +1,122 @@
+use dimensional_reduction::basic_iterative_decomposition::{
+    BasicIterativeDecomposition, IterativeParameters,
+};
+use dimensional_reduction::traits::GraphDimensionalReduction;
+use dimensional_reduction::*;
+
+const NUMBER_OF_NODES: usize = 200;
+const TARGET_DIMENSION: usize = 2;
+
+fn get_decomposition(iterations: usize, learning_rate: f32) -> BasicIterativeDecomposition {
+    BasicIterativeDecomposition::new(
+        "Graph test",
+        IterativeParameters {
+            iterations: Some(iterations),
+            learning_rate: Some(learning_rate),
+            deterministic: Some(true),
+            random_state: Some(42),
+            verbose: Some(false),
+            ..Default::default()
+        },
+    )
+    .unwrap()
+}
+
+/// Returns two rings of nodes, joined by a single edge.
+fn get_rings() -> Graph<f32> {
+    let ring_size = NUMBER_OF_NODES / 2;
+    let (mut sources, mut destinations) = (vec![0], vec![ring_size]);
+    (0..NUMBER_OF_NODES).for_each(|node| {
+        sources.push(node);
+        destinations.push(node / ring_size * ring_size + (node + 1) % ring_size);
+    });
+    let weights = (0..sources.len())
+        .map(|edge_number| 1.0 + (edge_number % 3) as f32)
+        .collect::<Vec<f32>>();
+    Graph::from_edge_list(NUMBER_OF_NODES, &sources, &destinations, Some(&weights)).unwrap()
+}
+
+fn get_model(iterations: usize, learning_rate: f32) -> SampledSigmoidDecomposition {
+    SampledSigmoidDecomposition::new(
+        get_decomposition(iterations, learning_rate),
+        StandardizedDot,
+        SigmoidDot,
+        Some(3),
+        Some(2),
+        None,
+        Some(0.75),
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_graph_embedding() {
+    let graph = get_rings();
+    let graph_from_adjacency = Graph::new(graph.get_adjacency().clone()).unwrap();
+    let model = get_model(10, 0.001);
+
+    let results = [1, 2, 8]
+        .into_iter()
+        .zip([&graph, &graph_from_adjacency, &graph])
+        .map(|(number_of_threads, graph)| {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(number_of_threads)
+                .build()
+                .unwrap()
+                .install(|| {
+                    let mut target = vec![0.0_f32; NUMBER_OF_NODES * TARGET_DIMENSION];
+                    let loss_history = model
+                        .fit_transform_graph(&mut target, TARGET_DIMENSION, graph)
+                        .unwrap();
+                    (target, loss_history)
+                })
+        })
+        .collect::<Vec<(Vec<f32>, Vec<f32>)>>();
+
+    for (target, loss_history) in results.iter() {
+        assert!(target.iter().all(|value| value.is_finite()));
+        assert!(loss_history.iter().all(|loss| loss.is_finite()));
+        assert_eq!(target, &results[0].0);
+        assert_eq!(loss_history, &results[0].1);
+    }
+
+    // Graphs require positive pairs, and have no features for the PCA initialization.
+    let mut target = vec![0.0_f32; NUMBER_OF_NODES * TARGET_DIMENSION];
+    assert!(
+        SampledSigmoidDecomposition::from(get_decomposition(10, 0.001))
+            .fit_transform_graph(&mut target, TARGET_DIMENSION, &graph)
+            .is_err()
+    );
+}
+
+#[test]
+fn test_graph_embedding_preserves_edges() {
+    let mut target = vec![0.0_f32; NUMBER_OF_NODES * TARGET_DIMENSION];
+    let graph = get_rings();
+    get_model(200, 0.1)
+        .fit_transform_graph(&mut target, TARGET_DIMENSION, &graph)
+        .unwrap();
+
+    // The linked nodes are more similar in the embedding than the other pairs of nodes.
+    let points = target.chunks(TARGET_DIMENSION).collect::<Vec<&[f32]>>();
+    let (mut linked, mut unlinked) = ((0.0, 0), (0.0, 0));
+    (0..NUMBER_OF_NODES).for_each(|left| {
+        ((left + 1)..NUMBER_OF_NODES).for_each(|right| {
+            let dot = points[left]
+                .iter()
+                .zip(points[right].iter())
+                .map(|(left, right)| left * right)
+                .sum::<f32>();
+            let similarity = 1.0 / (1.0 + (-dot).exp());
+            let (total, count) = if graph.get_weight(left, right) > 0.0 {
+                &mut linked
+            } else {
+                &mut unlinked
+            };
+            *total += similarity;
+            *count += 1;
+        });
+    });
+    let (linked, unlinked) = (linked.0 / linked.1 as f32, unlinked.0 / unlinked.1 as f32);
+    assert!(linked > unlinked + 0.2);
+}
@@ -0,0 +1,90 @@
+use dimensional_reduction::basic_iterative_decomposition::{
+    BasicIterativeDecomposition, IterativeParameters,
+};
+use dimensional_reduction::traits::DimensionalReduction;
+use dimensional_reduction::*;
+use vec_rand::{random_f32, splitmix64};
+
+const ITERATIONS: usize = 20;
+const NUMBER_OF_SAMPLES: usize = 200;
+const ORIGINAL_DIMENSION: usize = 8;
+const TARGET_DIMENSION: usize = 2;
+
+fn get_original() -> Vec<f32> {
+    (0..NUMBER_OF_SAMPLES * ORIGINAL_DIMENSION)
+        .map(|i| random_f32(splitmix64(i as u64)))
+        .collect()
+}
+
+fn get_decomposition() -> BasicIterativeDecomposition {
+    BasicIterativeDecomposition::new(
+        "Loss history test",
+        IterativeParameters {
+            iterations: Some(ITERATIONS),
+            learning_rate: Some(0.01),
+            random_state: Some(42),
+            verbose: Some(false),
+            ..Default::default()
+        },
+    )
+    .unwrap()
+}
+
+/// Asserts that the provided model returns a finite loss for every epoch,
+/// and that the loss of the last epoch is smaller than the one of the first.
+fn assert_loss_decreases<M>(model: &M)
+where
+    M: DimensionalReduction,
+{
+    let mut target = vec![0.0_f32; NUMBER_OF_SAMPLES * TARGET_DIMENSION];
+    let loss_history = model
+        .fit_transform(
+            &mut target,
+            TARGET_DIMENSION,
+            &get_original(),
+            ORIGINAL_DIMENSION,
+        )
+        .unwrap();
+
+    assert_eq!(loss_history.len(), ITERATIONS);
+    assert!(loss_history.iter().all(|loss| loss.is_finite()));
+    assert!(loss_history[ITERATIONS - 1] < loss_history[0]);
+}
+
+#[test]
+fn test_sigmoid_loss_decreases() {
+    assert_loss_decreases(&SigmoidDecomposition::from(get_decomposition()));
+}
+
+#[test]
+fn test_sampled_sigmoid_loss_decreases() {
+    // With enough pairs per epoch, the loss of the drawn pairs
+    // is not dominated by the noise of the sampling.
+    assert_loss_decreases(
+        &SampledSigmoidDecomposition::new(
+            get_decomposition(),
+            StandardizedDot,
+            SigmoidDot,
+            Some(10),
+            Some(20),
+            Some(10),
+            None,
+        )
+        .unwrap(),
+    );
+}
+
+#[test]
+fn test_barnes_hut_sigmoid_loss_decreases() {
+    assert_loss_decreases(
+        &BarnesHutSigmoidDecomposition::new(
+            get_decomposition(),
+            StandardizedDot,
+            SigmoidDot,
+            None,
+            None,
+            None,
+        )
+        .unwrap(),
+    );
+}
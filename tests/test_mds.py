@@ -0,0 +1,37 @@
+import numpy as np
+import pytest
+from dimensional_reduction import (
+    MDSDecomposition,
+    raw_stress,
+    normalized_stress,
+    sammon_stress,
+    InvalidParameterError,
+)
+from scipy.sparse import csr_matrix
+from sklearn.datasets import load_iris
+
+
+def test_mds():
+    X = load_iris().data
+    model = MDSDecomposition(iterations=50)
+    result, loss_history = model.fit_transform(X, return_loss_history=True)
+    assert result.shape == (X.shape[0], 2)
+    assert len(loss_history) == 50
+    assert np.all(np.diff(loss_history) <= 1e-3 * loss_history[0])
+    assert raw_stress(X, result) <= loss_history[-1] * (1 + 1e-4)
+    assert normalized_stress(X, result) < 0.2
+    assert sammon_stress(X, result) < sammon_stress(X, np.random.uniform(size=result.shape))
+
+
+def test_stress_of_identical_embedding():
+    X = load_iris().data
+    for metric in (raw_stress, normalized_stress, sammon_stress):
+        assert metric(X, X) == pytest.approx(0.0, abs=1e-6)
+        assert metric(csr_matrix(X), 2 * X) == pytest.approx(metric(X, 2 * X))
+    assert normalized_stress(X, 2 * X) == pytest.approx(1.0)
+    assert sammon_stress(X, 2 * X) == pytest.approx(1.0)
+
+
+def test_mds_requires_sgd():
+    with pytest.raises(InvalidParameterError):
+        MDSDecomposition(optimizer="adam")
@@ -0,0 +1,94 @@
+use dimensional_reduction::basic_iterative_decomposition::{
+    BasicIterativeDecomposition, IterativeParameters,
+};
+use dimensional_reduction::metrics::normalized_stress;
+use dimensional_reduction::traits::DimensionalReduction;
+use dimensional_reduction::*;
+use vec_rand::{random_f32, splitmix64};
+
+const NUMBER_OF_SAMPLES: usize = 200;
+const ORIGINAL_DIMENSION: usize = 8;
+const TARGET_DIMENSION: usize = 2;
+
+fn get_model(iterations: usize) -> MDSDecomposition {
+    MDSDecomposition::new(
+        BasicIterativeDecomposition::new(
+            "MDS test",
+            IterativeParameters {
+                iterations: Some(iterations),
+                deterministic: Some(true),
+                random_state: Some(42),
+                verbose: Some(false),
+                ..Default::default()
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap()
+}
+
+/// Returns the embedding and loss history of the provided model on the
+/// provided original samples with the provided number of threads.
+fn fit_transform(
+    model: &MDSDecomposition,
+    original: &[f32],
+    number_of_threads: usize,
+) -> (Vec<f32>, Vec<f32>) {
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(number_of_threads)
+        .build()
+        .unwrap()
+        .install(|| {
+            let mut target = vec![0.0_f32; NUMBER_OF_SAMPLES * TARGET_DIMENSION];
+            let loss_history = model
+                .fit_transform(&mut target, TARGET_DIMENSION, original, ORIGINAL_DIMENSION)
+                .unwrap();
+            (target, loss_history)
+        })
+}
+
+#[test]
+fn test_deterministic_mds_decomposition() {
+    let original = (0..(NUMBER_OF_SAMPLES * ORIGINAL_DIMENSION) as u64)
+        .map(|i| random_f32(splitmix64(i)))
+        .collect::<Vec<f32>>();
+    let model = get_model(10);
+    let expected = fit_transform(&model, &original, 1);
+    assert!(expected.0.iter().all(|value| value.is_finite()));
+    [2, 3, 8].into_iter().for_each(|number_of_threads| {
+        assert_eq!(
+            fit_transform(&model, &original, number_of_threads),
+            expected
+        );
+    });
+}
+
+#[test]
+fn test_mds_recovers_planar_samples() {
+    // The samples lie on a plane spanned by two orthogonal directions of the
+    // original space, so that their distances can be embedded exactly.
+    let directions = [
+        [0.5, 0.5, 0.5, 0.5, 0.0, 0.0, 0.0, 0.0],
+        [0.0, 0.0, 0.0, 0.0, 0.5, -0.5, 0.5, -0.5],
+    ];
+    let original = (0..NUMBER_OF_SAMPLES)
+        .flat_map(|sample_number| {
+            let coordinates = [
+                random_f32(splitmix64(2 * sample_number as u64)),
+                random_f32(splitmix64(2 * sample_number as u64 + 1)),
+            ];
+            (0..ORIGINAL_DIMENSION).map(move |feature| {
+                coordinates[0] * directions[0][feature] + coordinates[1] * directions[1][feature]
+            })
+        })
+        .collect::<Vec<f32>>();
+
+    let (target, _) = fit_transform(&get_model(300), &original, 4);
+    let stress = normalized_stress(
+        &DenseMatrix::new(&original, ORIGINAL_DIMENSION).unwrap(),
+        &target,
+        TARGET_DIMENSION,
+    )
+    .unwrap();
+    assert!(stress < 0.01);
+}
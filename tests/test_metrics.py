@@ -0,0 +1,52 @@
+import numpy as np
+import pytest
+from dimensional_reduction import (
+    SigmoidDecomposition,
+    trustworthiness,
+    continuity,
+    knn_recall,
+    co_ranking_matrix,
+    quality_curves,
+    spearman_correlation,
+    InvalidParameterError,
+)
+from scipy.sparse import csr_matrix
+from sklearn.datasets import load_digits
+from sklearn.manifold import trustworthiness as sklearn_trustworthiness
+
+
+def test_metrics_of_identical_embedding():
+    X = load_digits().data[:200]
+    assert trustworthiness(X, X) == pytest.approx(1.0)
+    assert continuity(X, X) == pytest.approx(1.0)
+    assert knn_recall(X, X) == pytest.approx(1.0)
+    assert spearman_correlation(X, X) == pytest.approx(1.0)
+    quality, local_continuity = quality_curves(X, X, maximum_rank=20)
+    assert np.allclose(quality, 1.0)
+    assert local_continuity.shape == (20,)
+
+
+def test_trustworthiness_matches_sklearn():
+    X = load_digits().data[:200]
+    embedding = SigmoidDecomposition(iterations=5).fit_transform(X)
+    assert trustworthiness(X, embedding, number_of_neighbours=5) == pytest.approx(
+        sklearn_trustworthiness(X, embedding, n_neighbors=5), abs=1e-6
+    )
+
+
+def test_sparse_original_matches_dense():
+    X = load_digits().data[:200]
+    embedding = SigmoidDecomposition(iterations=5).fit_transform(X)
+    for metric in (trustworthiness, continuity, knn_recall, spearman_correlation):
+        assert metric(csr_matrix(X), embedding) == pytest.approx(metric(X, embedding))
+    co_ranking = co_ranking_matrix(X, embedding, maximum_rank=10)
+    assert co_ranking.shape == (10, 10)
+    assert np.array_equal(co_ranking, co_ranking_matrix(csr_matrix(X), embedding, maximum_rank=10))
+
+
+def test_invalid_metric_parameters():
+    X = load_digits().data[:200]
+    with pytest.raises(InvalidParameterError):
+        trustworthiness(X, X, number_of_neighbours=0)
+    with pytest.raises(InvalidParameterError):
+        co_ranking_matrix(X, X, maximum_rank=200)
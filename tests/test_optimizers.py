@@ -0,0 +1,21 @@
+import pytest
+from dimensional_reduction import SigmoidDecomposition, TSNEDecomposition, InvalidParameterError
+from sklearn.datasets import load_iris
+
+
+@pytest.mark.parametrize("optimizer", ["sgd", "momentum", "adam", "adagrad"])
+def test_sigmoid_optimizers(optimizer):
+    iris = load_iris()
+    X = iris.data
+    model = SigmoidDecomposition(
+        iterations=10,
+        learning_rate=0.01,
+        optimizer=optimizer,
+    )
+    result = model.fit_transform(X)
+    assert result.shape == (X.shape[0], 2)
+
+
+def test_tsne_rejects_stateful_optimizers():
+    with pytest.raises(InvalidParameterError):
+        TSNEDecomposition(optimizer="adam")
@@ -0,0 +1,14 @@
+from dimensional_reduction import PCADecomposition
+from sklearn.datasets import load_iris
+import numpy as np
+
+
+def test_pca():
+    iris = load_iris()
+    X = iris.data
+    model = PCADecomposition()
+    result = model.fit_transform(X)
+    assert result.shape == (X.shape[0], 2)
+    components, explained_variance_ratio = model.fit_components(X, 2)
+    assert components.shape == (2, X.shape[1])
+    assert np.all(np.diff(explained_variance_ratio) <= 0)
@@ -0,0 +1,76 @@
+import numpy as np
+import pytest
+from dimensional_reduction import (
+    SigmoidDecomposition,
+    SampledSigmoidDecomposition,
+    InvalidParameterError,
+)
+from scipy.sparse import csr_matrix
+from sklearn.datasets import load_digits, make_blobs
+from sklearn.metrics import pairwise_distances
+
+
+@pytest.mark.parametrize("model", [
+    SigmoidDecomposition(iterations=5, deterministic=True),
+    SampledSigmoidDecomposition(iterations=5, deterministic=True),
+])
+def test_precomputed_sparse_matches_dense(model):
+    X = load_digits().data[:200]
+    distances = pairwise_distances(X)
+    affinities = np.exp(-distances / distances.mean())
+    affinities[affinities < 0.5] = 0.0
+    dense = model.fit_transform_precomputed(affinities)
+    sparse = model.fit_transform_precomputed(csr_matrix(affinities))
+    assert dense.shape == (X.shape[0], 2)
+    assert np.allclose(dense, sparse, atol=1e-4)
+
+
+def test_precomputed_separates_clusters():
+    X, labels = make_blobs(
+        n_samples=200, n_features=8, centers=4, cluster_std=0.3, random_state=42
+    )
+    squared_distances = pairwise_distances(X) ** 2
+    affinities = np.exp(-squared_distances)
+    affinities[squared_distances > 3.0] = 0.0
+    model = SampledSigmoidDecomposition(
+        iterations=500,
+        learning_rate=0.1,
+        number_of_samples_per_point=3,
+        negative_samples=2,
+        number_of_neighbours=5,
+        negative_sampling_exponent=0.75,
+    )
+    result = model.fit_transform_precomputed(csr_matrix(affinities))
+    # The embedded samples are more similar to the samples in their cluster.
+    similarities = 1.0 / (1.0 + np.exp(-result @ result.T))
+    same_cluster = labels[:, None] == labels[None, :]
+    np.fill_diagonal(same_cluster, False)
+    different_cluster = labels[:, None] != labels[None, :]
+    assert similarities[same_cluster].mean() > similarities[different_cluster].mean() + 0.05
+
+
+def test_precomputed_distances():
+    X = load_digits().data[:200]
+    distances = pairwise_distances(X)
+    model = SigmoidDecomposition(iterations=5, initialization="spectral")
+    result = model.fit_transform_precomputed(
+        distances,
+        kind="distance",
+        bandwidth=float(distances.mean()),
+    )
+    assert result.shape == (X.shape[0], 2)
+    assert np.isfinite(result).all()
+
+
+def test_invalid_precomputed_matrices():
+    model = SigmoidDecomposition(iterations=5)
+    with pytest.raises(InvalidParameterError):
+        model.fit_transform_precomputed(np.ones((10, 5)))
+    with pytest.raises(InvalidParameterError):
+        model.fit_transform_precomputed(2 * np.ones((10, 10)))
+    with pytest.raises(InvalidParameterError):
+        model.fit_transform_precomputed(np.ones((10, 10)), kind="unknown")
+    with pytest.raises(InvalidParameterError):
+        SigmoidDecomposition(initialization="pca").fit_transform_precomputed(
+            np.ones((10, 10))
+        )
@@ -0,0 +1,164 @@
+use dimensional_reduction::basic_iterative_decomposition::{
+    BasicIterativeDecomposition, IterativeParameters,
+};
+use dimensional_reduction::traits::PrecomputedDimensionalReduction;
+use dimensional_reduction::*;
+use vec_rand::{random_f32, splitmix64};
+
+const NUMBER_OF_CLUSTERS: usize = 4;
+const NUMBER_OF_SAMPLES: usize = 200;
+const ORIGINAL_DIMENSION: usize = 8;
+const TARGET_DIMENSION: usize = 2;
+
+/// Returns a deterministic set of original samples, where the sample with
+/// number i belongs to the cluster i % NUMBER_OF_CLUSTERS.
+fn get_original() -> Vec<f32> {
+    (0..NUMBER_OF_SAMPLES * ORIGINAL_DIMENSION)
+        .map(|i| {
+            let cluster = i / ORIGINAL_DIMENSION % NUMBER_OF_CLUSTERS;
+            let center = if cluster == i % ORIGINAL_DIMENSION {
+                4.0
+            } else {
+                0.0
+            };
+            center + random_f32(splitmix64(i as u64))
+        })
+        .collect()
+}
+
+/// Returns the affinities of the original samples, keeping only those of the
+/// closest pairs, as the values of a dense matrix and as a sparse matrix.
+fn get_affinities() -> (Vec<f32>, PrecomputedMatrix<CSRMatrix<f32>>) {
+    let original = get_original();
+    let samples = original.chunks(ORIGINAL_DIMENSION).collect::<Vec<&[f32]>>();
+    let affinities = samples
+        .iter()
+        .flat_map(|left| {
+            samples.iter().map(move |right| {
+                let distance = left
+                    .iter()
+                    .zip(right.iter())
+                    .map(|(left, right)| (left - right).powi(2))
+                    .sum::<f32>();
+                if distance < 3.0 {
+                    (-distance).exp()
+                } else {
+                    0.0
+                }
+            })
+        })
+        .collect::<Vec<f32>>();
+    let (mut indptr, mut indices, mut data) = (vec![0], Vec::new(), Vec::new());
+    affinities.chunks(NUMBER_OF_SAMPLES).for_each(|row| {
+        row.iter()
+            .enumerate()
+            .filter(|(_, &value)| value != 0.0)
+            .for_each(|(index, &value)| {
+                indices.push(index);
+                data.push(value);
+            });
+        indptr.push(indices.len());
+    });
+
+    (
+        affinities,
+        PrecomputedMatrix::new(
+            CSRMatrix::new(indptr, indices, data, NUMBER_OF_SAMPLES).unwrap(),
+            PrecomputedKind::Affinity,
+        )
+        .unwrap(),
+    )
+}
+
+fn get_decomposition(iterations: usize) -> BasicIterativeDecomposition {
+    BasicIterativeDecomposition::new(
+        "Precomputed test",
+        IterativeParameters {
+            iterations: Some(iterations),
+            learning_rate: Some(0.1),
+            deterministic: Some(true),
+            random_state: Some(42),
+            verbose: Some(false),
+            ..Default::default()
+        },
+    )
+    .unwrap()
+}
+
+fn get_sampled_decomposition(iterations: usize) -> SampledSigmoidDecomposition {
+    SampledSigmoidDecomposition::new(
+        get_decomposition(iterations),
+        StandardizedDot,
+        SigmoidDot,
+        Some(3),
+        Some(2),
+        Some(5),
+        Some(0.75),
+    )
+    .unwrap()
+}
+
+/// Asserts that the provided model produces the same embedding when the
+/// precomputed affinities are provided as a dense and as a sparse matrix.
+fn assert_precomputed_sparse_matches_dense<M>(model: &M)
+where
+    M: PrecomputedDimensionalReduction,
+{
+    let (affinities, sparse) = get_affinities();
+    let dense = PrecomputedMatrix::new(
+        DenseMatrix::new(&affinities, NUMBER_OF_SAMPLES).unwrap(),
+        PrecomputedKind::Affinity,
+    )
+    .unwrap();
+
+    let mut dense_target = vec![0.0_f32; NUMBER_OF_SAMPLES * TARGET_DIMENSION];
+    let mut sparse_target = dense_target.clone();
+    model
+        .fit_transform_precomputed(&mut dense_target, TARGET_DIMENSION, &dense)
+        .unwrap();
+    model
+        .fit_transform_precomputed(&mut sparse_target, TARGET_DIMENSION, &sparse)
+        .unwrap();
+
+    assert!(dense_target.iter().all(|value| value.is_finite()));
+    assert_eq!(dense_target, sparse_target);
+}
+
+#[test]
+fn test_precomputed_sparse_matches_dense() {
+    assert_precomputed_sparse_matches_dense(&SigmoidDecomposition::from(get_decomposition(10)));
+    assert_precomputed_sparse_matches_dense(&get_sampled_decomposition(10));
+}
+
+#[test]
+fn test_precomputed_separates_clusters() {
+    let (_, sparse) = get_affinities();
+    let mut target = vec![0.0_f32; NUMBER_OF_SAMPLES * TARGET_DIMENSION];
+    get_sampled_decomposition(500)
+        .fit_transform_precomputed(&mut target, TARGET_DIMENSION, &sparse)
+        .unwrap();
+
+    // The embedded samples are more similar to the samples in their cluster,
+    // whose affinities are positive, than to the samples in the other clusters.
+    let points = target.chunks(TARGET_DIMENSION).collect::<Vec<&[f32]>>();
+    let (mut within, mut between) = ((0.0, 0), (0.0, 0));
+    (0..NUMBER_OF_SAMPLES).for_each(|left| {
+        ((left + 1)..NUMBER_OF_SAMPLES).for_each(|right| {
+            let dot = points[left]
+                .iter()
+                .zip(points[right].iter())
+                .map(|(left, right)| left * right)
+                .sum::<f32>();
+            let similarity = 1.0 / (1.0 + (-dot).exp());
+            let (total, count) = if left % NUMBER_OF_CLUSTERS == right % NUMBER_OF_CLUSTERS {
+                &mut within
+            } else {
+                &mut between
+            };
+            *total += similarity;
+            *count += 1;
+        });
+    });
+    let (within, between) = (within.0 / within.1 as f32, between.0 / between.1 as f32);
+    assert!(within > between + 0.05);
+}
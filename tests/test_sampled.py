@@ -0,0 +1,16 @@
+from dimensional_reduction import SampledSigmoidDecomposition
+from sklearn.datasets import load_iris
+
+
+def test_sampled_negative_sampling():
+    iris = load_iris()
+    X = iris.data
+    model = SampledSigmoidDecomposition(
+        iterations=10,
+        learning_rate=0.01,
+        number_of_samples_per_point=5,
+        negative_samples=5,
+        negative_sampling_exponent=0.75
+    )
+    result = model.fit_transform(X)
+    assert result.shape == (X.shape[0], 2)
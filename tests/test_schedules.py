@@ -0,0 +1,35 @@
+import pytest
+from dimensional_reduction import SigmoidDecomposition, UMAPDecomposition, InvalidParameterError
+from sklearn.datasets import load_iris
+
+
+@pytest.mark.parametrize("schedule", ["constant", "linear", "exponential", "step", "cosine"])
+def test_sigmoid_learning_rate_schedules(schedule):
+    iris = load_iris()
+    X = iris.data
+    model = SigmoidDecomposition(
+        iterations=10,
+        learning_rate=0.01,
+        learning_rate_schedule=schedule,
+        warmup_iterations=2,
+    )
+    result = model.fit_transform(X)
+    assert result.shape == (X.shape[0], 2)
+
+
+@pytest.mark.parametrize("schedule", ["constant", "linear", "cosine"])
+def test_umap_learning_rate_schedules(schedule):
+    iris = load_iris()
+    X = iris.data
+    model = UMAPDecomposition(
+        iterations=10,
+        learning_rate_schedule=schedule,
+        warmup_iterations=2,
+    )
+    result = model.fit_transform(X)
+    assert result.shape == (X.shape[0], 2)
+
+
+def test_invalid_learning_rate_schedule():
+    with pytest.raises(InvalidParameterError):
+        UMAPDecomposition(learning_rate_schedule="unknown")
@@ -0,0 +1,127 @@
+use dimensional_reduction::basic_iterative_decomposition::{
+    BasicIterativeDecomposition, IterativeParameters,
+};
+use dimensional_reduction::traits::DimensionalReduction;
+use dimensional_reduction::*;
+use vec_rand::{random_f32, splitmix64};
+
+const NUMBER_OF_SAMPLES: usize = 200;
+const ORIGINAL_DIMENSION: usize = 8;
+const TARGET_DIMENSION: usize = 2;
+const SCHEDULES: [&str; 5] = ["constant", "linear", "exponential", "step", "cosine"];
+
+/// Returns a deterministic set of original samples.
+fn get_original() -> Vec<f32> {
+    (0..(NUMBER_OF_SAMPLES * ORIGINAL_DIMENSION) as u64)
+        .map(|i| random_f32(splitmix64(i)))
+        .collect()
+}
+
+fn get_decomposition(
+    iterations: usize,
+    learning_rate: f32,
+    schedule: LearningRateSchedule,
+) -> BasicIterativeDecomposition {
+    BasicIterativeDecomposition::new(
+        "Schedules test",
+        IterativeParameters {
+            iterations: Some(iterations),
+            learning_rate: Some(learning_rate),
+            learning_rate_schedule: Some(schedule),
+            deterministic: Some(true),
+            random_state: Some(42),
+            verbose: Some(false),
+            ..Default::default()
+        },
+    )
+    .unwrap()
+}
+
+/// Returns the embedding and loss history of the provided model
+/// with the provided number of threads.
+fn fit_transform<M>(model: &M, number_of_threads: usize) -> (Vec<f32>, Vec<f32>)
+where
+    M: DimensionalReduction + Sync,
+{
+    let original = get_original();
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(number_of_threads)
+        .build()
+        .unwrap()
+        .install(|| {
+            let mut target = vec![0.0_f32; NUMBER_OF_SAMPLES * TARGET_DIMENSION];
+            let loss_history = model
+                .fit_transform(&mut target, TARGET_DIMENSION, &original, ORIGINAL_DIMENSION)
+                .unwrap();
+            (target, loss_history)
+        })
+}
+
+/// Asserts that the provided model produces the same embedding and loss history
+/// regardless of the number of threads.
+fn assert_identical_across_thread_pools<M>(model: &M)
+where
+    M: DimensionalReduction + Sync,
+{
+    let expected = fit_transform(model, 1);
+    assert!(expected.0.iter().all(|value| value.is_finite()));
+    [2, 3, 8].into_iter().for_each(|number_of_threads| {
+        assert_eq!(fit_transform(model, number_of_threads), expected);
+    });
+}
+
+#[test]
+fn test_deterministic_learning_rate_schedules() {
+    for name in SCHEDULES {
+        let schedule = LearningRateSchedule::from_name(name, None, Some(3), Some(2)).unwrap();
+        let decomposition = || get_decomposition(10, 0.001, schedule);
+        assert_identical_across_thread_pools(&SigmoidDecomposition::from(decomposition()));
+        assert_identical_across_thread_pools(&SampledSigmoidDecomposition::from(decomposition()));
+        assert_identical_across_thread_pools(
+            &TSNEDecomposition::new(
+                decomposition(),
+                Some(10.0),
+                None,
+                Some(5),
+                None,
+                None,
+                None,
+                None,
+            )
+            .unwrap(),
+        );
+        assert_identical_across_thread_pools(
+            &UMAPDecomposition::new(
+                decomposition(),
+                UMAPCurve::default(),
+                None,
+                None,
+                None,
+                None,
+            )
+            .unwrap(),
+        );
+    }
+}
+
+#[test]
+fn test_learning_rate_schedules_scale_the_steps() {
+    let final_losses = SCHEDULES
+        .into_iter()
+        .map(|name| {
+            let schedule = LearningRateSchedule::from_name(name, None, Some(3), Some(2)).unwrap();
+            // MDS moves the embedding towards its Guttman transform by the scaled learning
+            // rate, and any fraction of this step never increases the stress.
+            let model = MDSDecomposition::new(get_decomposition(20, 1.0, schedule)).unwrap();
+            let (_, loss_history) = fit_transform(&model, 1);
+            loss_history.windows(2).for_each(|losses| {
+                assert!(losses[1] <= losses[0] * (1.0 + 1e-5), "{}", name);
+            });
+            loss_history[loss_history.len() - 1]
+        })
+        .collect::<Vec<f32>>();
+    // The full steps of the constant schedule decrease the stress the most.
+    final_losses[1..].iter().for_each(|&final_loss| {
+        assert!(final_losses[0] <= final_loss);
+    });
+}
@@ -0,0 +1,43 @@
+import numpy as np
+import pytest
+from dimensional_reduction import (
+    SigmoidDecomposition,
+    SampledSigmoidDecomposition,
+    BarnesHutSigmoidDecomposition,
+    TSNEDecomposition,
+    UMAPDecomposition,
+    PCADecomposition,
+    trustworthiness,
+)
+from scipy.sparse import csr_matrix, coo_matrix
+from sklearn.datasets import load_digits
+
+
+@pytest.mark.parametrize("model", [
+    SigmoidDecomposition(iterations=5, deterministic=True),
+    SampledSigmoidDecomposition(iterations=5, deterministic=True),
+    BarnesHutSigmoidDecomposition(iterations=5, deterministic=True),
+    TSNEDecomposition(iterations=5, deterministic=True),
+    UMAPDecomposition(iterations=5, deterministic=True),
+    PCADecomposition(),
+])
+def test_sparse_matches_dense(model):
+    X = load_digits().data[:200]
+    dense = model.fit_transform(X)
+    sparse = model.fit_transform(csr_matrix(X))
+    assert sparse.shape == (X.shape[0], 2)
+    assert np.allclose(dense, sparse, atol=1e-4)
+
+
+def test_other_sparse_formats_are_converted():
+    X = load_digits().data[:200]
+    model = SigmoidDecomposition(iterations=5)
+    result = model.fit_transform(coo_matrix(X.astype(np.float32)))
+    assert result.shape == (X.shape[0], 2)
+
+
+def test_sparse_preserves_neighbours():
+    X = csr_matrix(load_digits().data[:500])
+    model = TSNEDecomposition(iterations=200, perplexity=30)
+    result = model.fit_transform(X)
+    assert trustworthiness(X, result, 5) > 0.9
@@ -0,0 +1,199 @@
+use dimensional_reduction::basic_decomposition::BasicDecomposition;
+use dimensional_reduction::basic_iterative_decomposition::{
+    BasicIterativeDecomposition, IterativeParameters,
+};
+use dimensional_reduction::traits::DimensionalReduction;
+use dimensional_reduction::*;
+use vec_rand::{random_f32, splitmix64};
+
+const NUMBER_OF_CLUSTERS: usize = 4;
+const NUMBER_OF_SAMPLES: usize = 200;
+const ORIGINAL_DIMENSION: usize = 8;
+const TARGET_DIMENSION: usize = 2;
+
+/// Returns a deterministic set of original samples.
+fn get_original() -> Vec<f32> {
+    (0..(NUMBER_OF_SAMPLES * ORIGINAL_DIMENSION) as u64)
+        .map(|i| random_f32(splitmix64(i)))
+        .collect()
+}
+
+/// Returns the provided dense samples as a sparse matrix.
+fn to_sparse(original: &[f32]) -> CSRMatrix<f32> {
+    let (mut indptr, mut indices, mut data) = (vec![0], Vec::new(), Vec::new());
+    original.chunks(ORIGINAL_DIMENSION).for_each(|sample| {
+        sample
+            .iter()
+            .enumerate()
+            .filter(|(_, &value)| value != 0.0)
+            .for_each(|(index, &value)| {
+                indices.push(index);
+                data.push(value);
+            });
+        indptr.push(indices.len());
+    });
+    CSRMatrix::new(indptr, indices, data, ORIGINAL_DIMENSION).unwrap()
+}
+
+fn get_decomposition(iterations: usize) -> BasicIterativeDecomposition {
+    BasicIterativeDecomposition::new(
+        "Sparse test",
+        IterativeParameters {
+            iterations: Some(iterations),
+            learning_rate: Some(0.001),
+            deterministic: Some(true),
+            random_state: Some(42),
+            verbose: Some(false),
+            ..Default::default()
+        },
+    )
+    .unwrap()
+}
+
+/// Asserts that the provided model produces the same embedding
+/// when the original samples are provided as a sparse matrix.
+fn assert_sparse_matches_dense<M>(model: &M)
+where
+    M: DimensionalReduction,
+{
+    // We zero most of the features, so that the samples are actually sparse.
+    let original = get_original()
+        .into_iter()
+        .map(|value| if value < 0.7 { 0.0 } else { value })
+        .collect::<Vec<f32>>();
+    let sparse = to_sparse(&original);
+
+    let mut dense_target = vec![0.0_f32; NUMBER_OF_SAMPLES * TARGET_DIMENSION];
+    let mut sparse_target = dense_target.clone();
+    model
+        .fit_transform(
+            &mut dense_target,
+            TARGET_DIMENSION,
+            &original,
+            ORIGINAL_DIMENSION,
+        )
+        .unwrap();
+    model
+        .fit_transform_matrix(&mut sparse_target, TARGET_DIMENSION, &sparse)
+        .unwrap();
+
+    dense_target
+        .iter()
+        .zip(sparse_target.iter())
+        .for_each(|(dense, sparse)| {
+            assert!(
+                (dense - sparse).abs() <= 1e-4 * dense.abs().max(1.0),
+                "{} {}",
+                dense,
+                sparse
+            );
+        });
+}
+
+#[test]
+fn test_sparse_input_matches_dense_input() {
+    assert_sparse_matches_dense(&SigmoidDecomposition::from(get_decomposition(10)));
+    assert_sparse_matches_dense(
+        &SampledSigmoidDecomposition::new(
+            get_decomposition(10),
+            Cosine,
+            SigmoidDot,
+            Some(3),
+            Some(2),
+            Some(5),
+            Some(0.75),
+        )
+        .unwrap(),
+    );
+    assert_sparse_matches_dense(
+        &BarnesHutSigmoidDecomposition::new(
+            get_decomposition(10),
+            StandardizedDot,
+            SigmoidDot,
+            None,
+            Some(0.5),
+            Some(8),
+        )
+        .unwrap(),
+    );
+    assert_sparse_matches_dense(
+        &TSNEDecomposition::new(
+            get_decomposition(10),
+            Some(10.0),
+            None,
+            Some(5),
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap(),
+    );
+    assert_sparse_matches_dense(
+        &UMAPDecomposition::new(
+            get_decomposition(10),
+            UMAPCurve::default(),
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap(),
+    );
+    assert_sparse_matches_dense(&PCADecomposition::new(
+        BasicDecomposition::new("PCA", Some(42), Some(false)).unwrap(),
+        None,
+    ));
+}
+
+#[test]
+fn test_sparse_input_preserves_clusters() {
+    // The sample with number i belongs to the cluster i % NUMBER_OF_CLUSTERS,
+    // and only has non-zero values in the two features of its cluster.
+    let original = (0..NUMBER_OF_SAMPLES * ORIGINAL_DIMENSION)
+        .map(|i| {
+            if i % ORIGINAL_DIMENSION / 2 == i / ORIGINAL_DIMENSION % NUMBER_OF_CLUSTERS {
+                1.0 + random_f32(splitmix64(i as u64))
+            } else {
+                0.0
+            }
+        })
+        .collect::<Vec<f32>>();
+    let sparse = to_sparse(&original);
+    assert_eq!(sparse.get_indices().len(), NUMBER_OF_SAMPLES * 2);
+
+    let mut target = vec![0.0_f32; NUMBER_OF_SAMPLES * TARGET_DIMENSION];
+    TSNEDecomposition::new(
+        get_decomposition(200),
+        Some(10.0),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+    )
+    .unwrap()
+    .fit_transform_matrix(&mut target, TARGET_DIMENSION, &sparse)
+    .unwrap();
+
+    // The nearest embedded neighbour of almost every sample is in its cluster.
+    let points = target.chunks(TARGET_DIMENSION).collect::<Vec<&[f32]>>();
+    let preserved = (0..NUMBER_OF_SAMPLES)
+        .filter(|&sample_number| {
+            let distance = |other: usize| {
+                points[sample_number]
+                    .iter()
+                    .zip(points[other].iter())
+                    .map(|(left, right)| (left - right).powi(2))
+                    .sum::<f32>()
+            };
+            let nearest = (0..NUMBER_OF_SAMPLES)
+                .filter(|&other| other != sample_number)
+                .min_by(|&left, &right| distance(left).total_cmp(&distance(right)))
+                .unwrap();
+            nearest % NUMBER_OF_CLUSTERS == sample_number % NUMBER_OF_CLUSTERS
+        })
+        .count();
+    assert!(preserved as f64 > 0.95 * NUMBER_OF_SAMPLES as f64);
+}
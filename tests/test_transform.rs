@@ -0,0 +1,119 @@
+use dimensional_reduction::basic_iterative_decomposition::{
+    BasicIterativeDecomposition, IterativeParameters,
+};
+use dimensional_reduction::traits::DimensionalReduction;
+use dimensional_reduction::*;
+use vec_rand::{random_f32, splitmix64};
+
+const NUMBER_OF_CLUSTERS: usize = 4;
+const NUMBER_OF_SAMPLES: usize = 200;
+const NUMBER_OF_NEW_SAMPLES: usize = 40;
+const ORIGINAL_DIMENSION: usize = 8;
+const TARGET_DIMENSION: usize = 2;
+
+/// Returns a deterministic set of original samples, where the sample with
+/// number i belongs to the cluster i % NUMBER_OF_CLUSTERS.
+///
+/// # Arguments
+/// * `number_of_samples`: The number of samples to return.
+/// * `random_state`: The random state used to scatter the samples around their center.
+fn get_original(number_of_samples: usize, random_state: u64) -> Vec<f32> {
+    (0..number_of_samples * ORIGINAL_DIMENSION)
+        .map(|i| {
+            let cluster = i / ORIGINAL_DIMENSION % NUMBER_OF_CLUSTERS;
+            let center = if cluster == i % ORIGINAL_DIMENSION {
+                4.0
+            } else {
+                0.0
+            };
+            center + random_f32(splitmix64(random_state + i as u64))
+        })
+        .collect()
+}
+
+/// Returns a model comparing the samples by their distances, so that the
+/// embedded samples of the same cluster are close to each other.
+fn get_model() -> SigmoidDecomposition<GaussianRBF, StudentT> {
+    SigmoidDecomposition::new(
+        BasicIterativeDecomposition::new(
+            "Transform test",
+            IterativeParameters {
+                iterations: Some(200),
+                learning_rate: Some(0.01),
+                deterministic: Some(true),
+                random_state: Some(42),
+                verbose: Some(false),
+                ..Default::default()
+            },
+        )
+        .unwrap(),
+        GaussianRBF::new(Some(1.5)).unwrap(),
+        StudentT,
+    )
+}
+
+#[test]
+fn test_transform_places_new_samples_near_their_cluster() {
+    let fitted = get_model()
+        .fit::<f32, f32>(
+            TARGET_DIMENSION,
+            &get_original(NUMBER_OF_SAMPLES, 0),
+            ORIGINAL_DIMENSION,
+        )
+        .unwrap();
+    let training_target = fitted.get_target().to_vec();
+
+    let mut target = vec![0.0_f32; NUMBER_OF_NEW_SAMPLES * TARGET_DIMENSION];
+    let loss_history = fitted
+        .transform(&mut target, &get_original(NUMBER_OF_NEW_SAMPLES, 1 << 32))
+        .unwrap();
+    assert!(loss_history.iter().all(|loss| loss.is_finite()));
+    assert!(target.iter().all(|value| value.is_finite()));
+    // The embedding of the training samples is frozen.
+    assert_eq!(fitted.get_target(), training_target.as_slice());
+
+    // Every new sample is, on average, closer to the training samples
+    // of its own cluster than to those of any other cluster.
+    target
+        .chunks(TARGET_DIMENSION)
+        .enumerate()
+        .for_each(|(sample_number, sample)| {
+            let mut distances = vec![0.0_f32; NUMBER_OF_CLUSTERS];
+            training_target
+                .chunks(TARGET_DIMENSION)
+                .enumerate()
+                .for_each(|(training_sample_number, training_sample)| {
+                    distances[training_sample_number % NUMBER_OF_CLUSTERS] += sample
+                        .iter()
+                        .zip(training_sample.iter())
+                        .map(|(left, right)| (left - right).powi(2))
+                        .sum::<f32>()
+                        .sqrt();
+                });
+            let cluster = sample_number % NUMBER_OF_CLUSTERS;
+            assert!(distances
+                .iter()
+                .enumerate()
+                .all(|(other, &distance)| other == cluster || distances[cluster] < distance));
+        });
+}
+
+#[test]
+fn test_transform_rejects_incompatible_samples() {
+    let fitted = get_model()
+        .fit::<f32, f32>(
+            TARGET_DIMENSION,
+            &get_original(NUMBER_OF_SAMPLES, 0),
+            ORIGINAL_DIMENSION,
+        )
+        .unwrap();
+    let mut target = vec![0.0_f32; TARGET_DIMENSION];
+    assert!(matches!(
+        fitted.transform(&mut target, &[1.0_f32; ORIGINAL_DIMENSION + 1]),
+        Err(DimensionalReductionError::IncompatibleDimension { .. })
+    ));
+    assert!(matches!(
+        fitted.transform(&mut target, &[f32::NAN; ORIGINAL_DIMENSION]),
+        Err(DimensionalReductionError::NonFiniteValue { .. })
+    ));
+}
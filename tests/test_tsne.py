@@ -0,0 +1,28 @@
+from dimensional_reduction import TSNEDecomposition, trustworthiness
+from sklearn.datasets import load_iris
+
+
+def test_tsne():
+    iris = load_iris()
+    X = iris.data
+    model = TSNEDecomposition(
+        iterations=100,
+        perplexity=10
+    )
+    result, loss_history = model.fit_transform(X, return_loss_history=True)
+    assert result.shape == (X.shape[0], 2)
+    assert len(loss_history) == 100
+
+
+def test_tsne_approximate_neighbour_search():
+    iris = load_iris()
+    X = iris.data
+    model = TSNEDecomposition(
+        iterations=100,
+        perplexity=10,
+        neighbour_search="approximate",
+        nn_descent_iterations=5
+    )
+    result = model.fit_transform(X)
+    assert result.shape == (X.shape[0], 2)
+    assert trustworthiness(X, result, 5) > 0.9